@@ -1,26 +1,43 @@
 use ark_bls12_381::Bls12_381 as F;
-use ark_ec::{
-    pairing::{Pairing, PairingOutput},
-    CurveGroup,
-};
+use ark_ec::{pairing::Pairing, CurveGroup};
 use ark_std::{rand::Rng, test_rng, UniformRand, Zero};
 use criterion::{criterion_group, criterion_main, Criterion};
-use gs_ppe::{CommitmentKeys, Equation, Matrix, Proof, Variable};
-use std::ops::Mul;
+use gs_ppe::{
+    setup, setup_batch,
+    test_utils::{random_instance, random_instance_with_gamma, x_variable_vec, y_variable_vec},
+    CommitmentKeys, Matrix, Proof, SetupInstance,
+};
 use std::time::Duration;
 
-type G1 = <F as Pairing>::G1;
-type G2 = <F as Pairing>::G2;
 type Fr = <F as Pairing>::ScalarField;
 
 criterion_group! {
     name = gs_ppe;
     config = Criterion::default().sample_size(10).measurement_time(Duration::from_secs(10));
-    targets = bench_commit_g1, bench_commit_g2, bench_prove, bench_verify
+    targets = bench_rand_batch, bench_commit_g1, bench_commit_g2, bench_prove, bench_prove_sparse, bench_verify, bench_setup_batch
 }
 
 criterion_main!(gs_ppe);
 
+fn bench_rand_batch(c: &mut Criterion) {
+    let rng = &mut test_rng();
+
+    let mut group = c.benchmark_group("bench_rand_batch");
+
+    for count in [10, 50, 100] {
+        group.bench_with_input(format!("loop, count: {}", count), &count, |b, &count| {
+            b.iter(|| {
+                (0..count)
+                    .map(|_| CommitmentKeys::<F>::rand(rng))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(format!("batch, count: {}", count), &count, |b, &count| {
+            b.iter(|| CommitmentKeys::<F>::rand_batch(rng, count))
+        });
+    }
+}
+
 fn bench_commit_g1(c: &mut Criterion) {
     let rng = &mut test_rng();
 
@@ -28,11 +45,11 @@ fn bench_commit_g1(c: &mut Criterion) {
 
     for size in [5, 10, 20] {
         let cks = CommitmentKeys::<F>::rand(rng);
-        let x_vec = x_variable_vec(rng, size);
+        let x_vec = x_variable_vec::<F, _>(rng, size);
         group.bench_with_input(format!("size: {}", size), &x_vec, |b, x_vec| {
             b.iter(|| {
                 for x in x_vec {
-                    cks.u.commit(&x);
+                    cks.u.commit(x);
                 }
             })
         });
@@ -46,11 +63,11 @@ fn bench_commit_g2(c: &mut Criterion) {
 
     for size in [5, 10, 20] {
         let cks = CommitmentKeys::<F>::rand(rng);
-        let y_vec = y_variable_vec(rng, size);
+        let y_vec = y_variable_vec::<F, _>(rng, size);
         group.bench_with_input(format!("size: {}", size), &y_vec, |b, y_vec| {
             b.iter(|| {
                 for y in y_vec {
-                    cks.v.commit(&y);
+                    cks.v.commit(y);
                 }
             })
         });
@@ -64,7 +81,7 @@ fn bench_prove(c: &mut Criterion) {
 
     for size in [5, 10, 20] {
         let cks = CommitmentKeys::<F>::rand(rng);
-        let (equation, x, y) = prepare_prove(rng, size, size);
+        let (equation, x, y) = random_instance::<F, _>(rng, size, size);
         group.bench_with_input(
             format!("size: {}", size),
             &(cks, equation, x, y),
@@ -77,6 +94,37 @@ fn bench_prove(c: &mut Criterion) {
     }
 }
 
+fn bench_prove_sparse(c: &mut Criterion) {
+    let rng = &mut test_rng();
+
+    let mut group = c.benchmark_group("bench_prove_sparse");
+
+    let size = 20;
+    for density in [10, 100] {
+        let cks = CommitmentKeys::<F>::rand(rng);
+        let gamma = sparse_gamma(rng, size, size, density);
+        let (equation, x, y) = random_instance_with_gamma::<F, _>(rng, gamma);
+        group.bench_with_input(
+            format!("dense, density: {}%", density),
+            &(&cks, &equation, &x, &y),
+            |b, (cks, equation, x, y)| {
+                b.iter(|| {
+                    Proof::new(rng, cks, equation, x, y);
+                })
+            },
+        );
+        group.bench_with_input(
+            format!("sparse, density: {}%", density),
+            &(&cks, &equation, &x, &y),
+            |b, (cks, equation, x, y)| {
+                b.iter(|| {
+                    Proof::new_sparse(rng, cks, equation, x, y);
+                })
+            },
+        );
+    }
+}
+
 fn bench_verify(c: &mut Criterion) {
     let rng = &mut test_rng();
 
@@ -84,7 +132,7 @@ fn bench_verify(c: &mut Criterion) {
 
     for size in [5, 10, 20] {
         let cks = CommitmentKeys::<F>::rand(rng);
-        let (equation, x, y) = prepare_prove(rng, size, size);
+        let (equation, x, y) = random_instance::<F, _>(rng, size, size);
 
         let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
         let d = y.iter().map(|y_i| cks.v.commit(y_i)).collect::<Vec<_>>();
@@ -102,66 +150,55 @@ fn bench_verify(c: &mut Criterion) {
     }
 }
 
-// ... utility functions ...
-
-/// Returns a vector of `size` random `Variable<G1>`.
-fn x_variable_vec(rng: &mut impl Rng, size: usize) -> Vec<Variable<G1>> {
-    (0..size)
-        .map(|_| {
-            let value = G1::rand(rng);
-            Variable::new(rng, value.into_affine())
-        })
-        .collect()
-}
+fn bench_setup_batch(c: &mut Criterion) {
+    let rng = &mut test_rng();
 
-/// Returns a vector of `size` random `Variable<G2>`.
-fn y_variable_vec(rng: &mut impl Rng, size: usize) -> Vec<Variable<G2>> {
-    (0..size)
-        .map(|_| {
-            let value = G2::rand(rng);
-            Variable::new(rng, value.into_affine())
+    let mut group = c.benchmark_group("bench_setup_batch");
+
+    let count = 100;
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let instances = (0..count)
+        .map(|_| SetupInstance::<F> {
+            ay: y_variable_vec::<F, _>(rng, 2)
+                .into_iter()
+                .map(|y_j| (<F as Pairing>::G1::rand(rng).into_affine(), y_j))
+                .collect(),
+            xb: x_variable_vec::<F, _>(rng, 2)
+                .into_iter()
+                .map(|x_i| (x_i, <F as Pairing>::G2::rand(rng).into_affine()))
+                .collect(),
+            gamma: Matrix::<Fr>::rand(rng, 2, 2),
         })
-        .collect()
-}
-
-/// Prepare required input arguments to `prove` method, i.e. returns a random equation and
-/// its corresponding `x` and `y` variables.
-fn prepare_prove(
-    rng: &mut impl Rng,
-    m: usize,
-    n: usize,
-) -> (Equation<F>, Vec<Variable<G1>>, Vec<Variable<G2>>) {
-    let gamma = Matrix::<Fr>::rand(rng, m, n);
-    let x = x_variable_vec(rng, m);
-    let y = y_variable_vec(rng, n);
-    let a = (0..n)
-        .map(|_| G1::rand(rng).into_affine())
-        .collect::<Vec<_>>();
-    let b = (0..m)
-        .map(|_| G2::rand(rng).into_affine())
         .collect::<Vec<_>>();
+    group.bench_with_input(
+        format!("loop, count: {}", count),
+        &instances,
+        |b, instances| {
+            b.iter(|| {
+                for instance in instances {
+                    setup(rng, &cks, &instance.ay, &instance.xb, &instance.gamma);
+                }
+            })
+        },
+    );
+    group.bench_with_input(
+        format!("batch, count: {}", count),
+        &instances,
+        |b, instances| b.iter(|| setup_batch(rng, &cks, instances.clone())),
+    );
+}
 
-    let ay_product = a
-        .iter()
-        .zip(y.iter())
-        .fold(PairingOutput::zero(), |acc, (a, y)| {
-            acc + F::pairing(a, y.value)
-        });
-    let xb_product = x
-        .iter()
-        .zip(b.iter())
-        .fold(PairingOutput::zero(), |acc, (x, b)| {
-            acc + F::pairing(x.value, b)
-        });
+// ... utility functions ...
 
-    let mut xy_product = PairingOutput::zero();
-    for (j, y_j) in y.iter().enumerate() {
-        for (i, x_i) in x.iter().enumerate() {
-            xy_product += F::pairing(x_i.value, y_j.value).mul(gamma[(i, j)]);
+/// Returns a random `m x n` matrix in which about `density` percent of the entries are non-zero.
+fn sparse_gamma(rng: &mut impl Rng, m: usize, n: usize, density: u32) -> Matrix<Fr> {
+    let mut gamma = Matrix::<Fr>::rand(rng, m, n);
+    for i in 0..m {
+        for j in 0..n {
+            if rng.gen_range(0..100) >= density {
+                gamma[(i, j)] = Fr::zero();
+            }
         }
     }
-    let target = ay_product + xb_product + xy_product;
-    let equation = Equation::<F>::new(a, b, gamma, target);
-
-    (equation, x, y)
+    gamma
 }
@@ -1,10 +1,21 @@
 //! Defines the struct [Com], the `SXDH Commitments`` defined in section 6.2 in the paper [Fuc10](https://eprint.iacr.org/2010/233.pdf).
 
-use ark_ec::CurveGroup;
-use ark_std::rand::Rng;
-use std::ops::{Add, Mul};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use ark_std::{rand::Rng, Zero};
+use std::{
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+    iter::Sum,
+    ops::{Add, Mul, Neg, Sub},
+};
 
-use crate::{commit::CommitmentKey, randomness::Randomness};
+use crate::{
+    commit::CommitmentKey, randomness::Randomness, transcript, variable::fmt_short_hex, Variable,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Com<G: CurveGroup>(pub G::Affine, pub G::Affine);
@@ -29,8 +40,139 @@ impl<G: CurveGroup> Com<G> {
 
         (original, Randomness(r1, r2))
     }
+
+    /// The trivial commitment `Com(ck, X, 0) = (0, X)` to the public value `X` with zero randomness, which is
+    /// the same under any commitment key. See [Variable::public].
+    pub fn trivial(value: G::Affine) -> Self {
+        Com(G::Affine::zero(), value)
+    }
+
+    /// Checks that this commitment opens to the variable `x`, i.e. it equals `Com(ck, X, r)` where `X` and `r`
+    /// are the value and the randomness of `x`.
+    pub fn verify_opening(&self, ck: &CommitmentKey<G>, x: &Variable<G>) -> bool {
+        ck.commit(x) == *self
+    }
+
+    /// Checks that this commitment opens to the identity with the randomness `rand`, i.e. it equals
+    /// `Com(ck, 0, rand)`. For example, `c - Com(ck, X, r)` is a commitment to zero for `c = Com(ck, X, r')`
+    /// with the randomness `r' - r`.
+    pub fn is_zero_commitment(&self, ck: &CommitmentKey<G>, rand: &Randomness<G>) -> bool {
+        self.verify_opening(ck, &Variable::with_randomness(G::Affine::zero(), *rand))
+    }
+
+    /// Checks that both points of this commitment are on the curve and in the prime-order subgroup.
+    pub fn is_valid(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Returns the canonical transcript encoding of this commitment. See [transcript] for the details of the encoding.
+    pub fn to_transcript_bytes(&self) -> Vec<u8> {
+        let mut bytes = transcript::header(b"Com");
+        self.append_transcript_body(&mut bytes);
+        bytes
+    }
+
+    /// Appends the body `c1 || c2` of the transcript encoding.
+    pub(crate) fn append_transcript_body(&self, bytes: &mut Vec<u8>) {
+        transcript::append_element(bytes, &self.0);
+        transcript::append_element(bytes, &self.1);
+    }
+}
+
+impl<G: CurveGroup> Valid for Com<G> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.0.check()?;
+        self.1.check()
+    }
+}
+
+/// Serializes the commitment as `c1 || c2`.
+impl<G: CurveGroup> CanonicalSerialize for Com<G> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.0.serialize_with_mode(&mut writer, compress)?;
+        self.1.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.0.serialized_size(compress) + self.1.serialized_size(compress)
+    }
+}
+
+/// Deserializes the commitment `c1 || c2`. The points are checked by [Valid] if `validate` is [Validate::Yes].
+impl<G: CurveGroup> CanonicalDeserialize for Com<G> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let c1 = G::Affine::deserialize_with_mode(&mut reader, compress, Validate::No)?;
+        let c2 = G::Affine::deserialize_with_mode(&mut reader, compress, Validate::No)?;
+        let com = Com(c1, c2);
+        if let Validate::Yes = validate {
+            com.check()?;
+        }
+        Ok(com)
+    }
 }
 
+/// Hashes the compressed encoding `c1 || c2`, so that equal commitments hash equally regardless of how they
+/// were computed, e.g. for deduplication in a `HashSet`.
+///
+/// `Ord` is intentionally not implemented, as the group elements have no meaningful order. To key a `BTreeMap`,
+/// use the bytes of [Com::to_transcript_bytes] instead.
+impl<G: CurveGroup> Hash for Com<G> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes)
+            .expect("serializing into a vector never fails");
+        bytes.hash(state);
+    }
+}
+
+/// Displays the first 8 bytes of the compressed encodings of `c1` and `c2` in hex, e.g. `Com(8d0e2c1f9a3b7e44..., a1b2c3d4e5f60718...)`.
+impl<G: CurveGroup> Display for Com<G> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Com(")?;
+        fmt_short_hex(f, &self.0)?;
+        write!(f, ", ")?;
+        fmt_short_hex(f, &self.1)?;
+        write!(f, ")")
+    }
+}
+
+/// The trivial commitment `Com(ck, 0, 0)` to the identity with zero randomness, which is the same under any
+/// commitment key. It is the neutral element of the homomorphic addition.
+///
+/// Padding the commitments `c` (or `d`) of a proof system with zero commitments keeps the verification
+/// semantics, provided that the equation is padded with the same number of variables: the pairings of the
+/// zero commitments are the identity, so the same proof verifies against the padded equation.
+impl<G: CurveGroup> Zero for Com<G> {
+    fn zero() -> Self {
+        Com(G::Affine::zero(), G::Affine::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero() && self.1.is_zero()
+    }
+}
+
+impl<G: CurveGroup> Sum for Com<G> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Com::zero(), Add::add)
+    }
+}
+
+impl<'a, G: CurveGroup> Sum<&'a Com<G>> for Com<G> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Homomorphic addition of two commitments, i.e. `Com(ck, X, r) + Com(ck, X', r') = Com(ck, X + X', r + r')`.
 impl<G: CurveGroup> Add for Com<G> {
     type Output = Self;
 
@@ -39,6 +181,35 @@ impl<G: CurveGroup> Add for Com<G> {
     }
 }
 
+impl<G: CurveGroup> Neg for Com<G> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Com(
+            self.0.into_group().neg().into_affine(),
+            self.1.into_group().neg().into_affine(),
+        )
+    }
+}
+
+/// Homomorphic subtraction of two commitments, i.e. `Com(ck, X, r) - Com(ck, X', r') = Com(ck, X - X', r - r')`.
+impl<G: CurveGroup> Sub for Com<G> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+/// Homomorphic scalar multiplication of a commitment, i.e. `Com(ck, X, r) * ρ = Com(ck, X * ρ, r * ρ)`.
+impl<G: CurveGroup> Mul<G::ScalarField> for Com<G> {
+    type Output = Self;
+
+    fn mul(self, rhs: G::ScalarField) -> Self {
+        Com(self.0.mul(rhs).into(), self.1.mul(rhs).into())
+    }
+}
+
 /// A tuple of a commitment and its randomness. It is used in Proof Adaption as a the input
 /// `(c, r)` or `(d, s)` in the proof adaption function `RdProof`.
 pub type ComRandomness<G> = (Com<G>, Randomness<G>);
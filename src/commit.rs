@@ -1,10 +1,24 @@
 //! Defines the struct [CommitmentKeys], the commitment key `ck` for `SXDH Commitments`` defined in section 6.2 in the paper [Fuc10](https://eprint.iacr.org/2010/233.pdf).
 
-use ark_ec::{pairing::Pairing, CurveGroup};
-use ark_std::{rand::Rng, One, UniformRand};
-use std::ops::{Mul, Sub};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_serialize::Valid;
+use ark_std::{
+    rand::{Rng, SeedableRng},
+    One, UniformRand, Zero,
+};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::ops::{Add, Mul, Sub};
 
-use crate::{com::Com, randomness::Randomness, variable::Variable, ExtractKey};
+use crate::{
+    com::Com,
+    dual::Dual,
+    metrics::{self, Operation},
+    randomness::Randomness,
+    transcript,
+    variable::Variable,
+    ExtractKey,
+};
 
 /// Contains commitment keys `u` and `v` for the `SXDH Commitments`, where
 /// `u` and `v` belong to Group G1 and G2 respectively.
@@ -33,15 +47,49 @@ impl<E: Pairing> CommitmentKeys<E> {
         let a2 = E::ScalarField::rand(rng);
         let t1 = E::ScalarField::rand(rng);
         let t2 = E::ScalarField::rand(rng);
-        CommitmentKeys::new(g1, g2, a1, a2, t1, t2)
+        CommitmentKeys::from_trapdoor(g1, g2, a1, a2, t1, t2)
+    }
+
+    /// Constructs the commitment keys from the keys `u` in G1 and `v` in G2.
+    pub fn from_parts(u: CommitmentKey<E::G1>, v: CommitmentKey<E::G2>) -> Self {
+        Self { u, v }
+    }
+
+    /// Returns the keys `u` in G1 and `v` in G2.
+    pub fn into_parts(self) -> (CommitmentKey<E::G1>, CommitmentKey<E::G2>) {
+        (self.u, self.v)
+    }
+
+    /// Returns the commitment keys over the pairing [Dual] with the groups G1 and G2 exchanged, i.e. the keys
+    /// `u` and `v` are exchanged.
+    pub fn transpose(&self) -> CommitmentKeys<Dual<E>> {
+        CommitmentKeys {
+            u: self.v,
+            v: self.u,
+        }
+    }
+
+    /// Returns the generator `g1` of the keys, i.e. `u11`, as `u1 = (g1, g1^a1)` in both the standard and
+    /// the perfectly hiding setup.
+    #[inline]
+    pub fn generator_g1(&self) -> <E as Pairing>::G1Affine {
+        self.u.0 .0
+    }
+
+    /// Returns the generator `g2` of the keys, i.e. `v11`, as `v1 = (g2, g2^a2)` in both the standard and
+    /// the perfectly hiding setup.
+    #[inline]
+    pub fn generator_g2(&self) -> <E as Pairing>::G2Affine {
+        self.v.0 .0
     }
 
     /// Generates random commitment keys for standard setup of Commitment Scheme,
-    /// and in addition, returns the extract key.
-    pub fn rand_ex<R: Rng>(rng: &mut R) -> (CommitmentKeys<E>, ExtractKey<E>) {
+    /// and in addition, returns the extract key bound together in [BoundCommitmentKeys].
+    pub fn rand_ex<R: Rng>(rng: &mut R) -> BoundCommitmentKeys<E> {
         let g1 = E::G1Affine::rand(rng);
         let g2 = E::G2Affine::rand(rng);
-        Self::setup_ex(rng, g1, g2)
+        let (cks, ek) = Self::setup_ex(rng, g1, g2);
+        BoundCommitmentKeys { cks, ek }
     }
 
     /// Construct commitment keys for standard setup and in addition returns the extract key,
@@ -51,13 +99,25 @@ impl<E: Pairing> CommitmentKeys<E> {
         g1: <E as Pairing>::G1Affine,
         g2: <E as Pairing>::G2Affine,
     ) -> (CommitmentKeys<E>, ExtractKey<E>) {
+        let (cks, trapdoor) = Self::setup_full(rng, g1, g2);
+        (cks, ExtractKey::from(&trapdoor))
+    }
+
+    /// Construct commitment keys for standard setup and in addition returns the full [Trapdoor]
+    /// `(a1, a2, t1, t2)`, given the generators `g1` and `g2`. The extract key is derived from the trapdoor
+    /// by [From].
+    pub fn setup_full<R: Rng>(
+        rng: &mut R,
+        g1: <E as Pairing>::G1Affine,
+        g2: <E as Pairing>::G2Affine,
+    ) -> (CommitmentKeys<E>, Trapdoor<E>) {
         let a1 = E::ScalarField::rand(rng);
         let a2 = E::ScalarField::rand(rng);
         let t1 = E::ScalarField::rand(rng);
         let t2 = E::ScalarField::rand(rng);
         (
-            CommitmentKeys::new(g1, g2, a1, a2, t1, t2),
-            ExtractKey(a1, a2),
+            CommitmentKeys::from_trapdoor(g1, g2, a1, a2, t1, t2),
+            Trapdoor { a1, a2, t1, t2 },
         )
     }
 
@@ -71,6 +131,10 @@ impl<E: Pairing> CommitmentKeys<E> {
 
     /// Generates random commitment keys for perfectly hiding setup (also indistinguishable
     /// by SDXH) of Commitment Scheme, given the generators `g1` and `g2`.
+    ///
+    /// Note that the structure `u2 = u1^t1 * (1, g1^-1)` (and similarly for `v`) cannot be verified without the
+    /// trapdoor `t1` (resp. `t2`): a public check would distinguish these keys from the keys of the standard
+    /// setup, contradicting the SXDH assumption on which the witness indistinguishability relies.
     pub fn setup_wi<R: Rng>(
         rng: &mut R,
         g1: <E as Pairing>::G1Affine,
@@ -80,11 +144,175 @@ impl<E: Pairing> CommitmentKeys<E> {
         let a2 = E::ScalarField::rand(rng);
         let t1 = E::ScalarField::rand(rng);
         let t2 = E::ScalarField::rand(rng);
-        CommitmentKeys::new_wi(g1, g2, a1, a2, t1, t2)
+        CommitmentKeys::from_trapdoor_wi(g1, g2, a1, a2, t1, t2)
+    }
+
+    /// Generates `count` independent random commitment keys for standard setup of Commitment Scheme, i.e. the
+    /// same as calling [CommitmentKeys::rand] `count` times. All the scalars are sampled at once, and the points
+    /// are normalized to affine representation in a batch.
+    pub fn rand_batch<R: Rng>(rng: &mut R, count: usize) -> Vec<CommitmentKeys<E>> {
+        Self::new_batch(rng, count, false)
     }
 
-    /// Implements the `Setup` function in section 6.2 of the paper.
-    fn new(
+    /// Generates `count` independent random commitment keys for perfectly hiding setup of Commitment Scheme, i.e.
+    /// the same as calling [CommitmentKeys::rand_wi] `count` times. All the scalars are sampled at once, and the
+    /// points are normalized to affine representation in a batch.
+    pub fn rand_wi_batch<R: Rng>(rng: &mut R, count: usize) -> Vec<CommitmentKeys<E>> {
+        Self::new_batch(rng, count, true)
+    }
+
+    /// Checks the structural sanity of the commitment keys, e.g. after loading them from a file, so that a corrupted
+    /// key is rejected early instead of producing bogus verification results. It checks that:
+    /// - all the eight points are on the curve, in the prime-order subgroup and not the identity, which also
+    ///   ensures that `u11` and `v11` are generators of G1 and G2.
+    /// - the pairs are not duplicates, i.e. `u1 != u2` and `v1 != v2`.
+    ///
+    /// It does not guarantee that the keys are generated honestly, or whether they are of the standard
+    /// (binding) or the perfectly hiding setup, which are indistinguishable under the SXDH assumption.
+    ///
+    /// Note that no pairing equation between the components of `u` and `v` can check the keys further without
+    /// the trapdoor: the pairings `e(u_ij, v_kl)` are `e(g1, g2)` raised to the products of the exponents of `u`
+    /// (`1, a1, t1, a1*t1` or `a1*t1 - 1`) and of `v` (the same in `a2`, `t2`), which satisfy no relation for
+    /// independent scalars. A relation within `u` (or `v`) alone, such as `u2 = u1^t1`, would decide DDH in G1
+    /// (or G2), which is the SXDH assumption itself. Honestly generated keys are audited with the trapdoor
+    /// instead, e.g. by comparing them with [CommitmentKeys::from_trapdoor].
+    ///
+    /// Returns [InvalidCommitmentKeys] of the first failed check.
+    pub fn validate(&self) -> Result<(), InvalidCommitmentKeys> {
+        if let Some(index) = invalid_point(&self.u) {
+            return Err(InvalidCommitmentKeys::U(index));
+        }
+        if let Some(index) = invalid_point(&self.v) {
+            return Err(InvalidCommitmentKeys::V(index));
+        }
+        if self.u.0 == self.u.1 {
+            return Err(InvalidCommitmentKeys::DegenerateU);
+        }
+        if self.v.0 == self.v.1 {
+            return Err(InvalidCommitmentKeys::DegenerateV);
+        }
+        Ok(())
+    }
+
+    /// Derives the commitment keys for the `index`, e.g. one key for each attribute in a multi-attribute system.
+    ///
+    /// The derived keys are `u' = (u1, u2 * u1^δ1)` and `v' = (v1, v2 * v1^δ2)`, where the scalars `δ1`, `δ2`
+    /// are drawn from a ChaCha20 stream seeded by the PRF `SHA-256(domain || master key || index)`, with the master
+    /// key in its [transcript encoding](CommitmentKeys::to_transcript_bytes) and the `index` as a little-endian
+    /// `u64`. i.e. the parameters `t1`, `t2` become `t1 + δ1`, `t2 + δ2`, while `g1`, `g2`, `a1` and `a2` remain
+    /// the same. Therefore,
+    /// - the derived keys are of the same kind (binding or perfectly hiding) as these keys.
+    /// - the extract key of these keys also extracts the commitments under the derived keys.
+    /// - the scalars `δ1`, `δ2` do not need to be secret, and anyone can check the derivation by
+    ///   [CommitmentKeys::verify_derived].
+    ///
+    /// Note that it does not derive fresh `a1`, `a2` (nor `g1`, `g2`) for each index, as they are not computable
+    /// from the public keys, and the derived keys would need a new extract key for each index.
+    pub fn derive_subkey(&self, index: u64) -> CommitmentKeys<E> {
+        let seed: [u8; 32] = Sha256::new()
+            .chain_update(SUBKEY_DOMAIN)
+            .chain_update(self.to_transcript_bytes())
+            .chain_update(index.to_le_bytes())
+            .finalize()
+            .into();
+        let rng = &mut ChaCha20Rng::from_seed(seed);
+        let delta1 = E::ScalarField::rand(rng);
+        let delta2 = E::ScalarField::rand(rng);
+
+        let CommitmentKey(u1, u2) = self.u;
+        let CommitmentKey(v1, v2) = self.v;
+        // u2' = (u21 * u11^δ1, u22 * u12^δ1)
+        let u2 = (
+            u2.0.add(u1.0.mul(delta1)).into(),
+            u2.1.add(u1.1.mul(delta1)).into(),
+        );
+        // v2' = (v21 * v11^δ2, v22 * v12^δ2)
+        let v2 = (
+            v2.0.add(v1.0.mul(delta2)).into(),
+            v2.1.add(v1.1.mul(delta2)).into(),
+        );
+
+        Self {
+            u: CommitmentKey(u1, u2),
+            v: CommitmentKey(v1, v2),
+        }
+    }
+
+    /// Verifies that the `subkey` is derived from these commitment keys for the `index` by [CommitmentKeys::derive_subkey].
+    pub fn verify_derived(&self, subkey: &CommitmentKeys<E>, index: u64) -> bool {
+        self.derive_subkey(index) == *subkey
+    }
+
+    /// Returns the canonical transcript encoding of these commitment keys. See [transcript] for the details of
+    /// the encoding.
+    pub fn to_transcript_bytes(&self) -> Vec<u8> {
+        let mut bytes = transcript::header(b"CommitmentKeys");
+        let CommitmentKey((u11, u12), (u21, u22)) = self.u;
+        let CommitmentKey((v11, v12), (v21, v22)) = self.v;
+        [u11, u12, u21, u22]
+            .iter()
+            .for_each(|p| transcript::append_element(&mut bytes, p));
+        [v11, v12, v21, v22]
+            .iter()
+            .for_each(|p| transcript::append_element(&mut bytes, p));
+        bytes
+    }
+
+    /// Returns the 32-byte fingerprint of these commitment keys, i.e. the SHA-256 hash of
+    /// [CommitmentKeys::to_transcript_bytes]. It identifies the CRS which a proof system belongs to, see
+    /// [StampedProofSystem](crate::stamped::StampedProofSystem).
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha256::digest(self.to_transcript_bytes()).into()
+    }
+
+    /// Implements the `Setup` (or `WISetup` if `wi` is true) function in section 6.2 of the paper for `count`
+    /// commitment keys at once.
+    fn new_batch<R: Rng>(rng: &mut R, count: usize, wi: bool) -> Vec<CommitmentKeys<E>> {
+        let g1s = (0..count).map(|_| E::G1::rand(rng)).collect::<Vec<_>>();
+        let g2s = (0..count).map(|_| E::G2::rand(rng)).collect::<Vec<_>>();
+        // (a1, a2, t1, t2) for each commitment key
+        let scalars = (0..count)
+            .map(|_| {
+                let mut scalar = || E::ScalarField::rand(rng);
+                (scalar(), scalar(), scalar(), scalar())
+            })
+            .collect::<Vec<_>>();
+        // a * t - 1 for WISetup, or a * t for Setup
+        let offset = if wi {
+            E::ScalarField::one()
+        } else {
+            E::ScalarField::zero()
+        };
+
+        let mut u_points = Vec::with_capacity(4 * count);
+        let mut v_points = Vec::with_capacity(4 * count);
+        for ((g1, g2), (a1, a2, t1, t2)) in g1s.iter().zip(g2s.iter()).zip(scalars.iter()) {
+            // u1 = (g1, g1^a1), u2 = (g1^t1, g1^(a1*t1 - offset))
+            u_points.extend([*g1, g1.mul(a1), g1.mul(t1), g1.mul(a1.mul(t1).sub(&offset))]);
+            // v1 = (g2, g2^a2), v2 = (g2^t2, g2^(a2*t2 - offset))
+            v_points.extend([*g2, g2.mul(a2), g2.mul(t2), g2.mul(a2.mul(t2).sub(&offset))]);
+        }
+        let u_points = E::G1::normalize_batch(&u_points);
+        let v_points = E::G2::normalize_batch(&v_points);
+
+        u_points
+            .chunks_exact(4)
+            .zip(v_points.chunks_exact(4))
+            .map(|(u, v)| Self {
+                u: CommitmentKey((u[0], u[1]), (u[2], u[3])),
+                v: CommitmentKey((v[0], v[1]), (v[2], v[3])),
+            })
+            .collect()
+    }
+
+    /// Implements the `Setup` function in section 6.2 of the paper, with the given generators `g1` and `g2`
+    /// and the trapdoor scalars `a1`, `a2`, `t1` and `t2`, i.e. `u1 = (g1, g1^a1)`, `u2 = u1^t1`, `v1 = (g2, g2^a2)`
+    /// and `v2 = v1^t2`.
+    ///
+    /// It is intended for tests (e.g. known-answer tests against reference vectors), trusted setup ceremonies
+    /// and audits of the setup. The keys are not hiding to anyone who knows the scalars, so the scalars must be
+    /// sampled uniformly at random and kept secret (or discarded) in practice, as in [CommitmentKeys::setup].
+    pub fn from_trapdoor(
         g1: <E as Pairing>::G1Affine,
         g2: <E as Pairing>::G2Affine,
         a1: E::ScalarField,
@@ -107,8 +335,12 @@ impl<E: Pairing> CommitmentKeys<E> {
         }
     }
 
-    /// Implements the `WISetup` function in section 6.2 of the paper.
-    fn new_wi(
+    /// Implements the `WISetup` function in section 6.2 of the paper, with the given generators `g1` and `g2`
+    /// and the trapdoor scalars `a1`, `a2`, `t1` and `t2`, i.e. `u2 = u1^t1 * (1, g1^-1)` and `v2 = v1^t2 * (1, g2^-1)`.
+    ///
+    /// Same as [CommitmentKeys::from_trapdoor], it is intended for tests, ceremonies and audits, and the scalars
+    /// must be kept secret in practice, as in [CommitmentKeys::setup_wi].
+    pub fn from_trapdoor_wi(
         g1: <E as Pairing>::G1Affine,
         g2: <E as Pairing>::G2Affine,
         a1: E::ScalarField,
@@ -137,11 +369,122 @@ impl<E: Pairing> CommitmentKeys<E> {
     }
 }
 
+/// The error returned by [CommitmentKeys::validate].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InvalidCommitmentKeys {
+    /// The point `u_ij` is not on the curve, not in the prime-order subgroup, or the identity.
+    U((usize, usize)),
+    /// The point `v_ij` is not on the curve, not in the prime-order subgroup, or the identity.
+    V((usize, usize)),
+    /// `u1` is equal to `u2`.
+    DegenerateU,
+    /// `v1` is equal to `v2`.
+    DegenerateV,
+}
+
+impl std::fmt::Display for InvalidCommitmentKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidCommitmentKeys::U((i, j)) => {
+                write!(f, "invalid point u[{}][{}] in commitment keys", i, j)
+            }
+            InvalidCommitmentKeys::V((i, j)) => {
+                write!(f, "invalid point v[{}][{}] in commitment keys", i, j)
+            }
+            InvalidCommitmentKeys::DegenerateU => write!(f, "degenerate commitment key u, u1 = u2"),
+            InvalidCommitmentKeys::DegenerateV => write!(f, "degenerate commitment key v, v1 = v2"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidCommitmentKeys {}
+
+/// Returns the index `(i, j)` of the first point `u_ij` in the commitment key which is not valid or is the identity.
+fn invalid_point<G: CurveGroup>(key: &CommitmentKey<G>) -> Option<(usize, usize)> {
+    let CommitmentKey((p11, p12), (p21, p22)) = key;
+    [((0, 0), p11), ((0, 1), p12), ((1, 0), p21), ((1, 1), p22)]
+        .into_iter()
+        .find(|(_, p)| p.check().is_err() || p.is_zero())
+        .map(|(index, _)| index)
+}
+
+/// The domain separator of the PRF for deriving the commitment keys in [CommitmentKeys::derive_subkey].
+const SUBKEY_DOMAIN: &[u8] = b"gs-ppe/derive_subkey";
+
+/// Commitment keys of the standard (binding) setup bound together with their extract key `ek`.
+/// It is returned by [CommitmentKeys::rand_ex] so that the trapdoor is not lost, e.g. for testing extraction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BoundCommitmentKeys<E: Pairing> {
+    cks: CommitmentKeys<E>,
+    ek: ExtractKey<E>,
+}
+
+impl<E: Pairing> BoundCommitmentKeys<E> {
+    /// Returns the commitment keys `ck`.
+    pub fn commitment_keys(&self) -> &CommitmentKeys<E> {
+        &self.cks
+    }
+
+    /// Returns the extract key `ek` corresponding to the commitment keys.
+    pub fn extract_key(&self) -> &ExtractKey<E> {
+        &self.ek
+    }
+}
+
+/// The trapdoor `(a1, a2, t1, t2)` of the commitment keys of the standard setup returned by
+/// [CommitmentKeys::setup_full], i.e. `u1 = (g1, g1^a1)`, `u2 = u1^t1`, `v1 = (g2, g2^a2)` and `v2 = v1^t2`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Trapdoor<E: Pairing> {
+    pub a1: E::ScalarField,
+    pub a2: E::ScalarField,
+    pub t1: E::ScalarField,
+    pub t2: E::ScalarField,
+}
+
+/// The extract key `(a1, a2)` of the trapdoor.
+impl<E: Pairing> From<&Trapdoor<E>> for ExtractKey<E> {
+    fn from(trapdoor: &Trapdoor<E>) -> Self {
+        ExtractKey(trapdoor.a1, trapdoor.a2)
+    }
+}
+
+/// The points `((u11, u12), (u21, u22))` in a [CommitmentKey].
+pub type CommitmentKeyPoints<G> = (
+    (<G as CurveGroup>::Affine, <G as CurveGroup>::Affine),
+    (<G as CurveGroup>::Affine, <G as CurveGroup>::Affine),
+);
+
 /// The component in commitment keys, either `u` or `v` in [CommitmentKeys].
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct CommitmentKey<G: CurveGroup>(pub (G::Affine, G::Affine), pub (G::Affine, G::Affine));
 
 impl<G: CurveGroup> CommitmentKey<G> {
+    /// Creates a commitment key from the points `((u11, u12), (u21, u22))` in projective representation,
+    /// normalizing them to affine representation in a batch.
+    pub fn from_projective(((p11, p12), (p21, p22)): ((G, G), (G, G))) -> Self {
+        let affine = G::normalize_batch(&[p11, p12, p21, p22]);
+        Self((affine[0], affine[1]), (affine[2], affine[3]))
+    }
+
+    /// Returns the points `((u11, u12), (u21, u22))`.
+    pub fn into_generators(self) -> CommitmentKeyPoints<G> {
+        (self.0, self.1)
+    }
+
+    /// Returns the points `((u11, u12), (u21, u22))` in projective representation.
+    pub fn to_projective(&self) -> ((G, G), (G, G)) {
+        (
+            (self.0 .0.into(), self.0 .1.into()),
+            (self.1 .0.into(), self.1 .1.into()),
+        )
+    }
+
+    /// Returns the commitment key with all four points normalized by [CurveGroup::normalize_batch], i.e. the
+    /// same key in canonical affine representation.
+    pub fn normalize(&self) -> CommitmentKey<G> {
+        Self::from_projective(self.to_projective())
+    }
+
     /// The commitment function `Com`. Returns the commitment of the variable `x` or `y` according to
     /// which group G the commitment key belongs to.
     pub fn commit(&self, x: &Variable<G>) -> Com<G> {
@@ -151,6 +494,41 @@ impl<G: CurveGroup> CommitmentKey<G> {
         // Com(ck, X, r) = (u11^r1 + u21^r2, x + u12^r1 + u22^r2)
         let a = self.0 .0.mul(r1) + self.1 .0.mul(r2);
         let b = self.0 .1.mul(r1) + self.1 .1.mul(r2);
+        metrics::record_call(Operation::Commit);
+        metrics::record_scalar_muls(Operation::Commit, 4);
         Com(a.into(), (x + b).into())
     }
+
+    /// Commits to the values `xs` with the same randomness `r` generated by `rng`, e.g. for opening the
+    /// commitments in a batch. Returns the commitments and the shared randomness. See [CommitmentKey::open_multi].
+    ///
+    /// Note that the commitments are still binding (simultaneously to all the values), but not hiding relative
+    /// to each other: all of them have the same first element `u11^r1 + u21^r2`, and the difference of the second
+    /// elements of two commitments reveals the difference `X_i - X_j` of the committed values. Commit with
+    /// independent randomness (e.g. [CommitmentKey::commit] with [Variable::new]) for witnesses which must stay
+    /// hidden from each other.
+    pub fn multi_commit<R: Rng>(
+        &self,
+        rng: &mut R,
+        xs: &[G::Affine],
+    ) -> (Vec<Com<G>>, Randomness<G>) {
+        let r = Randomness::rand(rng);
+        (self.open_multi(xs, r), r)
+    }
+
+    /// Returns the commitments `Com(ck, X_i, r)` of the values `xs` with the same randomness `r`, i.e. the
+    /// deterministic version of [CommitmentKey::multi_commit].
+    pub fn open_multi(&self, xs: &[G::Affine], r: Randomness<G>) -> Vec<Com<G>> {
+        let Randomness(r1, r2) = r;
+        let a = self.0 .0.mul(r1) + self.1 .0.mul(r2);
+        let b = self.0 .1.mul(r1) + self.1 .1.mul(r2);
+        metrics::record_call(Operation::Commit);
+        metrics::record_scalar_muls(Operation::Commit, 4);
+
+        let points = std::iter::once(a)
+            .chain(xs.iter().map(|x| b + x))
+            .collect::<Vec<_>>();
+        let affine = G::normalize_batch(&points);
+        affine[1..].iter().map(|x_b| Com(affine[0], *x_b)).collect()
+    }
 }
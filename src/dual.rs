@@ -0,0 +1,76 @@
+//! Defines the pairing [Dual], the pairing of `E` with the groups G1 and G2 exchanged, i.e. `e'(Q, P) = e(P, Q)`
+//! for `P` in G1 and `Q` in G2 of `E`.
+//!
+//! A statement with the variables on the "wrong" side (e.g. the witness in G2 of `E` where a template expects
+//! G1) can be transposed to the dual statement over `Dual<E>` with [Equation::transpose](crate::Equation::transpose),
+//! and proved with the transposed commitment keys [CommitmentKeys::transpose](crate::CommitmentKeys::transpose).
+//! The commitments are unchanged, but `c` and `d` are exchanged. A proof over `E` can also be transposed to a proof
+//! of the dual statement with [Proof::transpose](crate::Proof::transpose).
+//!
+//! ## Example
+//!
+//! ```
+//! use ark_bls12_381::Bls12_381 as E;
+//! use ark_ec::pairing::Pairing;
+//! use ark_std::{test_rng, UniformRand};
+//! use gs_ppe::{dual::Dual, setup, CommitmentKeys, Matrix, Proof, ProofSystem, Variable};
+//!
+//! type G1 = <E as Pairing>::G1;
+//! type G2 = <E as Pairing>::G2;
+//! type G1Affine = <E as Pairing>::G1Affine;
+//! type G2Affine = <E as Pairing>::G2Affine;
+//! type Fr = <E as Pairing>::ScalarField;
+//!
+//! let rng = &mut test_rng();
+//! let cks = CommitmentKeys::<E>::rand(rng);
+//! let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+//! let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+//! let (x, y) = (
+//!     Variable::<G1>::new(rng, x_value),
+//!     Variable::<G2>::new(rng, y_value),
+//! );
+//! let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+//! let ProofSystem {
+//!     equation,
+//!     c,
+//!     d,
+//!     ..
+//! } = setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma);
+//!
+//! // In the dual statement, y are the variables in G1 and x are the variables in G2.
+//! let dual_cks = cks.transpose();
+//! let dual_equation = equation.transpose();
+//! let proof = Proof::<Dual<E>>::new(rng, &dual_cks, &dual_equation, &[y], &[x]);
+//! assert!(dual_equation.verify(&dual_cks, &d, &c, &proof));
+//! ```
+
+use std::marker::PhantomData;
+
+use ark_ec::pairing::{MillerLoopOutput, Pairing, PairingOutput};
+
+/// The pairing `E` with the groups G1 and G2 exchanged. The scalar field and the target group are the same as `E`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Dual<E: Pairing>(PhantomData<E>);
+
+impl<E: Pairing> Pairing for Dual<E> {
+    type BaseField = E::BaseField;
+    type ScalarField = E::ScalarField;
+    type G1 = E::G2;
+    type G1Affine = E::G2Affine;
+    type G1Prepared = E::G2Prepared;
+    type G2 = E::G1;
+    type G2Affine = E::G1Affine;
+    type G2Prepared = E::G1Prepared;
+    type TargetField = E::TargetField;
+
+    fn multi_miller_loop(
+        a: impl IntoIterator<Item = impl Into<Self::G1Prepared>>,
+        b: impl IntoIterator<Item = impl Into<Self::G2Prepared>>,
+    ) -> MillerLoopOutput<Self> {
+        MillerLoopOutput(E::multi_miller_loop(b, a).0)
+    }
+
+    fn final_exponentiation(mlo: MillerLoopOutput<Self>) -> Option<PairingOutput<Self>> {
+        E::final_exponentiation(MillerLoopOutput(mlo.0)).map(|target| PairingOutput(target.0))
+    }
+}
@@ -1,10 +1,21 @@
 //! Defines the struct [Equation], the equation `E` notated in section 6.3 in the paper [Fuc10](https://eprint.iacr.org/2010/233.pdf).
 
-use ark_ec::pairing::{Pairing, PairingOutput};
-use ark_std::Zero;
-use std::ops::{Add, Mul};
+use ark_ec::{
+    pairing::{Pairing, PairingOutput},
+    AffineRepr, CurveGroup,
+};
+use ark_std::{
+    rand::{Rng, RngCore},
+    Zero,
+};
+use ndarray::{Array2, ArrayView2, Axis};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
-use crate::{Com, CommitmentKeys, Matrix, Proof};
+use crate::{
+    dual::Dual,
+    metrics::{self, Operation},
+    transcript, Com, CommitmentKeys, Matrix, Proof, Variable,
+};
 
 /// The pairing product equation `E`, represented by:
 /// - the constant `a` in a vector of size `n`
@@ -22,7 +33,31 @@ pub struct Equation<E: Pairing> {
     pub(crate) a: Vec<<E as Pairing>::G1Affine>, // size = n
     pub(crate) b: Vec<<E as Pairing>::G2Affine>, // size = m
     pub(crate) gamma: Matrix<E::ScalarField>,    // dim = (m, n)
-    pub(crate) target: PairingOutput<E>,
+    pub(crate) target: Target<E>,
+}
+
+/// The target of an equation, either the value `Value(T)` in the target group, or the pairs `Pairs((P_k, Q_k))`
+/// of public constants such that the target is the product `Π e(P_k, Q_k)`. The product is not computed by
+/// [Equation::verify], in which the pairs are folded into the pairings of Equation 4.
+///
+/// Note that the targets are compared by their representations, e.g. `Value(e(P, Q)) != Pairs([(P, Q)])`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target<E: Pairing> {
+    Value(PairingOutput<E>),
+    Pairs(Vec<(<E as Pairing>::G1Affine, <E as Pairing>::G2Affine)>),
+}
+
+impl<E: Pairing> Target<E> {
+    /// Returns the value of the target in the target group, i.e. computes `Π e(P_k, Q_k)` for [Target::Pairs].
+    pub fn value(&self) -> PairingOutput<E> {
+        match self {
+            Target::Value(target) => *target,
+            Target::Pairs(pairs) => E::multi_pairing(
+                pairs.iter().map(|(p_k, _)| *p_k),
+                pairs.iter().map(|(_, q_k)| *q_k),
+            ),
+        }
+    }
 }
 
 impl<E: Pairing> Equation<E> {
@@ -35,6 +70,20 @@ impl<E: Pairing> Equation<E> {
         b: Vec<<E as Pairing>::G2Affine>,
         gamma: Matrix<E::ScalarField>,
         target: PairingOutput<E>,
+    ) -> Self {
+        Self::new_with_target(a, b, gamma, Target::Value(target))
+    }
+
+    /// Same as [Equation::new], but the target is given as a [Target], e.g. the pairs of constants
+    /// [Target::Pairs] whose product is not computed.
+    ///
+    /// ## Panics
+    /// Panics if the dimension of `gamma` != (m, n), where m = b.len() and n = a.len().
+    pub fn new_with_target(
+        a: Vec<<E as Pairing>::G1Affine>,
+        b: Vec<<E as Pairing>::G2Affine>,
+        gamma: Matrix<E::ScalarField>,
+        target: Target<E>,
     ) -> Self {
         assert_eq!(gamma.dim(), (b.len(), a.len()));
         Self {
@@ -45,6 +94,532 @@ impl<E: Pairing> Equation<E> {
         }
     }
 
+    /// Returns the target of the equation.
+    #[inline]
+    pub fn target(&self) -> &Target<E> {
+        &self.target
+    }
+
+    /// Constructs an equation `E` with the given constants `a`, `b` and `gamma`, where the target is computed
+    /// from the values `x` and `y` with [Equation::compute_target_from], so that `x` and `y` satisfy the equation.
+    ///
+    /// ## Panics
+    /// Panics if the dimension of `gamma` != (m, n), where m = b.len() and n = a.len(), or if
+    /// `x.len() != m` or `y.len() != n`.
+    pub fn from_witnesses(
+        a: Vec<<E as Pairing>::G1Affine>,
+        b: Vec<<E as Pairing>::G2Affine>,
+        gamma: Matrix<E::ScalarField>,
+        x: &[<E as Pairing>::G1Affine],
+        y: &[<E as Pairing>::G2Affine],
+    ) -> Self {
+        let target = Self::compute_target_from(&a, &b, &gamma, x, y);
+        Self::new(a, b, gamma, target)
+    }
+
+    /// Computes the target `Π e(a_j, y_j) Π e(x_i, b_i) ΠΠ e(x_i, y_j)^gamma_ij` of the equation with the
+    /// constants `a`, `b` and `gamma` and the values `x` and `y`.
+    ///
+    /// ## Panics
+    /// Panics if the dimension of `gamma` != (`x.len()`, `y.len()`), or if `a.len() != y.len()` or
+    /// `b.len() != x.len()`.
+    pub fn compute_target_from(
+        a: &[<E as Pairing>::G1Affine],
+        b: &[<E as Pairing>::G2Affine],
+        gamma: &Matrix<E::ScalarField>,
+        x: &[<E as Pairing>::G1Affine],
+        y: &[<E as Pairing>::G2Affine],
+    ) -> PairingOutput<E> {
+        assert_eq!(gamma.dim(), (x.len(), y.len()));
+        assert_eq!(a.len(), y.len());
+        assert_eq!(b.len(), x.len());
+        compute_target(a, b, gamma, x, y)
+    }
+
+    /// Constructs an equation `E` over the pairing product of the variable matrices `X` (dim = (m, k)) and
+    /// `Y` (dim = (k, n)), together with the constant matrices `A` (dim = (k, n)) and `B` (dim = (m, k)):
+    ///
+    /// Π e(A_jl, Y_jl) Π e(X_ij, B_ij) Π_il (Π_j e(X_ij, Y_jl)) = target
+    ///
+    /// where the last product is the sum of the entries of the matrix product `XY` in the target group.
+    /// The matrices are flattened in row-major order into the vectors `a`, `b`, `x` and `y`, and `gamma`
+    /// has entry 1 at `(i * k + j, j * n + l)` for all i, j, l, and 0 elsewhere. The target is computed
+    /// from the values of the variables.
+    ///
+    /// Returns the equation and the flattened variables `x` and `y`.
+    ///
+    /// ## Panics
+    /// Panics if `a_matrix.dim() != y_matrix.dim()`, `b_matrix.dim() != x_matrix.dim()`, or the number of
+    /// columns of `x_matrix` is not equal to the number of rows of `y_matrix`.
+    #[allow(clippy::type_complexity)]
+    pub fn from_matrices(
+        a_matrix: Matrix<<E as Pairing>::G1Affine>,
+        b_matrix: Matrix<<E as Pairing>::G2Affine>,
+        x_matrix: &Matrix<Variable<<E as Pairing>::G1>>,
+        y_matrix: &Matrix<Variable<<E as Pairing>::G2>>,
+    ) -> (
+        Equation<E>,
+        Vec<Variable<<E as Pairing>::G1>>,
+        Vec<Variable<<E as Pairing>::G2>>,
+    ) {
+        assert_eq!(a_matrix.dim(), y_matrix.dim());
+        assert_eq!(b_matrix.dim(), x_matrix.dim());
+        let (m, k) = x_matrix.dim();
+        let (k_y, n) = y_matrix.dim();
+        assert_eq!(k, k_y);
+
+        let a = a_matrix.as_ref().iter().copied().collect::<Vec<_>>();
+        let b = b_matrix.as_ref().iter().copied().collect::<Vec<_>>();
+        let x = x_matrix.as_ref().iter().copied().collect::<Vec<_>>();
+        let y = y_matrix.as_ref().iter().copied().collect::<Vec<_>>();
+
+        let mut gamma = Matrix::from_elem(m * k, k * n, E::ScalarField::zero());
+        for i in 0..m {
+            for j in 0..k {
+                for l in 0..n {
+                    gamma[(i * k + j, j * n + l)] = E::ScalarField::from(1u64);
+                }
+            }
+        }
+
+        let equation = Equation::from_witnesses(
+            a,
+            b,
+            gamma,
+            &x.iter().map(|x_i| x_i.value).collect::<Vec<_>>(),
+            &y.iter().map(|y_j| y_j.value).collect::<Vec<_>>(),
+        );
+
+        (equation, x, y)
+    }
+
+    /// Constructs the equation of the bilinear form `B(x, y) = Σ_ij gamma_ij * x_i * y_j` over the scalars `x_i`
+    /// and `y_j`, where the variables are `X_i = g1^x_i` and `Y_j = g2^y_j`. It is the equation without the
+    /// constants `a` and `b` (i.e. they are the identities):
+    ///
+    /// ΠΠ e(X_i, Y_j)^gamma_ij = e(g1, g2)^B(x, y)
+    ///
+    /// where the target is computed from the values of the variables. Returns the equation and the base
+    /// `e(g1, g2)` of the target, e.g. to check the target against a claimed value of `B(x, y)`.
+    ///
+    /// ## Panics
+    /// Panics if the dimension of `gamma` != (`x.len()`, `y.len()`).
+    pub fn from_bilinear_form(
+        gamma: Matrix<E::ScalarField>,
+        x: &[Variable<<E as Pairing>::G1>],
+        y: &[Variable<<E as Pairing>::G2>],
+        g1: <E as Pairing>::G1Affine,
+        g2: <E as Pairing>::G2Affine,
+    ) -> (Equation<E>, PairingOutput<E>) {
+        assert_eq!(gamma.dim(), (x.len(), y.len()));
+        let equation = Equation::from_witnesses(
+            vec![<E as Pairing>::G1Affine::zero(); y.len()],
+            vec![<E as Pairing>::G2Affine::zero(); x.len()],
+            gamma,
+            &x.iter().map(|x_i| x_i.value).collect::<Vec<_>>(),
+            &y.iter().map(|y_j| y_j.value).collect::<Vec<_>>(),
+        );
+        (equation, E::pairing(g1, g2))
+    }
+
+    /// Constructs the equation of a discrete logarithm relation `e(g1^x, g2) = e(g1, g2)^x`, i.e.
+    ///
+    /// e(X, g2) = target
+    ///
+    /// with the variables `x = (X)` and no variables `y`, where `target = e(g1, g2)^x` is public. Returns the
+    /// equation together with the constructor of the witness `X = g1^x` from the scalar `x`, with the randomness
+    /// sampled from the given rng.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ark_bls12_381::Bls12_381 as E;
+    /// use ark_ec::{pairing::Pairing, AffineRepr};
+    /// use ark_std::{test_rng, UniformRand};
+    /// use gs_ppe::{CommitmentKeys, Equation, Proof};
+    /// use std::ops::Mul;
+    ///
+    /// type G1Affine = <E as Pairing>::G1Affine;
+    /// type G2Affine = <E as Pairing>::G2Affine;
+    /// type Fr = <E as Pairing>::ScalarField;
+    ///
+    /// let rng = &mut test_rng();
+    /// let cks = CommitmentKeys::<E>::rand(rng);
+    /// let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+    /// let x = Fr::rand(rng);
+    ///
+    /// let (equation, witness) = Equation::<E>::dh_equation(g1, g2, E::pairing(g1, g2).mul(x));
+    /// let x = witness(rng, x);
+    /// let proof = Proof::new(rng, &cks, &equation, &[x], &[]);
+    /// assert!(equation.verify(&cks, &[cks.u.commit(&x)], &[], &proof));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn dh_equation(
+        g1: <E as Pairing>::G1Affine,
+        g2: <E as Pairing>::G2Affine,
+        target: PairingOutput<E>,
+    ) -> (
+        Equation<E>,
+        impl Fn(&mut dyn RngCore, E::ScalarField) -> Variable<<E as Pairing>::G1>,
+    ) {
+        let equation = Equation::new(
+            vec![],
+            vec![g2],
+            Matrix::from_elem(1, 0, E::ScalarField::zero()),
+            target,
+        );
+        let witness = move |mut rng: &mut dyn RngCore, x: E::ScalarField| {
+            Variable::new(&mut rng, g1.mul(x).into_affine())
+        };
+        (equation, witness)
+    }
+
+    /// Returns the equation that two commitments in G1 under the keys `cks` open to the same value, i.e.
+    /// [committed_equal_g1](crate::statements::committed_equal_g1) with the generator `g2` of the keys:
+    ///
+    /// e(X1, g2) e(X2, g2^-1) = 1
+    ///
+    /// The proof is generated by [prove_commitment_equality](crate::statements::prove_commitment_equality).
+    pub fn for_commitment_equality(cks: &CommitmentKeys<E>) -> Equation<E> {
+        crate::statements::committed_equal_g1(cks.generator_g2())
+    }
+
+    /// Returns the equation with the target `T^k`, while `a`, `b` and `gamma` are unchanged, i.e.
+    ///
+    /// Π e(a_j, y_j) Π e(x_i, b_i) ΠΠ e(x_i, y_j)^gamma_ij = target^k
+    ///
+    /// Note that it is a different statement, which is in general not satisfied by the variables of this equation.
+    /// Raising all the pairings to the power `k` (so that the same variables satisfy the equation) would also
+    /// require scaling the constants `a` and `b` to `a^k` and `b^k`, which this method does not do.
+    pub fn scale(&self, k: E::ScalarField) -> Equation<E> {
+        Self {
+            target: self.target.clone() * k,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the equation with the matrix `gamma * k`, while `a`, `b` and `target` are unchanged, i.e.
+    ///
+    /// Π e(a_j, y_j) Π e(x_i, b_i) ΠΠ e(x_i, y_j)^(k * gamma_ij) = target
+    ///
+    /// Same as [Equation::scale], it is a different statement in general.
+    pub fn scale_gamma(&self, k: E::ScalarField) -> Equation<E> {
+        let gamma = Matrix::from(self.gamma.as_ref().mapv(|gamma_ij| gamma_ij * k));
+        Self {
+            gamma,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the dual equation over the pairing [Dual] with the groups G1 and G2 exchanged, i.e.
+    ///
+    /// Π e'(b_i, x_i) Π e'(y_j, a_j) ΠΠ e'(y_j, x_i)^gamma'_ji = target
+    ///
+    /// where `e'(Q, P) = e(P, Q)` and `gamma' = gamma^T`. i.e. the constants `a` and `b` are exchanged
+    /// (`a' = b` in G1 and `b' = a` in G2 of `Dual<E>`), and the equation is satisfied by the same variables,
+    /// with `x' = y` as the variables in G1 and `y' = x` as the variables in G2 of `Dual<E>`. The target is the
+    /// same value. The groups cannot be exchanged within `E` itself, as `a` and `b` are in different groups.
+    ///
+    /// The original equation is recovered by [Equation::from_dual].
+    pub fn transpose(&self) -> Equation<Dual<E>> {
+        let target = match &self.target {
+            Target::Value(target) => Target::Value(PairingOutput(target.0)),
+            Target::Pairs(pairs) => Target::Pairs(pairs.iter().map(|(p, q)| (*q, *p)).collect()),
+        };
+        Equation {
+            a: self.b.clone(),
+            b: self.a.clone(),
+            gamma: Matrix::from(self.gamma.as_ref().t().to_owned()),
+            target,
+        }
+    }
+
+    /// Returns the equation over `E` of the dual equation, i.e. the inverse of [Equation::transpose], such that
+    /// `Equation::from_dual(&equation.transpose()) == equation`.
+    pub fn from_dual(dual: &Equation<Dual<E>>) -> Self {
+        let target = match &dual.target {
+            Target::Value(target) => Target::Value(PairingOutput(target.0)),
+            Target::Pairs(pairs) => Target::Pairs(pairs.iter().map(|(p, q)| (*q, *p)).collect()),
+        };
+        Equation {
+            a: dual.b.clone(),
+            b: dual.a.clone(),
+            gamma: Matrix::from(dual.gamma.as_ref().t().to_owned()),
+            target,
+        }
+    }
+
+    /// Returns the dimension `(m, n)` of the equation, i.e. the dimension of `gamma`, where `m` is the number of
+    /// variables `x` and `n` is the number of variables `y`.
+    #[inline]
+    pub fn dim(&self) -> (usize, usize) {
+        self.gamma.dim()
+    }
+
+    /// Returns the number of variables `x` in G1, i.e. `m`.
+    #[inline]
+    pub fn num_x(&self) -> usize {
+        self.dim().0
+    }
+
+    /// Returns the number of variables `y` in G2, i.e. `n`.
+    #[inline]
+    pub fn num_y(&self) -> usize {
+        self.dim().1
+    }
+
+    /// Returns the sum of the equations by the homomorphic addition, i.e. the same as `((e1 + e2) + e3) + ...`.
+    /// Returns the empty equation (i.e. without variables and the target is the identity) if
+    /// `equations` is empty.
+    pub fn sum<I: IntoIterator<Item = Equation<E>>>(equations: I) -> Equation<E> {
+        equations
+            .into_iter()
+            .reduce(Add::add)
+            .unwrap_or_else(Equation::empty)
+    }
+
+    /// Returns the empty equation, i.e. without variables and the target is the identity. It is the neutral
+    /// element of the homomorphic addition.
+    pub fn empty() -> Equation<E> {
+        Equation::new(vec![], vec![], Matrix::empty(), PairingOutput::zero())
+    }
+
+    /// Checks that the target is not the identity. If the target is the identity, the equation is satisfied by
+    /// the identities as the variables, e.g. the equation of [Equation::empty].
+    pub fn has_nonzero_target(&self) -> bool {
+        !self.target.value().is_zero()
+    }
+
+    /// Checks that the equation is degenerate, i.e. the target is the identity and there are no constants `a`
+    /// and `b`, so that the equation only constrains the products `ΠΠ e(x_i, y_j)^gamma_ij` to the identity.
+    /// It is useful for assertions in debugging protocols.
+    pub fn is_degenerate(&self) -> bool {
+        !self.has_nonzero_target() && self.a.is_empty() && self.b.is_empty()
+    }
+
+    /// Checks that the equation is linear, i.e. `gamma` is zero, so that the equation is
+    /// `Π e(a_j, y_j) Π e(x_i, b_i) = T`. [Proof::new] and [Equation::verify] skip the terms of `gamma` in
+    /// the linear case.
+    pub fn is_linear(&self) -> bool {
+        self.gamma.is_zero()
+    }
+
+    /// Checks that the values `x` and `y` satisfy the equation, i.e. recomputes the pairing product
+    /// `Π e(a_j, y_j) Π e(x_i, b_i) ΠΠ e(x_i, y_j)^gamma_ij` and compares it with the target. It is an algebraic
+    /// check on the witnesses, which requires no commitment or proof.
+    ///
+    /// Returns false if `x.len() != m` or `y.len() != n`.
+    pub fn verify_target_with_values(
+        &self,
+        x: &[<E as Pairing>::G1Affine],
+        y: &[<E as Pairing>::G2Affine],
+    ) -> bool {
+        if self.gamma.dim() != (x.len(), y.len()) {
+            return false;
+        }
+        compute_target(&self.a, &self.b, &self.gamma, x, y) == self.target.value()
+    }
+
+    /// Merges the terms `e(a_j, y_j)` which have the same constant `a_j` and the same column `j` in `gamma`,
+    /// so that the merged equation has fewer variables `y`. i.e. if `a_j = a_k` and the columns `j` and `k`
+    /// are equal, the terms are merged into the single term `e(a_j, y_j y_k)`, and the products
+    /// `e(x_i, y_j)^gamma_ij e(x_i, y_k)^gamma_ik` into `e(x_i, y_j y_k)^gamma_ij`. The target is unchanged.
+    ///
+    /// The merged terms are ordered by their first occurrences. The merged variables can be computed with
+    /// [Equation::merge_y_variables]. By the homomorphic property, their commitments are the sums of the
+    /// commitments of the original variables.
+    pub fn merge_ay_terms(&self) -> Equation<E> {
+        let groups = merge_groups(&self.a, self.gamma.as_ref().t());
+        let firsts = groups.iter().map(|g| g[0]).collect::<Vec<_>>();
+
+        Equation {
+            a: firsts.iter().map(|&j| self.a[j]).collect(),
+            b: self.b.clone(),
+            gamma: Matrix::from(self.gamma.as_ref().select(Axis(1), &firsts)),
+            target: self.target.clone(),
+        }
+    }
+
+    /// Merges the terms `e(x_i, b_i)` which have the same constant `b_i` and the same row `i` in `gamma`,
+    /// so that the merged equation has fewer variables `x`. It is the counterpart of [Equation::merge_ay_terms].
+    ///
+    /// The merged variables can be computed with [Equation::merge_x_variables].
+    pub fn merge_xb_terms(&self) -> Equation<E> {
+        let groups = merge_groups(&self.b, self.gamma.as_ref().view());
+        let firsts = groups.iter().map(|g| g[0]).collect::<Vec<_>>();
+
+        Equation {
+            a: self.a.clone(),
+            b: firsts.iter().map(|&i| self.b[i]).collect(),
+            gamma: Matrix::from(self.gamma.as_ref().select(Axis(0), &firsts)),
+            target: self.target.clone(),
+        }
+    }
+
+    /// Merges the variables `y` of this equation for the equation returned by [Equation::merge_ay_terms].
+    /// The merged variable is the sum of the values and the randomness of the variables in the merged terms.
+    ///
+    /// ## Panics
+    /// Panics if `y.len() != a.len()`, where `a` is the constant in this equation.
+    pub fn merge_y_variables(
+        &self,
+        y: &[Variable<<E as Pairing>::G2>],
+    ) -> Vec<Variable<<E as Pairing>::G2>> {
+        assert_eq!(self.a.len(), y.len());
+        merge_groups(&self.a, self.gamma.as_ref().t())
+            .iter()
+            .map(|g| merge_variables(g, y))
+            .collect()
+    }
+
+    /// Merges the variables `x` of this equation for the equation returned by [Equation::merge_xb_terms].
+    /// The merged variable is the sum of the values and the randomness of the variables in the merged terms.
+    ///
+    /// ## Panics
+    /// Panics if `x.len() != b.len()`, where `b` is the constant in this equation.
+    pub fn merge_x_variables(
+        &self,
+        x: &[Variable<<E as Pairing>::G1>],
+    ) -> Vec<Variable<<E as Pairing>::G1>> {
+        assert_eq!(self.b.len(), x.len());
+        merge_groups(&self.b, self.gamma.as_ref().view())
+            .iter()
+            .map(|g| merge_variables(g, x))
+            .collect()
+    }
+
+    /// Returns the sub-equation with only the terms `e(x_i, b_i)`, i.e. `a` is the identity and `gamma` is zero:
+    ///
+    /// Π e(x_i, b_i) = T_1
+    ///
+    /// over the same variables. The sub-target `T_1` is computed from the values `x`, and the prover announces
+    /// it, as the verifier cannot split the target. The sub-targets of [Equation::linear_g1_equation],
+    /// [Equation::linear_g2_equation] and [Equation::bilinear_equation] multiply to the target of this equation
+    /// for values which satisfy it.
+    ///
+    /// ## Security
+    /// The announced sub-target is a pairing value of the witness, which the proofs do not hide. For example,
+    /// with `m = 1`, `T_1 = e(X, b)` lets anyone test a guess of `X`. Splitting the equation is only for the
+    /// statements whose partial products are already public. Otherwise, the sub-targets should be given by the
+    /// caller (e.g. with [Equation::new_with_target]) rather than computed from the witness.
+    ///
+    /// ## Panics
+    /// Panics if `x.len() != b.len()`, where `b` is the constant in this equation.
+    pub fn linear_g1_equation(&self, x: &[<E as Pairing>::G1Affine]) -> Equation<E> {
+        let (m, n) = self.gamma.dim();
+        let a = vec![<E as Pairing>::G1Affine::zero(); n];
+        let gamma = Matrix::from_elem(m, n, E::ScalarField::zero());
+        let y = vec![<E as Pairing>::G2Affine::zero(); n];
+        Self::from_witnesses(a, self.b.clone(), gamma, x, &y)
+    }
+
+    /// Returns the sub-equation with only the terms `e(a_j, y_j)`, i.e. `b` is the identity and `gamma` is zero:
+    ///
+    /// Π e(a_j, y_j) = T_2
+    ///
+    /// over the same variables, where the sub-target `T_2` is computed from the values `y`. See
+    /// [Equation::linear_g1_equation].
+    ///
+    /// ## Security
+    /// The announced sub-target leaks a pairing value of the witness, e.g. `T_2 = e(a, Y)` with `n = 1` lets
+    /// anyone test a guess of `Y`. Use it only when the partial product is already public, as explained in
+    /// [Equation::linear_g1_equation].
+    ///
+    /// ## Panics
+    /// Panics if `y.len() != a.len()`, where `a` is the constant in this equation.
+    pub fn linear_g2_equation(&self, y: &[<E as Pairing>::G2Affine]) -> Equation<E> {
+        let (m, n) = self.gamma.dim();
+        let b = vec![<E as Pairing>::G2Affine::zero(); m];
+        let gamma = Matrix::from_elem(m, n, E::ScalarField::zero());
+        let x = vec![<E as Pairing>::G1Affine::zero(); m];
+        Self::from_witnesses(self.a.clone(), b, gamma, &x, y)
+    }
+
+    /// Returns the sub-equation with only the terms `e(x_i, y_j)^gamma_ij`, i.e. `a` and `b` are the identity:
+    ///
+    /// ΠΠ e(x_i, y_j)^gamma_ij = T_3
+    ///
+    /// over the same variables, where the sub-target `T_3` is computed from the values `x` and `y`. See
+    /// [Equation::linear_g1_equation].
+    ///
+    /// ## Security
+    /// The announced sub-target leaks a pairing value of the witness, e.g. `T_3 = e(X, Y)^gamma` with
+    /// `m = n = 1` lets anyone test a guess of `X` given `Y`, or vice versa. Use it only when the partial product
+    /// is already public, as explained in [Equation::linear_g1_equation].
+    ///
+    /// ## Panics
+    /// Panics if `x.len() != b.len()` or `y.len() != a.len()`, where `a` and `b` are the constants in this equation.
+    pub fn bilinear_equation(
+        &self,
+        x: &[<E as Pairing>::G1Affine],
+        y: &[<E as Pairing>::G2Affine],
+    ) -> Equation<E> {
+        let (m, n) = self.gamma.dim();
+        let a = vec![<E as Pairing>::G1Affine::zero(); n];
+        let b = vec![<E as Pairing>::G2Affine::zero(); m];
+        Self::from_witnesses(a, b, self.gamma.clone(), x, y)
+    }
+
+    /// Returns the equation over the variables `x' = L x` in G1 for an invertible `m x m` matrix `L`, i.e.
+    /// `X'_k = Π_i X_i^L_ki`. The constants are `b' = L^-T b` and `gamma' = L^-T gamma`, while `a` and the target
+    /// are unchanged, so that `x'` satisfy the returned equation if and only if `x` satisfy this equation.
+    /// See [Proof::apply_linear_map_g1] for the proof of the returned equation.
+    ///
+    /// Returns `None` if `L` is singular.
+    ///
+    /// ## Panics
+    /// Panics if `l.dim() != (m, m)`, where `m = b.len()`.
+    pub fn apply_linear_map_g1(&self, l: &Matrix<E::ScalarField>) -> Option<Equation<E>> {
+        let m = self.b.len();
+        assert_eq!(l.dim(), (m, m));
+        let l_inv = l.inverse()?;
+
+        let b = (0..m)
+            .map(|k| {
+                (0..m)
+                    .fold(<E as Pairing>::G2::zero(), |acc, i| {
+                        acc + self.b[i] * l_inv[(i, k)]
+                    })
+                    .into_affine()
+            })
+            .collect();
+        let gamma = Array2::from_shape_fn(self.gamma.dim(), |(k, j)| {
+            (0..m).fold(E::ScalarField::zero(), |acc, i| {
+                acc + l_inv[(i, k)] * self.gamma[(i, j)]
+            })
+        });
+        Some(Equation {
+            a: self.a.clone(),
+            b,
+            gamma: Matrix::from_ndarray(gamma),
+            target: self.target.clone(),
+        })
+    }
+
+    /// Removes the terms which contribute nothing to the equation, i.e. the variables `y_j` with the identity
+    /// constant `a_j` and a zero column `j` in `gamma`, and the variables `x_i` with the identity constant `b_i`
+    /// and a zero row `i` in `gamma`. The target is unchanged.
+    ///
+    /// Returns the pruned equation and the [PruneMap] of the indices of the remaining variables, e.g. for
+    /// dropping the commitments of the removed variables (see [ProofSystem::prune](crate::ProofSystem::prune)).
+    pub fn prune(&self) -> (Equation<E>, PruneMap) {
+        let gamma = self.gamma.as_ref();
+        let x = (0..self.b.len())
+            .filter(|&i| !self.b[i].is_zero() || gamma.row(i).iter().any(|g| !g.is_zero()))
+            .collect::<Vec<_>>();
+        let y = (0..self.a.len())
+            .filter(|&j| !self.a[j].is_zero() || gamma.column(j).iter().any(|g| !g.is_zero()))
+            .collect::<Vec<_>>();
+
+        let equation = Equation {
+            a: y.iter().map(|&j| self.a[j]).collect(),
+            b: x.iter().map(|&i| self.b[i]).collect(),
+            gamma: Matrix::from(gamma.select(Axis(0), &x).select(Axis(1), &y)),
+            target: self.target.clone(),
+        };
+        (equation, PruneMap { x, y })
+    }
+
     // TODO:
     // "Remark 5. Blazy et al. [BFI+10] show that by using techniques of batch verification, the number of pairing
     // computations can be reduced from 4m + n + 16 to 2m+n+8".
@@ -53,6 +628,9 @@ impl<E: Pairing> Equation<E> {
     /// the given commitments `c`, `d`, and `proof`. Returns false if the verification fails or
     /// the dimensions of the inputs are incorrect.
     ///
+    /// It assumes that the points in the commitments and the proof are valid, e.g. they are deserialized
+    /// with validation. Use [Equation::verify_checked] for the inputs from an unchecked source.
+    ///
     /// ## Example
     ///
     /// ```
@@ -93,12 +671,32 @@ impl<E: Pairing> Equation<E> {
     /// assert_eq!(equation, proof_system.equation);
     /// assert!(equation.verify(&cks, &proof_system.c, &proof_system.d, &proof_system.proof));
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Equation::verify", skip_all, fields(m = c.len(), n = d.len()))
+    )]
     pub fn verify(
         &self,
         cks: &CommitmentKeys<E>,
         c: &[Com<<E as Pairing>::G1>],
         d: &[Com<<E as Pairing>::G2>],
         proof: &Proof<E>,
+    ) -> bool {
+        self.verify_selected(cks, c, d, proof, &[true; 4])
+    }
+
+    /// Same as [Equation::verify], but checks only the verification equations `i + 1` for which `checks[i]`
+    /// is true, e.g. `[true, false, false, true]` checks Equation 1 and Equation 4. The dimensions are always
+    /// checked, and true is returned if no equation is selected.
+    ///
+    /// Note that the proof is not verified unless all the four equations are checked.
+    pub fn verify_selected(
+        &self,
+        cks: &CommitmentKeys<E>,
+        c: &[Com<<E as Pairing>::G1>],
+        d: &[Com<<E as Pairing>::G2>],
+        proof: &Proof<E>,
+        checks: &[bool; 4],
     ) -> bool {
         let (m, n) = self.gamma.dim();
         if self.a.len() != n
@@ -110,112 +708,377 @@ impl<E: Pairing> Equation<E> {
         {
             return false;
         }
+        metrics::record_call(Operation::Verify);
         let u = &cks.u;
         let v = &cks.v;
+        // In a linear equation, the products Π d_j^gamma_ij and Π c_i^gamma_ij are the identity.
+        let linear = self.is_linear();
 
         // Check Equation 1:
         // Π e(c_i1, Π d_j1^gamma_ij) = e(u11, φ11) e(u21, φ21) e(θ11, v11) e(θ21, v21)
-        let lhs = c
-            .iter()
-            .enumerate()
-            .fold(PairingOutput::zero(), |acc, (i, c_i)| {
+        if checks[0] {
+            let lhs = if linear {
+                PairingOutput::zero()
+            } else {
+                metrics::record_scalar_muls(Operation::Verify, m * n);
+                metrics::record_pairings(Operation::Verify, m);
+                c.iter()
+                    .enumerate()
+                    .fold(PairingOutput::zero(), |acc, (i, c_i)| {
+                        let d_product = d
+                            .iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
+                                acc + d_j.0.mul(self.gamma[(i, j)])
+                            })
+                            .into();
+
+                        acc + E::pairing(c_i.0, d_product)
+                    })
+            };
+            let rhs = E::pairing(u.0 .0, proof.phi[(0, 0)])
+                + E::pairing(u.1 .0, proof.phi[(1, 0)])
+                + E::pairing(proof.theta[(0, 0)], v.0 .0)
+                + E::pairing(proof.theta[(1, 0)], v.1 .0);
+            metrics::record_pairings(Operation::Verify, 4);
+
+            if lhs != rhs {
+                return false;
+            }
+        }
+
+        // create pre-calculated value b_i Π d_j2^gamma_ij for equation 2 and 4 for efficiency.
+        let b_d = if !checks[1] && !checks[3] {
+            Vec::new()
+        } else if linear {
+            self.b.iter().map(|b_i| b_i.into_group()).collect()
+        } else {
+            metrics::record_scalar_muls(Operation::Verify, m * n);
+            c.iter().enumerate().fold(Vec::new(), |mut acc, (i, _)| {
                 let d_product = d
                     .iter()
                     .enumerate()
                     .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
-                        acc + d_j.0.mul(self.gamma[(i, j)])
+                        acc + d_j.1.mul(self.gamma[(i, j)])
                     })
                     .into();
+                acc.push(self.b[i] + d_product);
+                acc
+            })
+        };
 
-                acc + E::pairing(c_i.0, d_product)
-            });
-        let rhs = E::pairing(u.0 .0, proof.phi[(0, 0)])
-            + E::pairing(u.1 .0, proof.phi[(1, 0)])
-            + E::pairing(proof.theta[(0, 0)], v.0 .0)
-            + E::pairing(proof.theta[(1, 0)], v.1 .0);
-
-        if lhs != rhs {
-            return false;
+        // Check Equation 2:
+        // Π e(c_i1, b_i Π d_j2^gamma_ij) = e(u11, φ12) e(u21, φ22) e(θ11, v12) e(θ21, v22)
+        if checks[1] {
+            let lhs = c
+                .iter()
+                .enumerate()
+                .fold(PairingOutput::zero(), |acc, (i, c_i)| {
+                    acc + E::pairing(c_i.0, b_d[i])
+                });
+            let rhs = E::pairing(u.0 .0, proof.phi[(0, 1)])
+                + E::pairing(u.1 .0, proof.phi[(1, 1)])
+                + E::pairing(proof.theta[(0, 0)], v.0 .1)
+                + E::pairing(proof.theta[(1, 0)], v.1 .1);
+            metrics::record_pairings(Operation::Verify, m + 4);
+            if lhs != rhs {
+                return false;
+            }
         }
 
-        // create pre-calculated value b_i Π d_j2^gamma_ij for equation 2 and 4 for efficiency.
-        let b_d = c.iter().enumerate().fold(Vec::new(), |mut acc, (i, _)| {
-            let d_product = d
+        // Check Equation 3:
+        // Π e(a_j Π c_i2^gamma_ij, d_j1) = e(u12, φ11) e(u22, φ21) e(θ12, v11) e(θ22, v21)
+        if checks[2] {
+            let lhs = d
                 .iter()
                 .enumerate()
-                .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
-                    acc + d_j.1.mul(self.gamma[(i, j)])
-                })
-                .into();
-            acc.push(self.b[i] + d_product);
-            acc
-        });
+                .fold(PairingOutput::zero(), |acc, (j, d_j)| {
+                    if linear {
+                        return acc + E::pairing(self.a[j], d_j.0);
+                    }
+                    let c_product = c
+                        .iter()
+                        .enumerate()
+                        .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
+                            acc + c_i.1.mul(self.gamma[(i, j)])
+                        })
+                        .into();
 
-        // Check Equation 2:
-        // Π e(c_i1, b_i Π d_j2^gamma_ij) = e(u11, φ12) e(u21, φ22) e(θ11, v12) e(θ21, v22)
-        let lhs = c
-            .iter()
-            .enumerate()
-            .fold(PairingOutput::zero(), |acc, (i, c_i)| {
-                acc + E::pairing(c_i.0, b_d[i])
-            });
-        let rhs = E::pairing(u.0 .0, proof.phi[(0, 1)])
-            + E::pairing(u.1 .0, proof.phi[(1, 1)])
-            + E::pairing(proof.theta[(0, 0)], v.0 .1)
-            + E::pairing(proof.theta[(1, 0)], v.1 .1);
-        if lhs != rhs {
-            return false;
+                    acc + E::pairing(self.a[j] + c_product, d_j.0)
+                });
+            let rhs = E::pairing(u.0 .1, proof.phi[(0, 0)])
+                + E::pairing(u.1 .1, proof.phi[(1, 0)])
+                + E::pairing(proof.theta[(0, 1)], v.0 .0)
+                + E::pairing(proof.theta[(1, 1)], v.1 .0);
+            if !linear {
+                metrics::record_scalar_muls(Operation::Verify, m * n);
+            }
+            metrics::record_pairings(Operation::Verify, n + 4);
+            if lhs != rhs {
+                return false;
+            }
         }
 
-        // Check Equation 3:
-        // Π e(a_j Π c_i2^gamma_ij, d_j1) = e(u12, φ11) e(u22, φ21) e(θ12, v11) e(θ22, v21)
-        let lhs = d
-            .iter()
-            .enumerate()
-            .fold(PairingOutput::zero(), |acc, (j, d_j)| {
-                let c_product = c
+        // Check Equation 4:
+        // Π e(a_j, d_j2) Π e(c_i2, b_i Π d_j2^gamma_ij) = t_T e(u12, φ12) e(u22, φ22) e(θ12, v12) e(θ22, v22)
+        if checks[3] {
+            let lhs = {
+                let a_d = self
+                    .a
+                    .iter()
+                    .zip(d.iter())
+                    .fold(PairingOutput::zero(), |acc, (a_j, d_j)| {
+                        acc + E::pairing(a_j, d_j.1)
+                    });
+                let c_bd = c
                     .iter()
                     .enumerate()
-                    .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
-                        acc + c_i.1.mul(self.gamma[(i, j)])
-                    })
-                    .into();
+                    .fold(PairingOutput::zero(), |acc, (i, c_i)| {
+                        acc + E::pairing(c_i.1, b_d[i])
+                    });
+                a_d + c_bd
+            };
+            let rhs = match &self.target {
+                Target::Value(target) => {
+                    *target
+                        + E::pairing(u.0 .1, proof.phi[(0, 1)])
+                        + E::pairing(u.1 .1, proof.phi[(1, 1)])
+                        + E::pairing(proof.theta[(0, 1)], v.0 .1)
+                        + E::pairing(proof.theta[(1, 1)], v.1 .1)
+                }
+                Target::Pairs(pairs) => {
+                    metrics::record_pairings(Operation::Verify, pairs.len());
+                    let g1s = pairs.iter().map(|(p_k, _)| *p_k).chain([
+                        u.0 .1,
+                        u.1 .1,
+                        proof.theta[(0, 1)],
+                        proof.theta[(1, 1)],
+                    ]);
+                    let g2s = pairs.iter().map(|(_, q_k)| *q_k).chain([
+                        proof.phi[(0, 1)],
+                        proof.phi[(1, 1)],
+                        v.0 .1,
+                        v.1 .1,
+                    ]);
+                    E::multi_pairing(g1s, g2s)
+                }
+            };
 
-                acc + E::pairing(self.a[j] + c_product, d_j.0)
-            });
-        let rhs = E::pairing(u.0 .1, proof.phi[(0, 0)])
-            + E::pairing(u.1 .1, proof.phi[(1, 0)])
-            + E::pairing(proof.theta[(0, 1)], v.0 .0)
-            + E::pairing(proof.theta[(1, 1)], v.1 .0);
-        if lhs != rhs {
+            metrics::record_pairings(Operation::Verify, n + m + 4);
+            if lhs != rhs {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Same as [Equation::verify], but first checks that every point in the commitments `c`, `d` and the
+    /// components `φ`, `θ` of the proof is valid (i.e. on the curve and in the prime-order subgroup).
+    ///
+    /// Returns [InvalidPoint] of the first invalid point, or the result of [Equation::verify] if all the
+    /// points are valid.
+    pub fn verify_checked(
+        &self,
+        cks: &CommitmentKeys<E>,
+        c: &[Com<<E as Pairing>::G1>],
+        d: &[Com<<E as Pairing>::G2>],
+        proof: &Proof<E>,
+    ) -> Result<bool, InvalidPoint> {
+        if let Some(i) = c.iter().position(|c_i| !c_i.is_valid()) {
+            return Err(InvalidPoint::C(i));
+        }
+        if let Some(j) = d.iter().position(|d_j| !d_j.is_valid()) {
+            return Err(InvalidPoint::D(j));
+        }
+        if let Some((index, _)) = proof
+            .phi
+            .as_ref()
+            .indexed_iter()
+            .find(|(_, p)| !is_valid(*p))
+        {
+            return Err(InvalidPoint::Phi(index));
+        }
+        if let Some((index, _)) = proof
+            .theta
+            .as_ref()
+            .indexed_iter()
+            .find(|(_, p)| !is_valid(*p))
+        {
+            return Err(InvalidPoint::Theta(index));
+        }
+        Ok(self.verify(cks, c, d, proof))
+    }
+
+    /// Verifies many instances `(c, d, π)` of this equation `E` at once. It is equivalent to calling
+    /// [Equation::verify] on every instance, but the four verification equations of all instances are
+    /// linearly combined with random scalars sampled from `rng` and checked by a single multi-pairing.
+    ///
+    /// The number of pairings is `2mN + n + 8` for `N` instances, instead of `N(4m + n + 16)`. i.e. only
+    /// the pairings with the commitments `c` are computed per instance, while the pairings with the constants
+    /// `a` and with the commitment keys (against `φ` and `θ`) are shared by all instances.
+    ///
+    /// ## Soundness
+    /// The random scalars are sampled as 128-bit integers (small exponents test). If any instance does not
+    /// verify, the aggregated check still passes with probability at most `2^-128` over the choice of the
+    /// random scalars. The scalars must therefore be sampled by the verifier from a cryptographically
+    /// secure `rng` that is not known to the prover in advance.
+    ///
+    /// Returns false if the verification fails or the dimensions of any instance are incorrect. Returns
+    /// true if `instances` is empty.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ark_bls12_381::Bls12_381 as E;
+    /// use ark_ec::pairing::Pairing;
+    /// use ark_std::{test_rng, UniformRand};
+    /// use gs_ppe::{setup, CommitmentKeys, Matrix, Variable};
+    ///
+    /// type G1 = <E as Pairing>::G1;
+    /// type G2 = <E as Pairing>::G2;
+    /// type G1Affine = <E as Pairing>::G1Affine;
+    /// type G2Affine = <E as Pairing>::G2Affine;
+    /// type Fr = <E as Pairing>::ScalarField;
+    ///
+    /// let rng = &mut test_rng();
+    /// let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    /// let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    /// let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+    ///
+    /// let cks = CommitmentKeys::<E>::rand(rng);
+    ///
+    /// // Two provers commit to the same witness with different randomness.
+    /// let instances = (0..2)
+    ///     .map(|_| {
+    ///         let x = Variable::<G1>::new(rng, x_value);
+    ///         let y = Variable::<G2>::new(rng, y_value);
+    ///         setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma)
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let equation = instances[0].equation.clone();
+    /// let instances = instances
+    ///     .into_iter()
+    ///     .map(|ps| (ps.c, ps.d, ps.proof))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert!(equation.verify_aggregate(rng, &cks, &instances));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn verify_aggregate<R: Rng>(
+        &self,
+        rng: &mut R,
+        cks: &CommitmentKeys<E>,
+        instances: &[(
+            Vec<Com<<E as Pairing>::G1>>,
+            Vec<Com<<E as Pairing>::G2>>,
+            Proof<E>,
+        )],
+    ) -> bool {
+        let (m, n) = self.gamma.dim();
+        if self.a.len() != n
+            || self.b.len() != m
+            || instances.iter().any(|(c, d, proof)| {
+                c.len() != m
+                    || d.len() != n
+                    || proof.phi.dim() != (2, 2)
+                    || proof.theta.dim() != (2, 2)
+            })
+        {
             return false;
         }
+        let u = &cks.u;
+        let v = &cks.v;
 
-        // Check Equation 4:
-        // Π e(a_j, d_j2) Π e(c_i2, b_i Π d_j2^gamma_ij) = t_T e(u12, φ12) e(u22, φ22) e(θ12, v12) e(θ22, v22)
-        let lhs = {
-            let a_d = self
-                .a
-                .iter()
-                .zip(d.iter())
-                .fold(PairingOutput::zero(), |acc, (a_j, d_j)| {
-                    acc + E::pairing(a_j, d_j.1)
-                });
-            let c_bd = c
-                .iter()
-                .enumerate()
-                .fold(PairingOutput::zero(), |acc, (i, c_i)| {
-                    acc + E::pairing(c_i.1, b_d[i])
-                });
-            a_d + c_bd
-        };
-        let rhs = self.target
-            + E::pairing(u.0 .1, proof.phi[(0, 1)])
-            + E::pairing(u.1 .1, proof.phi[(1, 1)])
-            + E::pairing(proof.theta[(0, 1)], v.0 .1)
-            + E::pairing(proof.theta[(1, 1)], v.1 .1);
+        let mut g1s = Vec::with_capacity(2 * m * instances.len() + n + 8);
+        let mut g2s = Vec::with_capacity(2 * m * instances.len() + n + 8);
+
+        // Aggregated values paired with the constants `a`, and with the commitment keys `u` and `v`.
+        let mut a_d = vec![<E as Pairing>::G2::zero(); n];
+        let mut phi_u = [<E as Pairing>::G2::zero(); 4]; // paired with u11, u21, u12, u22
+        let mut theta_v = [<E as Pairing>::G1::zero(); 4]; // paired with v11, v21, v12, v22
+        let mut target_exp = E::ScalarField::zero();
 
-        lhs == rhs
+        for (c, d, proof) in instances {
+            // 128-bit random scalars for Equation 1, 2, 3 and 4 respectively.
+            let (r1, r2, r3, r4) = (
+                E::ScalarField::from(rng.gen::<u128>()),
+                E::ScalarField::from(rng.gen::<u128>()),
+                E::ScalarField::from(rng.gen::<u128>()),
+                E::ScalarField::from(rng.gen::<u128>()),
+            );
+
+            // Equation 1 and 2:
+            // Π e(c_i1, (Π d_j1^gamma_ij)^r1 (b_i Π d_j2^gamma_ij)^r2)
+            // Equation 3 and 4 (without the constants `a`):
+            // Π e(c_i2, (Π d_j1^gamma_ij)^r3 (b_i Π d_j2^gamma_ij)^r4)
+            for (i, c_i) in c.iter().enumerate() {
+                let (d1_product, d2_product) = d.iter().enumerate().fold(
+                    (<E as Pairing>::G2::zero(), self.b[i].into_group()),
+                    |(acc1, acc2), (j, d_j)| {
+                        (
+                            acc1 + d_j.0.mul(self.gamma[(i, j)]),
+                            acc2 + d_j.1.mul(self.gamma[(i, j)]),
+                        )
+                    },
+                );
+                g1s.push(c_i.0.into_group());
+                g2s.push(d1_product.mul(r1) + d2_product.mul(r2));
+                g1s.push(c_i.1.into_group());
+                g2s.push(d1_product.mul(r3) + d2_product.mul(r4));
+            }
+
+            // Equation 3 and 4 (the constants `a`):
+            // Π e(a_j, d_j1^r3 d_j2^r4)
+            for (a_d_j, d_j) in a_d.iter_mut().zip(d.iter()) {
+                *a_d_j += d_j.0.mul(r3) + d_j.1.mul(r4);
+            }
+
+            phi_u[0] += proof.phi[(0, 0)].mul(r1) + proof.phi[(0, 1)].mul(r2);
+            phi_u[1] += proof.phi[(1, 0)].mul(r1) + proof.phi[(1, 1)].mul(r2);
+            phi_u[2] += proof.phi[(0, 0)].mul(r3) + proof.phi[(0, 1)].mul(r4);
+            phi_u[3] += proof.phi[(1, 0)].mul(r3) + proof.phi[(1, 1)].mul(r4);
+
+            theta_v[0] += proof.theta[(0, 0)].mul(r1) + proof.theta[(0, 1)].mul(r3);
+            theta_v[1] += proof.theta[(1, 0)].mul(r1) + proof.theta[(1, 1)].mul(r3);
+            theta_v[2] += proof.theta[(0, 0)].mul(r2) + proof.theta[(0, 1)].mul(r4);
+            theta_v[3] += proof.theta[(1, 0)].mul(r2) + proof.theta[(1, 1)].mul(r4);
+
+            target_exp += r4;
+        }
+
+        for (a_j, a_d_j) in self.a.iter().zip(a_d) {
+            g1s.push(a_j.into_group());
+            g2s.push(a_d_j);
+        }
+
+        // Move the right hand sides of the equations to the left hand side.
+        for (u_k, phi_u_k) in [u.0 .0, u.1 .0, u.0 .1, u.1 .1].into_iter().zip(phi_u) {
+            g1s.push(u_k.into_group());
+            g2s.push(phi_u_k.neg());
+        }
+        for (v_k, theta_v_k) in [v.0 .0, v.1 .0, v.0 .1, v.1 .1].into_iter().zip(theta_v) {
+            g1s.push(theta_v_k.neg());
+            g2s.push(v_k.into_group());
+        }
+
+        E::multi_pairing(g1s, g2s) == self.target.value().mul(target_exp)
+    }
+
+    /// Returns the canonical transcript encoding of this equation. See [transcript] for the details of the encoding.
+    pub fn to_transcript_bytes(&self) -> Vec<u8> {
+        let mut bytes = transcript::header(b"Equation");
+        self.append_transcript_body(&mut bytes);
+        bytes
+    }
+
+    /// Appends the body `a || b || gamma || target` of the transcript encoding.
+    pub(crate) fn append_transcript_body(&self, bytes: &mut Vec<u8>) {
+        transcript::append_vec(bytes, &self.a);
+        transcript::append_vec(bytes, &self.b);
+        transcript::append_matrix(bytes, &self.gamma);
+        transcript::append_element(bytes, &self.target.value());
     }
 }
 
@@ -227,30 +1090,31 @@ impl<E: Pairing> Add for Equation<E> {
             mut a,
             mut b,
             gamma,
-            mut target,
+            target,
         } = self;
 
         a.append(&mut rhs.a);
         b.append(&mut rhs.b);
 
-        target += rhs.target;
+        let target = target + rhs.target;
 
         // Compute [[ gamma1, 0], [0, gamma2]]
+        // The blocks are indexed directly (rather than appended), so that any of them can have zero rows or
+        // zero columns.
         let gamma = {
             let (m, n) = gamma.dim();
             let (m_prime, n_prime) = rhs.gamma.dim();
-            let mut gamma1 = gamma.take();
-            let gamma22 = rhs.gamma.take();
-
-            let zeros12 = ndarray::Array2::from_elem((m_prime, n_prime), E::ScalarField::zero());
-            let mut gamma2 = ndarray::Array2::from_elem((m, n), E::ScalarField::zero());
-
-            gamma1.append(ndarray::Axis(1), zeros12.view()).unwrap();
-            gamma2.append(ndarray::Axis(1), gamma22.view()).unwrap();
-
-            gamma1.append(ndarray::Axis(0), gamma2.view()).unwrap();
+            let block = ndarray::Array2::from_shape_fn((m + m_prime, n + n_prime), |(i, j)| {
+                if i < m && j < n {
+                    gamma[(i, j)]
+                } else if i >= m && j >= n {
+                    rhs.gamma[(i - m, j - n)]
+                } else {
+                    E::ScalarField::zero()
+                }
+            });
 
-            Matrix::from(gamma1)
+            Matrix::from(block)
         };
 
         Self {
@@ -261,3 +1125,229 @@ impl<E: Pairing> Add for Equation<E> {
         }
     }
 }
+
+/// Concatenates the pairs if both targets are [Target::Pairs], otherwise adds the values.
+impl<E: Pairing> Add for Target<E> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Target::Pairs(mut pairs), Target::Pairs(mut rhs_pairs)) => {
+                pairs.append(&mut rhs_pairs);
+                Target::Pairs(pairs)
+            }
+            (lhs, rhs) => Target::Value(lhs.value() + rhs.value()),
+        }
+    }
+}
+
+/// Negates the points `P_k` if the target is [Target::Pairs], i.e. `Π e(P_k^-1, Q_k) = T^-1`.
+impl<E: Pairing> Neg for Target<E> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        match self {
+            Target::Value(target) => Target::Value(-target),
+            Target::Pairs(pairs) => {
+                let (p, q): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+                Target::Pairs(negate_points(&p).into_iter().zip(q).collect())
+            }
+        }
+    }
+}
+
+/// Scales the points `P_k` if the target is [Target::Pairs], i.e. `Π e(P_k^k, Q_k) = T^k`.
+impl<E: Pairing> Mul<E::ScalarField> for Target<E> {
+    type Output = Self;
+
+    fn mul(self, k: E::ScalarField) -> Self {
+        match self {
+            Target::Value(target) => Target::Value(target.mul(k)),
+            Target::Pairs(pairs) => {
+                let (p, q): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+                let p = <E as Pairing>::G1::normalize_batch(
+                    &p.into_iter().map(|p_k| p_k * k).collect::<Vec<_>>(),
+                );
+                Target::Pairs(p.into_iter().zip(q).collect())
+            }
+        }
+    }
+}
+
+impl<E: Pairing> From<PairingOutput<E>> for Target<E> {
+    fn from(target: PairingOutput<E>) -> Self {
+        Target::Value(target)
+    }
+}
+
+impl<E: Pairing> From<Vec<(<E as Pairing>::G1Affine, <E as Pairing>::G2Affine)>> for Target<E> {
+    fn from(pairs: Vec<(<E as Pairing>::G1Affine, <E as Pairing>::G2Affine)>) -> Self {
+        Target::Pairs(pairs)
+    }
+}
+
+/// Computes the value of the target with [Target::value].
+impl<E: Pairing> From<Target<E>> for PairingOutput<E> {
+    fn from(target: Target<E>) -> Self {
+        target.value()
+    }
+}
+
+/// The inverse of the equation, i.e. `a`, `b`, `gamma` and `target` are negated:
+///
+/// Π e(a_j^-1, y_j) Π e(x_i, b_i^-1) ΠΠ e(x_i, y_j)^-gamma_ij = target^-1
+///
+/// which is satisfied by the same variables as the original equation.
+impl<E: Pairing> Neg for Equation<E> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            a: negate_points(&self.a),
+            b: negate_points(&self.b),
+            gamma: -self.gamma,
+            target: -self.target,
+        }
+    }
+}
+
+/// The addition of the equation and the inverse of `rhs`, i.e. `self + (-rhs)`. The variables of `rhs` are
+/// appended to the variables of this equation as in [Add], and the target is the difference of the targets.
+impl<E: Pairing> Sub for Equation<E> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.add(-rhs)
+    }
+}
+
+/// The same as [Sub], in the multiplicative notation of the target group (as [Div] for [Proof]).
+impl<E: Pairing> Div for Equation<E> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        self.sub(rhs)
+    }
+}
+
+/// The indices of the variables `x` and `y` which remain in the equation returned by [Equation::prune], in
+/// increasing order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruneMap {
+    pub x: Vec<usize>,
+    pub y: Vec<usize>,
+}
+
+impl PruneMap {
+    /// Returns the elements of `items` (e.g. the variables `x` or their commitments `c`) at the indices `x`.
+    pub fn select_x<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        self.x.iter().map(|&i| items[i].clone()).collect()
+    }
+
+    /// Returns the elements of `items` (e.g. the variables `y` or their commitments `d`) at the indices `y`.
+    pub fn select_y<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        self.y.iter().map(|&j| items[j].clone()).collect()
+    }
+}
+
+/// The invalid point found by [Equation::verify_checked], i.e. a point which is not on the curve or not in the
+/// prime-order subgroup.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InvalidPoint {
+    /// The commitment `c_i`.
+    C(usize),
+    /// The commitment `d_j`.
+    D(usize),
+    /// The element `φ_ij` of the proof.
+    Phi((usize, usize)),
+    /// The element `θ_ij` of the proof.
+    Theta((usize, usize)),
+}
+
+impl std::fmt::Display for InvalidPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidPoint::C(i) => write!(f, "invalid point in commitment c[{}]", i),
+            InvalidPoint::D(j) => write!(f, "invalid point in commitment d[{}]", j),
+            InvalidPoint::Phi((i, j)) => write!(f, "invalid point phi[{}][{}] in proof", i, j),
+            InvalidPoint::Theta((i, j)) => write!(f, "invalid point theta[{}][{}] in proof", i, j),
+        }
+    }
+}
+
+impl std::error::Error for InvalidPoint {}
+
+/// Returns the inverses of the points.
+fn negate_points<A: AffineRepr>(points: &[A]) -> Vec<A> {
+    let negated = points
+        .iter()
+        .map(|p| p.into_group().neg())
+        .collect::<Vec<_>>();
+    A::Group::normalize_batch(&negated)
+}
+
+/// Checks that the point is on the curve and in the prime-order subgroup.
+fn is_valid<A: AffineRepr>(p: &A) -> bool {
+    p.check().is_ok()
+}
+
+/// Computes the target Π e(a_j, y_j) Π e(x_i, b_i) ΠΠ e(x_i, y_j)^gamma_ij.
+fn compute_target<E: Pairing>(
+    a: &[<E as Pairing>::G1Affine],
+    b: &[<E as Pairing>::G2Affine],
+    gamma: &Matrix<E::ScalarField>,
+    x: &[<E as Pairing>::G1Affine],
+    y: &[<E as Pairing>::G2Affine],
+) -> PairingOutput<E> {
+    let ay_product = a
+        .iter()
+        .zip(y.iter())
+        .fold(PairingOutput::zero(), |acc, (a_j, y_j)| {
+            acc + E::pairing(a_j, y_j)
+        });
+    let xb_product = x
+        .iter()
+        .zip(b.iter())
+        .fold(PairingOutput::zero(), |acc, (x_i, b_i)| {
+            acc + E::pairing(x_i, b_i)
+        });
+
+    let mut xy_product = PairingOutput::zero();
+    for (j, y_j) in y.iter().enumerate() {
+        for (i, x_i) in x.iter().enumerate() {
+            xy_product += E::pairing(x_i, y_j).mul(gamma[(i, j)]);
+        }
+    }
+    ay_product + xb_product + xy_product
+}
+
+/// Groups the indices of the terms which have the same constant and the same line (the row in `lines`),
+/// in the order of their first occurrences.
+fn merge_groups<T: PartialEq, F: PartialEq>(
+    constants: &[T],
+    lines: ArrayView2<F>,
+) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for k in 0..constants.len() {
+        match groups
+            .iter_mut()
+            .find(|g| constants[g[0]] == constants[k] && lines.row(g[0]) == lines.row(k))
+        {
+            Some(g) => g.push(k),
+            None => groups.push(vec![k]),
+        }
+    }
+    groups
+}
+
+/// Returns the sum of the variables at the indices in the `group`.
+fn merge_variables<G: CurveGroup>(group: &[usize], variables: &[Variable<G>]) -> Variable<G> {
+    let (value, rand) = group[1..].iter().fold(
+        (
+            variables[group[0]].value.into_group(),
+            variables[group[0]].rand,
+        ),
+        |(value, rand), &k| (value + variables[k].value, rand + variables[k].rand),
+    );
+    Variable::with_randomness(value.into(), rand)
+}
@@ -1,10 +1,42 @@
 //! Defines the struct [ExtractKey], the key `ek` for extracting `SXDH Commitments`` defined in section 6.2 in
 //! the paper [Fuc10](https://eprint.iacr.org/2010/233.pdf).
 
-use ark_ec::pairing::Pairing;
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_std::{rand::Rng, UniformRand};
 use std::ops::{Mul, Neg};
 
-use crate::com::Com;
+use crate::{com::Com, verifiable_encryption::VerifiableCiphertext, CommitmentKeys, ProofSystem};
+
+/// The commitments `c` in G1 and `d` in G2 of a proof system, e.g. returned by
+/// [ProofSystem::extractable_commitments](crate::ProofSystem::extractable_commitments), for extracting the
+/// committed variables in a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtractableCommitments<E: Pairing> {
+    pub c: Vec<Com<<E as Pairing>::G1>>,
+    pub d: Vec<Com<<E as Pairing>::G2>>,
+}
+
+impl<E: Pairing> ExtractableCommitments<E> {
+    /// Extracts the commitments `c` by [ExtractKey::extract_1] and `d` by [ExtractKey::extract_2], and returns
+    /// the values of the committed variables `x` and `y`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "ExtractableCommitments::extract",
+            skip_all,
+            fields(m = self.c.len(), n = self.d.len())
+        )
+    )]
+    pub fn extract(
+        &self,
+        ek: &ExtractKey<E>,
+    ) -> (Vec<<E as Pairing>::G1Affine>, Vec<<E as Pairing>::G2Affine>) {
+        (
+            self.c.iter().map(|c_i| ek.extract_1(c_i)).collect(),
+            self.d.iter().map(|d_j| ek.extract_2(d_j)).collect(),
+        )
+    }
+}
 
 /// The key `ek` for extracting `SXDH Commitments`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -26,7 +58,8 @@ impl<E: Pairing> ExtractKey<E> {
     ///
     /// let rng = &mut test_rng();
     ///
-    /// let (cks, ek) = CommitmentKeys::<E>::rand_ex(rng);
+    /// let keys = CommitmentKeys::<E>::rand_ex(rng);
+    /// let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
     ///
     /// let x_value = G1Affine::rand(rng);
     /// let x = Variable::new(rng, x_value);
@@ -54,7 +87,8 @@ impl<E: Pairing> ExtractKey<E> {
     ///
     /// let rng = &mut test_rng();
     ///
-    /// let (cks, ek) = CommitmentKeys::<E>::rand_ex(rng);
+    /// let keys = CommitmentKeys::<E>::rand_ex(rng);
+    /// let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
     ///
     /// let y_value = G2Affine::rand(rng);
     /// let y = Variable::new(rng, y_value);
@@ -67,4 +101,121 @@ impl<E: Pairing> ExtractKey<E> {
     pub fn extract_2(&self, c: &Com<<E as Pairing>::G2>) -> E::G2Affine {
         (c.0.mul(&self.1.neg()) + c.1).into()
     }
+
+    /// Extracts the commitments `c` and `d` of the proof system, and checks that the extracted values satisfy the
+    /// equation, e.g. for an authority to confirm that the values are the ones the proof was about, and not the
+    /// result of extracting with a mismatched extract key. Returns the extracted values of `x` and `y`.
+    ///
+    /// Returns [ExtractionError::InvalidProof] if the proof system is not consistent or the proof does not verify
+    /// with the commitment keys `cks`, and [ExtractionError::EquationMismatch] if the extracted values do not
+    /// hit the target of the equation.
+    #[allow(clippy::type_complexity)]
+    pub fn extract_and_verify(
+        &self,
+        cks: &CommitmentKeys<E>,
+        ps: &ProofSystem<E>,
+    ) -> Result<(Vec<<E as Pairing>::G1Affine>, Vec<<E as Pairing>::G2Affine>), ExtractionError>
+    {
+        if !ps.is_consistent() || !ps.equation.verify(cks, &ps.c, &ps.d, &ps.proof) {
+            return Err(ExtractionError::InvalidProof);
+        }
+        let (x, y) = ps.extractable_commitments().extract(self);
+        if !ps.equation.verify_target_with_values(&x, &y) {
+            return Err(ExtractionError::EquationMismatch);
+        }
+        Ok((x, y))
+    }
+
+    /// Splits the extract key `(a1, a2)` into two additive shares `(a1 - t1, a2 - t2)` and `(t1, t2)` for random
+    /// `t1` and `t2` generated by `rng`, so that both holders of the shares must cooperate to extract a commitment
+    /// by [PartialExtraction::combine]. Each share alone is uniformly random and reveals nothing about the key.
+    pub fn split<R: Rng>(&self, rng: &mut R) -> (ExtractKeyShare<E>, ExtractKeyShare<E>) {
+        let (t1, t2) = (E::ScalarField::rand(rng), E::ScalarField::rand(rng));
+        (
+            ExtractKeyShare(self.0 - t1, self.1 - t2),
+            ExtractKeyShare(t1, t2),
+        )
+    }
+
+    /// Decrypts the ciphertext of a variable in Group 1, i.e. extracts its commitment by [ExtractKey::extract_1].
+    pub fn decrypt_1(&self, ct: &VerifiableCiphertext<<E as Pairing>::G1>) -> E::G1Affine {
+        self.extract_1(&ct.com)
+    }
+
+    /// Decrypts the ciphertext of a variable in Group 2, i.e. extracts its commitment by [ExtractKey::extract_2].
+    pub fn decrypt_2(&self, ct: &VerifiableCiphertext<<E as Pairing>::G2>) -> E::G2Affine {
+        self.extract_2(&ct.com)
+    }
 }
+
+/// A share of an extract key, returned by [ExtractKey::split]. It is the share `(a1_k, a2_k)` of the key `(a1, a2)`
+/// such that `a1 = a1_0 + a1_1` and `a2 = a2_0 + a2_1`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExtractKeyShare<E: Pairing>(pub E::ScalarField, pub E::ScalarField);
+
+impl<E: Pairing> ExtractKeyShare<E> {
+    /// Computes the partial extraction `c1^-a1_k` of the commitment `c = (c1, c2)` in Group 1 with this share.
+    pub fn partial_extract_1(
+        &self,
+        c: &Com<<E as Pairing>::G1>,
+    ) -> PartialExtraction<<E as Pairing>::G1> {
+        PartialExtraction {
+            com: *c,
+            partial: c.0.mul(&self.0.neg()).into(),
+        }
+    }
+
+    /// Computes the partial extraction `c1^-a2_k` of the commitment `c = (c1, c2)` in Group 2 with this share.
+    pub fn partial_extract_2(
+        &self,
+        c: &Com<<E as Pairing>::G2>,
+    ) -> PartialExtraction<<E as Pairing>::G2> {
+        PartialExtraction {
+            com: *c,
+            partial: c.0.mul(&self.1.neg()).into(),
+        }
+    }
+}
+
+/// The partial extraction `partial` of the commitment `com` with an [ExtractKeyShare]. Note that `c2 * partial`
+/// alone is the committed value blinded by the other share, so it reveals nothing about the value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PartialExtraction<G: CurveGroup> {
+    pub com: Com<G>,
+    pub partial: G::Affine,
+}
+
+impl<G: CurveGroup> PartialExtraction<G> {
+    /// Combines the partial extractions of the same commitment `c = (c1, c2)` with both shares of the extract
+    /// key, i.e. returns `c2 c1^-a1_0 c1^-a1_1`, which is the value [ExtractKey::extract_1] (or
+    /// [ExtractKey::extract_2]) returns.
+    ///
+    /// ## Panics
+    /// Panics if the partial extractions are of different commitments.
+    pub fn combine(a: &Self, b: &Self) -> G::Affine {
+        assert_eq!(a.com, b.com);
+        (a.com.1 + a.partial + b.partial).into()
+    }
+}
+
+/// The error returned by [ExtractKey::extract_and_verify].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExtractionError {
+    /// The proof system is not consistent or the proof does not verify.
+    InvalidProof,
+    /// The extracted values do not satisfy the equation, e.g. the extract key does not match the commitment keys.
+    EquationMismatch,
+}
+
+impl std::fmt::Display for ExtractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractionError::InvalidProof => write!(f, "the proof does not verify"),
+            ExtractionError::EquationMismatch => {
+                write!(f, "the extracted values do not satisfy the equation")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractionError {}
@@ -6,28 +6,56 @@ pub use com::Com;
 pub mod commit;
 pub use commit::CommitmentKeys;
 
+pub mod dual;
+
 pub mod equation;
-pub use equation::Equation;
+pub use equation::{Equation, Target};
 
 pub mod extract;
-pub use extract::ExtractKey;
+pub use extract::{ExtractKey, ExtractableCommitments};
 
 pub mod matrix;
 pub use matrix::Matrix;
 
+pub mod metrics;
+
 pub mod prove;
 pub use prove::Proof;
 
 pub mod randomness;
 pub use randomness::Randomness;
 
+pub mod sparse;
+pub use sparse::SparseMatrix;
+
+pub mod stamped;
+
+pub mod statements;
+
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
+pub mod transcript;
+
 pub mod variable;
-pub use variable::Variable;
+pub use variable::{CommittedVariable, Variable};
 
-use ark_ec::pairing::{Pairing, PairingOutput};
-use ark_std::{rand::Rng, Zero};
+pub mod verifiable_encryption;
+
+use ark_ec::{
+    pairing::{Pairing, PairingOutput},
+    AffineRepr,
+};
+use ark_std::{
+    rand::{Rng, SeedableRng},
+    Zero,
+};
+use rand_chacha::ChaCha20Rng;
 use std::ops::{Add, Mul};
 
+use com::ComRandomness;
+use prove::StreamingProver;
+
 /// Setup the proof system over the Pairing Product Equation:
 ///
 /// ∏e(a, y) ∏e(x, b) ∏∏e(x, y)^gamma = T
@@ -37,8 +65,15 @@ use std::ops::{Add, Mul};
 ///
 /// It returns the Proof System that contains the equation, commitments, and the proof.
 ///
+/// A slot whose value is public can be given as [Variable::public], which is committed with zero randomness, so
+/// that the verifier can check its commitment against the announced value with [ProofSystem::check_public_slots].
+///
 /// ## Panics
 /// Panics if dimension of gamma does not match the length of `xb` and `ay`. i.e. gamma.dim() != (xb.len(), ay.len())
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "setup", skip_all, fields(m = xb.len(), n = ay.len()))
+)]
 pub fn setup<E: Pairing, R: Rng>(
     rng: &mut R,
     cks: &CommitmentKeys<E>,
@@ -47,7 +82,19 @@ pub fn setup<E: Pairing, R: Rng>(
     gamma: &Matrix<E::ScalarField>,
 ) -> ProofSystem<E> {
     assert_eq!(gamma.dim(), (xb.len(), ay.len()));
+    let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
+    setup_with_internal_randomness(cks, ay, xb, gamma, z)
+}
 
+/// Same as [setup], but the proof is generated with the internal randomness `z` as in
+/// [Proof::new_with_internal_randomness].
+fn setup_with_internal_randomness<E: Pairing>(
+    cks: &CommitmentKeys<E>,
+    ay: &[(<E as Pairing>::G1Affine, Variable<<E as Pairing>::G2>)],
+    xb: &[(Variable<<E as Pairing>::G1>, <E as Pairing>::G2Affine)],
+    gamma: &Matrix<E::ScalarField>,
+    z: Matrix<E::ScalarField>,
+) -> ProofSystem<E> {
     let ay_product = ay.iter().fold(PairingOutput::zero(), |acc, (a, y)| {
         acc + E::pairing(a, y.value)
     });
@@ -73,10 +120,229 @@ pub fn setup<E: Pairing, R: Rng>(
         a,
         b,
         gamma: gamma.clone(),
-        target,
+        target: target.into(),
     };
     let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect();
     let d = y.iter().map(|y_i| cks.v.commit(y_i)).collect();
+    let proof = Proof::new_with_internal_randomness(cks, &equation, &x, &y, z);
+    ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    }
+}
+
+/// The inputs `ay`, `xb` and `gamma` of [setup] for one equation, e.g. for [setup_batch].
+#[derive(Clone, Debug)]
+pub struct SetupInstance<E: Pairing> {
+    pub ay: Vec<(<E as Pairing>::G1Affine, Variable<<E as Pairing>::G2>)>,
+    pub xb: Vec<(Variable<<E as Pairing>::G1>, <E as Pairing>::G2Affine)>,
+    pub gamma: Matrix<E::ScalarField>,
+}
+
+/// Setup the proof systems of many independent equations under the same commitment keys `cks`. It produces
+/// the same proof systems as calling [setup] on each instance in order with the same rng.
+///
+/// The internal randomness of all the proofs is sampled from `rng` first, and then the instances are proved
+/// independently, in parallel if the feature `parallel` is enabled. It is a plain batched setup, i.e. no
+/// precomputation (e.g. fixed-base tables of `cks`) is shared across the instances, so that it is not faster
+/// than calling [setup] in a loop unless the feature `parallel` is enabled.
+///
+/// ## Panics
+/// Panics if the dimension of gamma of an instance does not match the length of its `xb` and `ay`.
+/// i.e. gamma.dim() != (xb.len(), ay.len())
+pub fn setup_batch<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    instances: Vec<SetupInstance<E>>,
+) -> Vec<ProofSystem<E>> {
+    for instance in instances.iter() {
+        assert_eq!(instance.gamma.dim(), (instance.xb.len(), instance.ay.len()));
+    }
+    let zs = instances
+        .iter()
+        .map(|_| Matrix::<E::ScalarField>::rand(rng, 2, 2))
+        .collect::<Vec<_>>();
+
+    let prove = |(instance, z): (SetupInstance<E>, Matrix<E::ScalarField>)| {
+        setup_with_internal_randomness(cks, &instance.ay, &instance.xb, &instance.gamma, z)
+    };
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        instances.into_par_iter().zip(zs).map(prove).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        instances.into_iter().zip(zs).map(prove).collect()
+    }
+}
+
+/// Same as [setup], except that the internal randomness of the proof is derived deterministically from the `seed`
+/// by [Proof::new_with_seed], so that the proof system is identical (byte-for-byte) for the same inputs. Unlike
+/// [setup_deterministic], the randomness of the variables is given by the caller.
+///
+/// The `seed` must be secret and uniformly random, and never reused for different statements.
+///
+/// ## Panics
+/// Panics if dimension of gamma does not match the length of `xb` and `ay`. i.e. gamma.dim() != (xb.len(), ay.len())
+pub fn setup_with_seed<E: Pairing>(
+    seed: &[u8; 32],
+    cks: &CommitmentKeys<E>,
+    ay: &[(<E as Pairing>::G1Affine, Variable<<E as Pairing>::G2>)],
+    xb: &[(Variable<<E as Pairing>::G1>, <E as Pairing>::G2Affine)],
+    gamma: &Matrix<E::ScalarField>,
+) -> ProofSystem<E> {
+    setup(&mut ChaCha20Rng::from_seed(*seed), cks, ay, xb, gamma)
+}
+
+/// Same as [setup], except that every random value is derived deterministically from the `seed`, so that
+/// a stateless prover can re-derive the same proof system. The variables are given by their values only.
+///
+/// The randomness is derived by the ChaCha20 stream cipher keyed by the `seed`, with separated streams
+/// (the 64-bit stream id) for each purpose:
+/// - stream 1: the randomness `r` of the variables `x`, in order.
+/// - stream 2: the randomness `s` of the variables `y`, in order.
+/// - stream 3: the internal randomness `Z` of the proof.
+///
+/// Scalars are sampled from the streams by `UniformRand`. The output is identical across platforms.
+///
+/// The `seed` must be secret and uniformly random, and never reused for different statements.
+///
+/// ## Panics
+/// Panics if dimension of gamma does not match the length of `xb` and `ay`. i.e. gamma.dim() != (xb.len(), ay.len())
+pub fn setup_deterministic<E: Pairing>(
+    seed: [u8; 32],
+    cks: &CommitmentKeys<E>,
+    ay: &[(<E as Pairing>::G1Affine, <E as Pairing>::G2Affine)],
+    xb: &[(<E as Pairing>::G1Affine, <E as Pairing>::G2Affine)],
+    gamma: &Matrix<E::ScalarField>,
+) -> ProofSystem<E> {
+    let stream = |id: u64| {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        rng.set_stream(id);
+        rng
+    };
+
+    let r_rng = &mut stream(1);
+    let xb = xb
+        .iter()
+        .map(|(x, b)| (Variable::new(r_rng, *x), *b))
+        .collect::<Vec<_>>();
+    let s_rng = &mut stream(2);
+    let ay = ay
+        .iter()
+        .map(|(a, y)| (*a, Variable::new(s_rng, *y)))
+        .collect::<Vec<_>>();
+
+    setup(&mut stream(3), cks, &ay, &xb, gamma)
+}
+
+/// Same as [setup], but the terms `(a_j, y_j)`, `(x_i, b_i)` and the rows of `gamma` are consumed from iterators
+/// in a single pass, without buffering copies of the slices. The terms `(x_i, b_i)` are consumed together with the
+/// rows `gamma_i` of `gamma`, while the variables `y` are buffered as they are combined with every `x_i` (see
+/// [Proof::new_from_iter]). It produces the same proof system as [setup] given the same rng.
+///
+/// Note that the returned [ProofSystem] still contains the constants `a`, `b`, `gamma` and the commitments in
+/// full, so that the peak memory is about the size of the output plus the variables `y`. At large sizes, the
+/// saving is the slices `ay` and `xb` and the copies of the variables made by [setup], e.g. on BLS12-381 a term
+/// `(a_j, y_j)` or `(x_i, b_i)` takes 368 bytes, i.e. about 3.7 MB per copy of 10,000 terms.
+///
+/// ## Panics
+/// Panics if the rows of gamma do not match the length of `xb` and `ay`, i.e. the number of rows != `xb.len()` or
+/// any row has length != `ay.len()`.
+pub fn setup_from_iter<'g, E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    ay: impl ExactSizeIterator<Item = (<E as Pairing>::G1Affine, Variable<<E as Pairing>::G2>)>,
+    xb: impl ExactSizeIterator<Item = (Variable<<E as Pairing>::G1>, <E as Pairing>::G2Affine)>,
+    gamma_rows: impl ExactSizeIterator<Item = &'g [E::ScalarField]>,
+) -> ProofSystem<E> {
+    assert_eq!(gamma_rows.len(), xb.len());
+    let (m, n) = (xb.len(), ay.len());
+
+    let (a, y): (Vec<_>, Vec<_>) = ay.unzip();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect();
+    let mut target = a
+        .iter()
+        .zip(y.iter())
+        .fold(PairingOutput::zero(), |acc, (a_j, y_j)| {
+            acc + E::pairing(a_j, y_j.value)
+        });
+
+    let mut prover = StreamingProver::new(cks, &a, &y);
+    let mut b = Vec::with_capacity(m);
+    let mut c = Vec::with_capacity(m);
+    let mut gamma = Vec::with_capacity(m * n);
+    for ((x_i, b_i), gamma_i) in xb.zip(gamma_rows) {
+        prover.push(&x_i, b_i, gamma_i.iter());
+        // w_i = b_i Π y_j^gamma_ij for the term e(x_i, w_i) of the target.
+        let w_i = y
+            .iter()
+            .zip(gamma_i.iter())
+            .fold(b_i.into_group(), |acc, (y_j, gamma_ij)| {
+                acc + y_j.value.mul(gamma_ij)
+            });
+        target += E::pairing(x_i.value, w_i);
+        b.push(b_i);
+        c.push(cks.u.commit(&x_i));
+        gamma.extend_from_slice(gamma_i);
+    }
+    let proof = prover.finish(Matrix::<E::ScalarField>::rand(rng, 2, 2));
+
+    let equation = Equation::new(
+        a,
+        b,
+        Matrix::from_ndarray(ndarray::Array2::from_shape_vec((m, n), gamma).unwrap()),
+        target,
+    );
+    ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    }
+}
+
+/// Same as [setup], but over the variables committed in advance (e.g. by [CommittedVariable::commit]), so
+/// that the commitments `c` and `d` of the proof system are the given commitments. The commitments must be
+/// made under `cks`, otherwise the proof does not verify against them.
+///
+/// ## Panics
+/// Panics if dimension of gamma does not match the length of `xb` and `ay`. i.e. gamma.dim() != (xb.len(), ay.len())
+pub fn setup_with_commitments<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    ay: &[(
+        <E as Pairing>::G1Affine,
+        CommittedVariable<<E as Pairing>::G2>,
+    )],
+    xb: &[(
+        CommittedVariable<<E as Pairing>::G1>,
+        <E as Pairing>::G2Affine,
+    )],
+    gamma: &Matrix<E::ScalarField>,
+) -> ProofSystem<E> {
+    assert_eq!(gamma.dim(), (xb.len(), ay.len()));
+
+    let x = xb
+        .iter()
+        .map(|(x_i, _)| *x_i.variable())
+        .collect::<Vec<_>>();
+    let y = ay
+        .iter()
+        .map(|(_, y_j)| *y_j.variable())
+        .collect::<Vec<_>>();
+    let equation = Equation::from_witnesses(
+        ay.iter().map(|(a_j, _)| *a_j).collect(),
+        xb.iter().map(|(_, b_i)| *b_i).collect(),
+        gamma.clone(),
+        &x.iter().map(|x_i| x_i.value).collect::<Vec<_>>(),
+        &y.iter().map(|y_j| y_j.value).collect::<Vec<_>>(),
+    );
+    let c = xb.iter().map(|(x_i, _)| *x_i.commitment()).collect();
+    let d = ay.iter().map(|(_, y_j)| *y_j.commitment()).collect();
     let proof = Proof::new(rng, cks, &equation, &x, &y);
     ProofSystem {
         equation,
@@ -86,6 +352,38 @@ pub fn setup<E: Pairing, R: Rng>(
     }
 }
 
+/// Same as [setup], but also returns the openings of the commitments in [SetupOutput], i.e. each commitment
+/// `c_i` (resp. `d_j`) together with the randomness `r_i` (resp. `s_j`) of the variable it commits to.
+///
+/// ## Panics
+/// Panics if dimension of gamma does not match the length of `xb` and `ay`. i.e. gamma.dim() != (xb.len(), ay.len())
+pub fn setup_with_openings<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    ay: &[(<E as Pairing>::G1Affine, Variable<<E as Pairing>::G2>)],
+    xb: &[(Variable<<E as Pairing>::G1>, <E as Pairing>::G2Affine)],
+    gamma: &Matrix<E::ScalarField>,
+) -> SetupOutput<E> {
+    let proof_system = setup(rng, cks, ay, xb, gamma);
+    let cr = proof_system
+        .c
+        .iter()
+        .zip(xb)
+        .map(|(c_i, (x_i, _))| (*c_i, x_i.rand))
+        .collect();
+    let ds = proof_system
+        .d
+        .iter()
+        .zip(ay)
+        .map(|(d_j, (_, y_j))| (*d_j, y_j.rand))
+        .collect();
+    SetupOutput {
+        proof_system,
+        cr,
+        ds,
+    }
+}
+
 /// The Proof System over the Pairing Product Equation. It consists of
 /// - The specified pairing product `equation`.
 /// - The commitments `c` and `d` which commit to the variables `x` and `y` respectively.
@@ -98,9 +396,150 @@ pub struct ProofSystem<E: Pairing> {
     pub proof: Proof<E>,
 }
 
+/// The output of [setup_with_openings], i.e. the `proof_system` and the openings `cr` and `ds` of the commitments
+/// `c` and `d`, as created, in the form of [ComRandomness].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupOutput<E: Pairing> {
+    pub proof_system: ProofSystem<E>,
+    pub cr: Vec<ComRandomness<<E as Pairing>::G1>>,
+    pub ds: Vec<ComRandomness<<E as Pairing>::G2>>,
+}
+
+/// The dimensions of a [ProofSystem], returned by [ProofSystem::dims].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProofSystemDims {
+    /// The number of variables `x` in the equation.
+    pub m: usize,
+    /// The number of variables `y` in the equation.
+    pub n: usize,
+    /// The number of commitments `c`.
+    pub c: usize,
+    /// The number of commitments `d`.
+    pub d: usize,
+}
+
+/// The components `(equation, c, d, proof)` of a [ProofSystem].
+pub type ProofSystemComponents<E> = (
+    Equation<E>,
+    Vec<Com<<E as Pairing>::G1>>,
+    Vec<Com<<E as Pairing>::G2>>,
+    Proof<E>,
+);
+
+/// The references to the components `(equation, c, d, proof)` of a [ProofSystem].
+pub type ProofSystemComponentsRef<'a, E> = (
+    &'a Equation<E>,
+    &'a [Com<<E as Pairing>::G1>],
+    &'a [Com<<E as Pairing>::G2>],
+    &'a Proof<E>,
+);
+
 impl<E: Pairing> ProofSystem<E> {
+    /// Returns the components `(equation, c, d, proof)` of this proof system.
+    pub fn into_components(self) -> ProofSystemComponents<E> {
+        (self.equation, self.c, self.d, self.proof)
+    }
+
+    /// Returns the references to the components `(equation, c, d, proof)` of this proof system.
+    pub fn components(&self) -> ProofSystemComponentsRef<'_, E> {
+        (&self.equation, &self.c, &self.d, &self.proof)
+    }
+
+    /// Returns the dimension `(m, n)` of the equation and the numbers of the commitments `c` and `d`.
+    pub fn dims(&self) -> ProofSystemDims {
+        let (m, n) = self.equation.dim();
+        ProofSystemDims {
+            m,
+            n,
+            c: self.c.len(),
+            d: self.d.len(),
+        }
+    }
+
+    /// Checks that the dimensions of the components agree, i.e. there is one commitment for each variable in the
+    /// equation, and `φ` and `θ` are 2x2 matrices. It does not verify the proof.
+    pub fn is_consistent(&self) -> bool {
+        let ProofSystemDims { m, n, c, d } = self.dims();
+        self.equation.a.len() == n
+            && self.equation.b.len() == m
+            && c == m
+            && d == n
+            && self.proof.phi.dim() == (2, 2)
+            && self.proof.theta.dim() == (2, 2)
+    }
+
+    /// Checks that the commitments of the public slots are the trivial commitments [Com::trivial] to the
+    /// announced values, i.e. `c_i = (0, x_i)` for each `(i, x_i)` in `x` and `d_j = (0, y_j)` for each
+    /// `(j, y_j)` in `y`, as committed by [setup] with public variables (see [Variable::public]). Returns false
+    /// if an index is out of range.
+    pub fn check_public_slots(
+        &self,
+        x: &[(usize, <E as Pairing>::G1Affine)],
+        y: &[(usize, <E as Pairing>::G2Affine)],
+    ) -> bool {
+        x.iter()
+            .all(|&(i, x_i)| self.c.get(i) == Some(&Com::trivial(x_i)))
+            && y.iter()
+                .all(|&(j, y_j)| self.d.get(j) == Some(&Com::trivial(y_j)))
+    }
+
+    /// Removes the terms which contribute nothing to the equation by [Equation::prune], together with the
+    /// commitments of the removed variables. The proof is unchanged, as the removed variables do not appear in
+    /// the verification, so that the pruned proof system verifies whenever this proof system does.
+    ///
+    /// Returns the pruned proof system and the [PruneMap](equation::PruneMap) of the remaining variables.
+    ///
+    /// ## Panics
+    /// Panics if the proof system is not consistent, see [ProofSystem::is_consistent].
+    pub fn prune(&self) -> (ProofSystem<E>, equation::PruneMap) {
+        assert!(self.is_consistent());
+        let (equation, map) = self.equation.prune();
+        let c = map.select_x(&self.c);
+        let d = map.select_y(&self.d);
+        let proof_system = ProofSystem {
+            equation,
+            c,
+            d,
+            proof: self.proof.clone(),
+        };
+        (proof_system, map)
+    }
+
+    /// Returns the commitments `c` and `d` for extracting the committed variables with an extract key.
+    pub fn extractable_commitments(&self) -> ExtractableCommitments<E> {
+        ExtractableCommitments {
+            c: self.c.clone(),
+            d: self.d.clone(),
+        }
+    }
+
+    /// Returns the sum of the proof systems by the homomorphic addition, i.e. the same as `((p1 + p2) + p3) + ...`.
+    /// Returns the proof system of the empty equation (see [Equation::sum]) if `proof_systems` is empty.
+    pub fn sum<I: IntoIterator<Item = ProofSystem<E>>>(proof_systems: I) -> ProofSystem<E> {
+        proof_systems
+            .into_iter()
+            .reduce(Add::add)
+            .unwrap_or_else(|| ProofSystem {
+                equation: Equation::sum([]),
+                c: vec![],
+                d: vec![],
+                proof: Proof {
+                    phi: Matrix::from_elem(2, 2, <E as Pairing>::G2Affine::zero()),
+                    theta: Matrix::from_elem(2, 2, <E as Pairing>::G1Affine::zero()),
+                },
+            })
+    }
+
     /// Randomize the commitments `c` and `d` and the proof by applying the functions `RdCom` and `RdProof`
     /// define in the paper [Fuc10](https://eprint.iacr.org/2010/233.pdf).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "ProofSystem::randomize",
+            skip_all,
+            fields(m = self.c.len(), n = self.d.len())
+        )
+    )]
     pub fn randomize<R: Rng>(mut self, rng: &mut R, cks: &CommitmentKeys<E>) -> Self {
         let cr = self
             .c
@@ -116,6 +555,23 @@ impl<E: Pairing> ProofSystem<E> {
         self.proof.randomize(rng, cks, &self.equation, &cr, &ds);
         self
     }
+
+    /// Returns the canonical transcript encoding of this proof system, e.g. for deriving a Fiat-Shamir
+    /// challenge. See [transcript] for the details of the encoding.
+    pub fn to_transcript_bytes(&self) -> Vec<u8> {
+        let mut bytes = transcript::header(b"ProofSystem");
+        self.equation.append_transcript_body(&mut bytes);
+        transcript::append_len(&mut bytes, self.c.len());
+        self.c
+            .iter()
+            .for_each(|c_i| c_i.append_transcript_body(&mut bytes));
+        transcript::append_len(&mut bytes, self.d.len());
+        self.d
+            .iter()
+            .for_each(|d_j| d_j.append_transcript_body(&mut bytes));
+        self.proof.append_transcript_body(&mut bytes);
+        bytes
+    }
 }
 
 /// Homomorphic addition of two Proof Systems, defined in section 7.2 of the paper.
@@ -1,10 +1,17 @@
 //! Provides a struct [Matrix] type that wraps around [ndarray::Array] for matrix operations required in the GS Proof.
 
-use std::ops::{Add, Index, IndexMut, Mul, Neg};
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
 
+use ark_ec::{
+    pairing::{Pairing, PairingOutput},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::Field;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
 use ark_std::{rand::Rng, UniformRand, Zero};
-use ndarray::{Array, Ix2};
+use ndarray::{s, Array, Axis, Ix2, Zip};
+
+use crate::SparseMatrix;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Matrix<F>
@@ -34,6 +41,29 @@ where
         }
     }
 
+    /// An array with `m` rows and zero columns, e.g. the matrix `gamma` of an equation with `m` variables `x`
+    /// and no variables `y`.
+    pub fn empty_rows(m: usize) -> Self {
+        Self {
+            inner: Array::from_shape_vec((m, 0), Vec::new()).unwrap(),
+        }
+    }
+
+    /// An array with zero rows and `n` columns, e.g. the matrix `gamma` of an equation with no variables `x`
+    /// and `n` variables `y`.
+    pub fn empty_cols(n: usize) -> Self {
+        Self {
+            inner: Array::from_shape_vec((0, n), Vec::new()).unwrap(),
+        }
+    }
+
+    /// An array with zero rows and zero columns, e.g. the matrix `gamma` of an equation without variables.
+    pub fn empty() -> Self {
+        Self {
+            inner: Array::from_shape_vec((0, 0), Vec::new()).unwrap(),
+        }
+    }
+
     pub fn from_elem(rows: usize, cols: usize, elem: F) -> Self {
         Self {
             inner: Array::from_elem((rows, cols), elem),
@@ -79,10 +109,442 @@ where
         self.inner
     }
 
+    /// Wraps the array `arr` without converting its elements, e.g. for an array computed by other users of
+    /// [ndarray]. See also the conversion [From] arrays of other element types.
+    pub fn from_ndarray(arr: Array<F, Ix2>) -> Self {
+        Self { inner: arr }
+    }
+
+    /// Returns the underlying array. Same as [Matrix::take].
+    pub fn to_ndarray(self) -> Array<F, Ix2> {
+        self.inner
+    }
+
+    /// Returns the rows of the matrix, moving the elements out of the matrix without cloning them
+    /// (unless the elements are not in row-major order). See also [Matrix::to_vecs].
+    pub fn to_owned_rows(self) -> Vec<Vec<F>> {
+        let (rows, cols) = self.dim();
+        let mut flat = self.into_flat_vec().into_iter();
+        (0..rows)
+            .map(|_| flat.by_ref().take(cols).collect())
+            .collect()
+    }
+
+    /// Returns the elements of the matrix in row-major order, moving the elements out of the matrix
+    /// without cloning them (unless the elements are not in row-major order).
+    pub fn into_flat_vec(self) -> Vec<F> {
+        let inner = if self.inner.is_standard_layout() {
+            self.inner
+        } else {
+            self.inner.as_standard_layout().into_owned()
+        };
+        let len = inner.len();
+        let (mut elements, offset) = inner.into_raw_vec_and_offset();
+        elements.drain(..offset.unwrap_or(0));
+        elements.truncate(len);
+        elements
+    }
+
+    /// Returns a copy of the elements of the matrix in row-major order. See also [Matrix::into_flat_vec].
+    pub fn flatten_row_major(&self) -> Vec<F> {
+        self.inner.iter().cloned().collect()
+    }
+
+    /// Creates a `rows x cols` matrix of the elements `data` in row-major order, e.g. the output of
+    /// [Matrix::flatten_row_major].
+    ///
+    /// ## Panics
+    /// Panics if `data.len() != rows * cols`.
+    pub fn from_flat_row_major(data: Vec<F>, rows: usize, cols: usize) -> Self {
+        assert_eq!(data.len(), rows * cols);
+        Self {
+            inner: Array::from_shape_vec((rows, cols), data).unwrap(),
+        }
+    }
+
     #[inline]
     pub fn dim(&self) -> (usize, usize) {
         self.inner.dim()
     }
+
+    /// Returns the diagonal entries `m[(i, i)]` for `i < min(rows, cols)`.
+    pub fn diagonal_entries(&self) -> Vec<F> {
+        self.inner.diag().to_vec()
+    }
+
+    /// Returns the trace of a square matrix, i.e. the sum of the diagonal entries.
+    ///
+    /// ## Panics
+    /// Panics if the matrix is not square.
+    pub fn trace(&self) -> F
+    where
+        F: Zero,
+    {
+        assert!(self.inner.is_square());
+        self.inner
+            .diag()
+            .iter()
+            .fold(F::zero(), |acc, m_ii| acc + m_ii.clone())
+    }
+
+    /// Returns the elements of a 2x2 matrix as a reference to a fixed-size array in row-major order,
+    /// i.e. `m.as_2x2_array().unwrap()[i][j] == m[(i, j)]`, without copying the elements.
+    ///
+    /// Returns `None` if the matrix is not 2x2, or its elements are not contiguous in row-major order.
+    pub fn as_2x2_array(&self) -> Option<&[[F; 2]; 2]> {
+        if self.dim() != (2, 2) {
+            return None;
+        }
+        let slice = self.inner.as_slice()?;
+        // SAFETY: the slice contains exactly 4 elements in row-major order, and `[[F; 2]; 2]` has the same
+        // layout as `[F; 4]`. The returned reference borrows `self`, which owns the elements.
+        Some(unsafe { &*(slice.as_ptr() as *const [[F; 2]; 2]) })
+    }
+
+    /// Checks that all the entries are zero, which is true for a matrix with zero rows or zero columns. It
+    /// returns at the first non-zero entry.
+    pub fn is_zero(&self) -> bool
+    where
+        F: Zero,
+    {
+        self.inner.iter().all(|e| e.is_zero())
+    }
+
+    /// Converts to a [SparseMatrix] which stores only the non-zero entries.
+    pub fn to_sparse(&self) -> SparseMatrix<F>
+    where
+        F: Zero,
+    {
+        SparseMatrix::from(self)
+    }
+
+    /// Returns the Hadamard (element-wise) product of this matrix and `other`. It is not the matrix multiplication.
+    ///
+    /// ## Panics
+    /// Panics if the dimensions of the matrices are different.
+    pub fn hadamard<K, Out>(&self, other: &Matrix<K>) -> Matrix<Out>
+    where
+        F: Mul<K, Output = Out>,
+        K: Clone,
+        Out: Clone,
+    {
+        assert_eq!(self.dim(), other.dim());
+        Matrix {
+            inner: Zip::from(&self.inner)
+                .and(&other.inner)
+                .map_collect(|x, y| x.clone().mul(y.clone())),
+        }
+    }
+
+    /// Computes the Hadamard (element-wise) product of this matrix and `other` in place.
+    ///
+    /// ## Panics
+    /// Panics if the dimensions of the matrices are different.
+    pub fn hadamard_inplace(&mut self, other: &Matrix<F>)
+    where
+        F: Mul<Output = F>,
+    {
+        assert_eq!(self.dim(), other.dim());
+        Zip::from(&mut self.inner)
+            .and(&other.inner)
+            .for_each(|x, y| *x = x.clone().mul(y.clone()));
+    }
+
+    /// Swaps the rows `i` and `j` in place.
+    ///
+    /// ## Panics
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        let rows = self.dim().0;
+        assert!(i < rows && j < rows);
+        if i == j {
+            return;
+        }
+        let (row_i, row_j) = self.inner.multi_slice_mut((s![i, ..], s![j, ..]));
+        Zip::from(row_i).and(row_j).for_each(std::mem::swap);
+    }
+
+    /// Swaps the columns `i` and `j` in place.
+    ///
+    /// ## Panics
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn swap_cols(&mut self, i: usize, j: usize) {
+        let cols = self.dim().1;
+        assert!(i < cols && j < cols);
+        if i == j {
+            return;
+        }
+        let (col_i, col_j) = self.inner.multi_slice_mut((s![.., i], s![.., j]));
+        Zip::from(col_i).and(col_j).for_each(std::mem::swap);
+    }
+
+    /// Multiplies the row `i` by `scalar` in place.
+    ///
+    /// ## Panics
+    /// Panics if `i` is out of bounds.
+    pub fn scale_row(&mut self, i: usize, scalar: F)
+    where
+        F: Mul<Output = F>,
+    {
+        assert!(i < self.dim().0);
+        self.inner
+            .row_mut(i)
+            .iter_mut()
+            .for_each(|x| *x = x.clone().mul(scalar.clone()));
+    }
+
+    /// Adds the row `source` multiplied by `scalar` to the row `target` in place.
+    ///
+    /// ## Panics
+    /// Panics if `target` or `source` is out of bounds.
+    pub fn add_row_multiple(&mut self, target: usize, source: usize, scalar: F)
+    where
+        F: Add<Output = F> + Mul<Output = F>,
+    {
+        let rows = self.dim().0;
+        assert!(target < rows && source < rows);
+        let source_row = self.inner.row(source).to_owned();
+        Zip::from(self.inner.row_mut(target))
+            .and(&source_row)
+            .for_each(|x, y| *x = x.clone().add(y.clone().mul(scalar.clone())));
+    }
+
+    /// Returns the row-echelon form of the matrix by Gaussian elimination, together with the indices of the
+    /// pivot columns in increasing order. The pivots are normalized to one, and the number of pivots is the rank
+    /// of the matrix.
+    pub fn row_echelon_form(&self) -> (Matrix<F>, Vec<usize>)
+    where
+        F: Field,
+    {
+        let (rows, cols) = self.dim();
+        let mut echelon = self.clone();
+        let mut pivots = Vec::new();
+        for j in 0..cols {
+            let r = pivots.len();
+            if r == rows {
+                break;
+            }
+            let Some(p) = (r..rows).find(|&i| !echelon[(i, j)].is_zero()) else {
+                continue;
+            };
+            echelon.swap_rows(r, p);
+            let inverse = echelon[(r, j)].inverse().expect("the pivot is non-zero");
+            echelon.scale_row(r, inverse);
+            for i in r + 1..rows {
+                let factor = echelon[(i, j)];
+                if !factor.is_zero() {
+                    echelon.add_row_multiple(i, r, -factor);
+                }
+            }
+            pivots.push(j);
+        }
+        (echelon, pivots)
+    }
+
+    /// Returns the inverse of the square matrix by Gauss-Jordan elimination, or `None` if the matrix is singular.
+    ///
+    /// ## Panics
+    /// Panics if the matrix is not square.
+    pub fn inverse(&self) -> Option<Matrix<F>>
+    where
+        F: Field,
+    {
+        let (n, cols) = self.dim();
+        assert_eq!(n, cols);
+        let augmented = Array::from_shape_fn((n, 2 * n), |(i, j)| {
+            if j < n {
+                self[(i, j)]
+            } else if j - n == i {
+                F::one()
+            } else {
+                F::zero()
+            }
+        });
+        let (mut echelon, pivots) = Matrix::from_ndarray(augmented).row_echelon_form();
+        if !pivots.iter().copied().eq(0..n) {
+            return None;
+        }
+        for r in (0..n).rev() {
+            for i in 0..r {
+                let factor = echelon[(i, r)];
+                if !factor.is_zero() {
+                    echelon.add_row_multiple(i, r, -factor);
+                }
+            }
+        }
+        Some(Self {
+            inner: echelon.inner.slice(s![.., n..]).to_owned(),
+        })
+    }
+
+    /// Permutes the rows such that the `k`-th row becomes the `permutation[k]`-th row of the original matrix.
+    ///
+    /// ## Panics
+    /// Panics if `permutation` is not a permutation of the row indices.
+    pub fn permute_rows(&mut self, permutation: &[usize]) {
+        assert!(is_permutation(permutation, self.dim().0));
+        self.inner = self.inner.select(Axis(0), permutation);
+    }
+
+    /// Permutes the columns such that the `k`-th column becomes the `permutation[k]`-th column of the original matrix.
+    ///
+    /// ## Panics
+    /// Panics if `permutation` is not a permutation of the column indices.
+    pub fn permute_cols(&mut self, permutation: &[usize]) {
+        assert!(is_permutation(permutation, self.dim().1));
+        self.inner = self.inner.select(Axis(1), permutation);
+    }
+}
+
+impl<F> Matrix<F>
+where
+    F: Field,
+{
+    /// Creates the Vandermonde matrix of the `points` up to the `degree`, i.e. the `points.len() x (degree + 1)`
+    /// matrix with the entries `points[i]^j`, e.g. the matrix `gamma[i][j] = alpha^(i*j)` for the points
+    /// `alpha^i`. The product with the coefficients of a polynomial of the `degree` is the evaluations of the
+    /// polynomial at the points.
+    pub fn vandermonde(points: &[F], degree: usize) -> Self {
+        Self {
+            inner: Array::from_shape_fn((points.len(), degree + 1), |(i, j)| {
+                points[i].pow([j as u64])
+            }),
+        }
+    }
+
+    /// Creates the column matrix (dim = `(points.len(), 1)`) of the evaluations of the polynomial with the
+    /// coefficients `coeffs` (in increasing degree) at the `points`, computed by [Matrix::evaluate_polynomial].
+    pub fn from_polynomial_evaluations(coeffs: &[F], points: &[F]) -> Self {
+        Self {
+            inner: Array::from_shape_fn((points.len(), 1), |(i, _)| {
+                Self::evaluate_polynomial(coeffs, points[i])
+            }),
+        }
+    }
+
+    /// Evaluates the polynomial with the coefficients `p` (in increasing degree) at `x` by Horner's method.
+    /// Returns zero if `p` is empty.
+    pub fn evaluate_polynomial(p: &[F], x: F) -> F {
+        p.iter().rev().fold(F::zero(), |acc, p_k| acc * x + p_k)
+    }
+
+    /// Creates a random lower-triangular `n x n` matrix, i.e. the entries above the diagonal are zero and the
+    /// others are uniformly random. The matrix is invertible with overwhelming probability, as the diagonal
+    /// entries are non-zero.
+    pub fn rand_lower_triangular<R: Rng>(rng: &mut R, n: usize) -> Self {
+        Self {
+            inner: Array::from_shape_fn(
+                (n, n),
+                |(i, j)| {
+                    if j <= i {
+                        F::rand(rng)
+                    } else {
+                        F::zero()
+                    }
+                },
+            ),
+        }
+    }
+
+    /// Creates a random `rows x cols` matrix whose `i`-th row sums to `target_sums[i]`. The entries except the
+    /// last column are uniformly random, and the last column is determined by the sums.
+    ///
+    /// ## Panics
+    /// Panics if `target_sums.len() != rows`, or if `cols` is zero and a target sum is non-zero.
+    pub fn rand_with_fixed_row_sums<R: Rng>(
+        rng: &mut R,
+        rows: usize,
+        cols: usize,
+        target_sums: &[F],
+    ) -> Self {
+        assert_eq!(target_sums.len(), rows);
+        if cols == 0 {
+            assert!(target_sums.iter().all(|sum| sum.is_zero()));
+            return Self::empty_rows(rows);
+        }
+        let mut inner = Array::from_shape_fn((rows, cols), |(_, j)| {
+            if j + 1 < cols {
+                F::rand(rng)
+            } else {
+                F::zero()
+            }
+        });
+        for (mut row, sum) in inner.outer_iter_mut().zip(target_sums) {
+            let partial = row.iter().fold(F::zero(), |acc, e| acc + e);
+            row[cols - 1] = *sum - partial;
+        }
+        Self { inner }
+    }
+
+    /// Creates a random symmetric `n x n` matrix, i.e. the entries on and above the diagonal are uniformly
+    /// random and the entries below are the mirrored ones.
+    pub fn rand_symmetric<R: Rng>(rng: &mut R, n: usize) -> Self {
+        let mut inner = Array::from_elem((n, n), F::zero());
+        for i in 0..n {
+            for j in i..n {
+                let e = F::rand(rng);
+                inner[(i, j)] = e;
+                inner[(j, i)] = e;
+            }
+        }
+        Self { inner }
+    }
+}
+
+impl<A> Matrix<A>
+where
+    A: AffineRepr,
+{
+    /// Creates a `rows x cols` matrix of the points `elements` in row-major order, e.g. for [Proof::from_parts](crate::Proof::from_parts).
+    ///
+    /// ## Panics
+    /// Panics if `elements.len() != rows * cols`.
+    pub fn from_affine_slice(elements: &[A], rows: usize, cols: usize) -> Self {
+        assert_eq!(elements.len(), rows * cols);
+        Self {
+            inner: Array::from_shape_vec((rows, cols), elements.to_vec()).unwrap(),
+        }
+    }
+
+    /// Same as [Matrix::from_affine_slice], but the points `elements` are in projective representation, which
+    /// are normalized to affine representation in a batch.
+    ///
+    /// ## Panics
+    /// Panics if `elements.len() != rows * cols`.
+    pub fn from_projective_slice(elements: &[A::Group], rows: usize, cols: usize) -> Self {
+        assert_eq!(elements.len(), rows * cols);
+        Self {
+            inner: Array::from_shape_vec((rows, cols), A::Group::normalize_batch(elements))
+                .unwrap(),
+        }
+    }
+
+    /// Computes `Σ e(m[(i, i)], g2)` over the diagonal of a square matrix of points in G1, with a single pairing
+    /// `e(Σ m[(i, i)], g2)`.
+    ///
+    /// ## Panics
+    /// Panics if the matrix is not square.
+    pub fn identity_pairing_product<E>(&self, g2: E::G2Affine) -> PairingOutput<E>
+    where
+        E: Pairing<G1Affine = A>,
+    {
+        assert!(self.inner.is_square());
+        let diagonal_sum = self
+            .inner
+            .diag()
+            .iter()
+            .fold(E::G1::zero(), |acc, m_ii| acc + m_ii);
+        E::pairing(diagonal_sum, g2)
+    }
+}
+
+impl<F> From<Matrix<F>> for Array<F, Ix2>
+where
+    F: Clone,
+{
+    fn from(matrix: Matrix<F>) -> Self {
+        matrix.take()
+    }
 }
 
 impl<F, G> From<Array<G, Ix2>> for Matrix<F>
@@ -135,33 +597,61 @@ where
     }
 }
 
+/// Serializes the matrix as `rows (u32) || cols (u32) || elements`, where the elements are in row-major order.
+///
+/// The format is not compatible with the one of version 0.1 (i.e. the format of `Vec<Vec<F>>`), see the changelog.
 impl<F> CanonicalSerialize for Matrix<F>
 where
     F: Clone + CanonicalSerialize,
 {
     fn serialize_with_mode<W: ark_serialize::Write>(
         &self,
-        writer: W,
+        mut writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        Vec::<Vec<F>>::serialize_with_mode(&self.to_vecs(), writer, compress)
+        let (rows, cols) = self.dim();
+        for len in [rows, cols] {
+            u32::try_from(len)
+                .map_err(|_| ark_serialize::SerializationError::InvalidData)?
+                .serialize_with_mode(&mut writer, compress)?;
+        }
+        self.inner
+            .iter()
+            .try_for_each(|x| x.serialize_with_mode(&mut writer, compress))
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
-        Vec::<Vec<F>>::serialized_size(&self.to_vecs(), compress)
+        2 * 0u32.serialized_size(compress)
+            + self
+                .inner
+                .iter()
+                .map(|x| x.serialized_size(compress))
+                .sum::<usize>()
     }
 }
 
+/// Deserializes the matrix in the format of [CanonicalSerialize] for [Matrix], i.e.
+/// `rows (u32) || cols (u32) || elements` in row-major order.
 impl<F> CanonicalDeserialize for Matrix<F>
 where
     F: Clone + CanonicalDeserialize,
 {
     fn deserialize_with_mode<R: ark_serialize::Read>(
-        reader: R,
+        mut reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        Vec::<Vec<F>>::deserialize_with_mode(reader, compress, validate).map(Self::from_vecs)
+        let rows = u32::deserialize_with_mode(&mut reader, compress, validate)? as usize;
+        let cols = u32::deserialize_with_mode(&mut reader, compress, validate)? as usize;
+        let len = rows
+            .checked_mul(cols)
+            .ok_or(ark_serialize::SerializationError::InvalidData)?;
+        let elements = (0..len)
+            .map(|_| F::deserialize_with_mode(&mut reader, compress, validate))
+            .collect::<Result<Vec<_>, _>>()?;
+        let inner = Array::from_shape_vec((rows, cols), elements)
+            .map_err(|_| ark_serialize::SerializationError::InvalidData)?;
+        Ok(Self { inner })
     }
 }
 
@@ -179,6 +669,22 @@ where
     }
 }
 
+impl<F, K> Sub<Matrix<K>> for Matrix<F>
+where
+    F: Clone + Sub<K, Output = F>,
+    K: Clone,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Matrix<K>) -> Self::Output {
+        Self {
+            inner: self.inner - rhs.inner,
+        }
+    }
+}
+
+/// The Hadamard (element-wise) product of two matrices, which is not the matrix multiplication.
+/// See also [Matrix::hadamard].
 impl<F, K> Mul<Matrix<K>> for Matrix<F>
 where
     F: Clone + Mul<K, Output = F>,
@@ -205,3 +711,12 @@ where
         }
     }
 }
+
+/// Checks if `permutation` contains each of the indices `0..len` exactly once.
+fn is_permutation(permutation: &[usize], len: usize) -> bool {
+    let mut seen = vec![false; len];
+    permutation.len() == len
+        && permutation
+            .iter()
+            .all(|&k| k < len && !std::mem::replace(&mut seen[k], true))
+}
@@ -0,0 +1,115 @@
+//! Operation counters for the expensive functions in this crate, i.e. `commit`, `Proof::new`, `Proof::randomize`
+//! and `Equation::verify`. The counters are enabled by the feature `metrics`, otherwise recording is a no-op.
+//!
+//! The counters are thread-local. For each function, they count the number of calls, pairings and scalar
+//! multiplications (in G1 and G2), which are recorded where the operations are performed. For equation `E`
+//! with `gamma` of dimension `(m, n)`, the expected counts are:
+//! - `commit`: 4 scalar multiplications.
+//! - `Proof::new`: `4m + 4n + 24` scalar multiplications.
+//! - `Proof::randomize`: `6m + 6n + 24` scalar multiplications.
+//! - `Proof::add_key_randomness`: 16 scalar multiplications, counted as a call of `Proof::randomize`.
+//! - `Equation::verify`: `3m + 2n + 16` pairings (plus the number of pairs if the target is `Target::Pairs`) and
+//!   `3mn` scalar multiplications, if the verification passes.
+//!   The verification returns early if one of the four verification equations fails.
+//!
+//! The feature `tracing` complements the counters with `tracing` spans named after the functions `setup`,
+//! `Proof::new`, `Equation::verify`, `ProofSystem::randomize` and `ExtractableCommitments::extract`, which carry
+//! the dimension `(m, n)` as the fields `m` and `n`. The spans are not compiled if the feature is disabled.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! gs_ppe::metrics::reset();
+//! equation.verify(&cks, &c, &d, &proof);
+//! assert_eq!(gs_ppe::metrics::snapshot().verify.pairings, 3 * m + 2 * n + 16);
+//! ```
+
+use std::ops::Mul;
+
+/// The instrumented functions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Commit,
+    Prove,
+    Randomize,
+    Verify,
+}
+
+/// The counts of operations performed by an instrumented function.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    pub calls: usize,
+    pub pairings: usize,
+    pub scalar_muls: usize,
+}
+
+/// The snapshot of counters for all the instrumented functions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub commit: OpCounts,
+    pub prove: OpCounts,
+    pub randomize: OpCounts,
+    pub verify: OpCounts,
+}
+
+#[cfg(feature = "metrics")]
+thread_local! {
+    static METRICS: std::cell::Cell<Metrics> = std::cell::Cell::new(Metrics::default());
+}
+
+/// Returns the counters of the current thread. Always returns zero counters if the feature `metrics` is disabled.
+pub fn snapshot() -> Metrics {
+    #[cfg(feature = "metrics")]
+    return METRICS.with(|m| m.get());
+    #[cfg(not(feature = "metrics"))]
+    Metrics::default()
+}
+
+/// Resets the counters of the current thread to zero.
+pub fn reset() {
+    #[cfg(feature = "metrics")]
+    METRICS.with(|m| m.set(Metrics::default()));
+}
+
+/// Records a call to the instrumented function `op`.
+#[inline(always)]
+pub(crate) fn record_call(op: Operation) {
+    record(op, |counts| counts.calls += 1);
+}
+
+/// Records the number of pairings performed by the instrumented function `op`.
+#[inline(always)]
+pub(crate) fn record_pairings(op: Operation, pairings: usize) {
+    record(op, |counts| counts.pairings += pairings);
+}
+
+/// Records the number of scalar multiplications performed by the instrumented function `op`.
+#[inline(always)]
+pub(crate) fn record_scalar_muls(op: Operation, scalar_muls: usize) {
+    record(op, |counts| counts.scalar_muls += scalar_muls);
+}
+
+/// Multiplies the point `p` by the scalar `s`, and records the scalar multiplication performed by the
+/// instrumented function `op`.
+#[inline(always)]
+pub(crate) fn scalar_mul<P: Mul<S>, S>(op: Operation, p: P, s: S) -> P::Output {
+    record_scalar_muls(op, 1);
+    p.mul(s)
+}
+
+#[inline(always)]
+fn record(op: Operation, f: impl FnOnce(&mut OpCounts)) {
+    #[cfg(feature = "metrics")]
+    METRICS.with(|m| {
+        let mut metrics = m.get();
+        f(match op {
+            Operation::Commit => &mut metrics.commit,
+            Operation::Prove => &mut metrics.prove,
+            Operation::Randomize => &mut metrics.randomize,
+            Operation::Verify => &mut metrics.verify,
+        });
+        m.set(metrics);
+    });
+    #[cfg(not(feature = "metrics"))]
+    let _ = (op, f);
+}
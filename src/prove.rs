@@ -1,16 +1,39 @@
 //! Defines the struct [Proof] which implements the functions `Prove` and `RdProof` notated in section 6.3
 //! in the paper [Fuc10](https://eprint.iacr.org/2010/233.pdf).
 
-use ark_ec::pairing::Pairing;
-use ark_std::{rand::Rng, Zero};
-use std::ops::{Add, Div, Mul, Neg};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::{
+    rand::{Rng, SeedableRng},
+    Zero,
+};
+use rand_chacha::ChaCha20Rng;
+use std::{
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Add, Div, Mul, Neg},
+};
 
 use crate::{
-    com::ComRandomness, commit::CommitmentKey, CommitmentKeys, Equation, Matrix, Randomness,
-    Variable,
+    com::ComRandomness,
+    commit::CommitmentKey,
+    dual::Dual,
+    equation::InvalidPoint,
+    metrics::{self, Operation},
+    transcript,
+    variable::fmt_short_hex,
+    Com, CommitmentKeys, Equation, Matrix, Randomness, Variable,
 };
 
 /// Contains the components `φ` and `θ` as a Groth-Sahai proof (without internal randomness `Z`).
+///
+/// Note that there is no simulator which produces a valid proof for given commitments `c` and `d` without the
+/// witness, even under the keys of [CommitmentKeys::setup_wi](crate::CommitmentKeys::setup_wi): the verification
+/// equations are in `GT`, so adjusting random `φ` and `θ` to satisfy them requires the discrete logarithms of the
+/// commitments, which the trapdoor of the keys does not reveal. Witness indistinguishability only states that
+/// proofs for different witnesses of the same statement are identically distributed.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Proof<E: Pairing> {
     pub(crate) phi: Matrix<<E as Pairing>::G2Affine>,
@@ -61,28 +84,121 @@ impl<E: Pairing> Proof<E> {
     ///
     /// assert!(equation.verify(&cks, &c, &d, &proof));
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Proof::new", skip_all, fields(m = x.len(), n = y.len()))
+    )]
     pub fn new<R: Rng>(
         rng: &mut R,
         cks: &CommitmentKeys<E>,
         equ: &Equation<E>,
         x: &[Variable<<E as Pairing>::G1>],
         y: &[Variable<<E as Pairing>::G2>],
+    ) -> Self {
+        let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
+        Self::new_with_internal_randomness(cks, equ, x, y, z)
+    }
+
+    /// Constructs the proof from the components `φ` and `θ`, e.g. deserialized by third-party code.
+    ///
+    /// Returns [InvalidProof] if `φ` or `θ` is not a 2x2 matrix, or one of their elements is not on the curve
+    /// or not in the prime-order subgroup.
+    pub fn from_parts(
+        phi: Matrix<<E as Pairing>::G2Affine>,
+        theta: Matrix<<E as Pairing>::G1Affine>,
+    ) -> Result<Self, InvalidProof> {
+        if phi.dim() != (2, 2) {
+            return Err(InvalidProof::PhiDim(phi.dim()));
+        }
+        if theta.dim() != (2, 2) {
+            return Err(InvalidProof::ThetaDim(theta.dim()));
+        }
+        if let Some((index, _)) = phi
+            .as_ref()
+            .indexed_iter()
+            .find(|(_, p)| p.check().is_err())
+        {
+            return Err(InvalidProof::Point(InvalidPoint::Phi(index)));
+        }
+        if let Some((index, _)) = theta
+            .as_ref()
+            .indexed_iter()
+            .find(|(_, p)| p.check().is_err())
+        {
+            return Err(InvalidProof::Point(InvalidPoint::Theta(index)));
+        }
+        Ok(Self { phi, theta })
+    }
+
+    /// Returns the components `φ` and `θ` of the proof.
+    pub fn into_parts(
+        self,
+    ) -> (
+        Matrix<<E as Pairing>::G2Affine>,
+        Matrix<<E as Pairing>::G1Affine>,
+    ) {
+        (self.phi, self.theta)
+    }
+
+    /// Returns the proof of the dual equation [Equation::transpose] over the pairing [Dual], i.e. `φ` and `θ`
+    /// are exchanged, which verifies with the transposed commitment keys and the exchanged commitments.
+    pub fn transpose(&self) -> Proof<Dual<E>> {
+        Proof {
+            phi: self.theta.clone(),
+            theta: self.phi.clone(),
+        }
+    }
+
+    /// Same as [Proof::new], except that the internal randomness `Z` is sampled from the ChaCha20 stream cipher
+    /// keyed by the `seed`, so that the proof is identical (byte-for-byte) for the same inputs.
+    ///
+    /// The `seed` must be secret and uniformly random, and never reused for different statements.
+    ///
+    /// ## Panics
+    /// Panics if 'a.len() != x.len()' or 'b.len() != y.len()', where `a` and `b` are the constants in the equation `E`.
+    pub fn new_with_seed(
+        seed: &[u8; 32],
+        cks: &CommitmentKeys<E>,
+        equ: &Equation<E>,
+        x: &[Variable<<E as Pairing>::G1>],
+        y: &[Variable<<E as Pairing>::G2>],
+    ) -> Self {
+        Self::new(&mut ChaCha20Rng::from_seed(*seed), cks, equ, x, y)
+    }
+
+    /// Same as [Proof::new], except that the internal randomness `Z` (a 2x2 matrix) is supplied by the caller
+    /// instead of being sampled inside. The proof is deterministic given the inputs, e.g. for reproducible test
+    /// vectors or for deriving all the randomness of the prover from a seed.
+    ///
+    /// `Z` must be sampled uniformly at random and used only once, otherwise the proof does not hide the witnesses.
+    ///
+    /// ## Panics
+    /// Panics if 'a.len() != x.len()', 'b.len() != y.len()' or 'z.dim() != (2, 2)', where `a` and `b` are
+    /// the constants in the equation `E`.
+    pub fn new_with_internal_randomness(
+        cks: &CommitmentKeys<E>,
+        equ: &Equation<E>,
+        x: &[Variable<<E as Pairing>::G1>],
+        y: &[Variable<<E as Pairing>::G2>],
+        z: Matrix<E::ScalarField>,
     ) -> Self {
         assert_eq!(equ.a.len(), y.len());
         assert_eq!(equ.b.len(), x.len());
+        assert_eq!(z.dim(), (2, 2));
 
-        let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
+        let z_u = z_u::<E>(&z, &cks.u, Operation::Prove);
+        let z_v = z_v::<E>(&z, &cks.v, Operation::Prove);
+        // In a linear equation, the products of the variables by their exponents in `gamma` are the identity.
+        let linear = equ.is_linear();
 
-        let z_u = z_u::<E>(&z, &cks.u);
-        let z_v = z_v::<E>(&z, &cks.v);
-
-        let (t11, t12, t21, t22) = t11_t12_t21_t22::<E>(
+        let (t11, t12, t21, t22) = Randomness::gamma_t_product(
             &x.iter().map(|x_i| x_i.rand).collect::<Vec<_>>(),
             &y.iter().map(|y_j| y_j.rand).collect::<Vec<_>>(),
             &equ.gamma,
         );
 
-        let phi11 = cks.v.0 .0.mul(t11) + cks.v.1 .0.mul(t12);
+        let phi11 = metrics::scalar_mul(Operation::Prove, cks.v.0 .0, t11)
+            + metrics::scalar_mul(Operation::Prove, cks.v.1 .0, t12);
 
         let phi12 = {
             let b_product = equ
@@ -90,9 +206,11 @@ impl<E: Pairing> Proof<E> {
                 .iter()
                 .zip(x.iter())
                 .fold(<E as Pairing>::G2::zero(), |acc, (b_i, x_i)| {
-                    acc + b_i.mul(x_i.rand.0)
+                    acc + metrics::scalar_mul(Operation::Prove, *b_i, x_i.rand.0)
                 });
-            let y_product =
+            let y_product = if linear {
+                <E as Pairing>::G2::zero()
+            } else {
                 y.iter()
                     .enumerate()
                     .fold(<E as Pairing>::G2::zero(), |acc, (j, y_j)| {
@@ -102,12 +220,17 @@ impl<E: Pairing> Proof<E> {
                             .fold(E::ScalarField::zero(), |acc, (i, x_i)| {
                                 acc + equ.gamma[(i, j)].mul(x_i.rand.0)
                             });
-                        acc + y_j.value.mul(exp)
-                    });
-            cks.v.0 .1.mul(t11) + cks.v.1 .1.mul(t12) + b_product + y_product
+                        acc + metrics::scalar_mul(Operation::Prove, y_j.value, exp)
+                    })
+            };
+            metrics::scalar_mul(Operation::Prove, cks.v.0 .1, t11)
+                + metrics::scalar_mul(Operation::Prove, cks.v.1 .1, t12)
+                + b_product
+                + y_product
         };
 
-        let phi21 = cks.v.0 .0.mul(t21) + cks.v.1 .0.mul(t22);
+        let phi21 = metrics::scalar_mul(Operation::Prove, cks.v.0 .0, t21)
+            + metrics::scalar_mul(Operation::Prove, cks.v.1 .0, t22);
 
         let phi22 = {
             let b_product = equ
@@ -115,9 +238,11 @@ impl<E: Pairing> Proof<E> {
                 .iter()
                 .zip(x.iter())
                 .fold(<E as Pairing>::G2::zero(), |acc, (b_i, x_i)| {
-                    acc + b_i.mul(x_i.rand.1)
+                    acc + metrics::scalar_mul(Operation::Prove, *b_i, x_i.rand.1)
                 });
-            let y_product =
+            let y_product = if linear {
+                <E as Pairing>::G2::zero()
+            } else {
                 y.iter()
                     .enumerate()
                     .fold(<E as Pairing>::G2::zero(), |acc, (j, y_j)| {
@@ -127,9 +252,13 @@ impl<E: Pairing> Proof<E> {
                             .fold(E::ScalarField::zero(), |acc, (i, x_i)| {
                                 acc + equ.gamma[(i, j)].mul(x_i.rand.1)
                             });
-                        acc + y_j.value.mul(exp)
-                    });
-            cks.v.0 .1.mul(t21) + cks.v.1 .1.mul(t22) + b_product + y_product
+                        acc + metrics::scalar_mul(Operation::Prove, y_j.value, exp)
+                    })
+            };
+            metrics::scalar_mul(Operation::Prove, cks.v.0 .1, t21)
+                + metrics::scalar_mul(Operation::Prove, cks.v.1 .1, t22)
+                + b_product
+                + y_product
         };
 
         // Compute φ as in (7).
@@ -143,9 +272,11 @@ impl<E: Pairing> Proof<E> {
                 .iter()
                 .zip(y.iter())
                 .fold(<E as Pairing>::G1::zero(), |acc, (a_j, y_j)| {
-                    acc + a_j.mul(y_j.rand.0)
+                    acc + metrics::scalar_mul(Operation::Prove, *a_j, y_j.rand.0)
                 });
-            let x_product =
+            let x_product = if linear {
+                <E as Pairing>::G1::zero()
+            } else {
                 x.iter()
                     .enumerate()
                     .fold(<E as Pairing>::G1::zero(), |acc, (i, x_i)| {
@@ -155,8 +286,9 @@ impl<E: Pairing> Proof<E> {
                             .fold(E::ScalarField::zero(), |acc, (j, y_j)| {
                                 acc + equ.gamma[(i, j)].mul(y_j.rand.0)
                             });
-                        acc + x_i.value.mul(exp)
-                    });
+                        acc + metrics::scalar_mul(Operation::Prove, x_i.value, exp)
+                    })
+            };
             a_product + x_product
         };
 
@@ -168,9 +300,11 @@ impl<E: Pairing> Proof<E> {
                 .iter()
                 .zip(y.iter())
                 .fold(<E as Pairing>::G1::zero(), |acc, (a_j, y_j)| {
-                    acc + a_j.mul(y_j.rand.1)
+                    acc + metrics::scalar_mul(Operation::Prove, *a_j, y_j.rand.1)
                 });
-            let x_product =
+            let x_product = if linear {
+                <E as Pairing>::G1::zero()
+            } else {
                 x.iter()
                     .enumerate()
                     .fold(<E as Pairing>::G1::zero(), |acc, (i, x_i)| {
@@ -180,14 +314,130 @@ impl<E: Pairing> Proof<E> {
                             .fold(E::ScalarField::zero(), |acc, (j, y_j)| {
                                 acc + equ.gamma[(i, j)].mul(y_j.rand.1)
                             });
-                        acc + x_i.value.mul(exp)
-                    });
+                        acc + metrics::scalar_mul(Operation::Prove, x_i.value, exp)
+                    })
+            };
             a_product + x_product
         };
 
+        // Compute θ as in (7). Note that θ11 and θ21 are zero only before adding `Z (x) u`, which makes
+        // all the four points of θ uniformly random, so none of them can be omitted from the proof.
+        let theta = Matrix::new(&[[theta11, theta12], [theta21, theta22]]) + z_u;
+
+        metrics::record_call(Operation::Prove);
+
+        // π = (φ, θ)
+        Proof {
+            phi: phi.into(),
+            theta: theta.into(),
+        }
+    }
+
+    /// Same as [Proof::new], but computes only the terms of the non-zero entries in the matrix `gamma` of the
+    /// equation `E`. It is faster than [Proof::new] if most of the entries in `gamma` are zero, e.g. it skips
+    /// the scalar multiplications by the variables whose rows or columns in `gamma` are all zeros.
+    ///
+    /// ## Panics
+    /// Panics if 'a.len() != x.len()' or 'b.len() != y.len()', where `a` and `b` are the constants in the equation `E`.
+    pub fn new_sparse<R: Rng>(
+        rng: &mut R,
+        cks: &CommitmentKeys<E>,
+        equ: &Equation<E>,
+        x: &[Variable<<E as Pairing>::G1>],
+        y: &[Variable<<E as Pairing>::G2>],
+    ) -> Self {
+        assert_eq!(equ.a.len(), y.len());
+        assert_eq!(equ.b.len(), x.len());
+
+        let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
+        let z_u = z_u::<E>(&z, &cks.u, Operation::Prove);
+        let z_v = z_v::<E>(&z, &cks.v, Operation::Prove);
+
+        // Computes the values in (6), and the exponents Σ_i gamma_ij r_ik (for each j) and
+        // Σ_j gamma_ij s_jk (for each i) in (7), over the non-zero entries of gamma.
+        let zero = E::ScalarField::zero();
+        let (mut t11, mut t12, mut t21, mut t22) = (zero, zero, zero, zero);
+        let mut gamma_r = [vec![zero; y.len()], vec![zero; y.len()]];
+        let mut gamma_s = [vec![zero; x.len()], vec![zero; x.len()]];
+        for ((i, j), gamma_ij) in equ.gamma.to_sparse().iter() {
+            let (r_i, s_j) = (&x[i].rand, &y[j].rand);
+            let (rs11, rs12, rs21, rs22) = Randomness::cross_mul(r_i, s_j);
+            t11 += gamma_ij.mul(rs11);
+            t12 += gamma_ij.mul(rs12);
+            t21 += gamma_ij.mul(rs21);
+            t22 += gamma_ij.mul(rs22);
+            gamma_r[0][j] += gamma_ij.mul(r_i.0);
+            gamma_r[1][j] += gamma_ij.mul(r_i.1);
+            gamma_s[0][i] += gamma_ij.mul(s_j.0);
+            gamma_s[1][i] += gamma_ij.mul(s_j.1);
+        }
+        let r = [
+            x.iter().map(|x_i| x_i.rand.0).collect::<Vec<_>>(),
+            x.iter().map(|x_i| x_i.rand.1).collect::<Vec<_>>(),
+        ];
+        let s = [
+            y.iter().map(|y_j| y_j.rand.0).collect::<Vec<_>>(),
+            y.iter().map(|y_j| y_j.rand.1).collect::<Vec<_>>(),
+        ];
+
+        // Π b_i^r_ik Π y_j^(Σ_i gamma_ij r_ik), skipping the zero exponents.
+        let phi_product = |k: usize| {
+            let b_product = equ
+                .b
+                .iter()
+                .zip(r[k].iter())
+                .fold(<E as Pairing>::G2::zero(), |acc, (b_i, r_ik)| {
+                    acc + metrics::scalar_mul(Operation::Prove, *b_i, r_ik)
+                });
+            y.iter()
+                .zip(gamma_r[k].iter())
+                .filter(|(_, exp)| !exp.is_zero())
+                .fold(b_product, |acc, (y_j, exp)| {
+                    acc + metrics::scalar_mul(Operation::Prove, y_j.value, exp)
+                })
+        };
+
+        // Π a_j^s_jk Π x_i^(Σ_j gamma_ij s_jk), skipping the zero exponents.
+        let theta_product = |k: usize| {
+            let a_product = equ
+                .a
+                .iter()
+                .zip(s[k].iter())
+                .fold(<E as Pairing>::G1::zero(), |acc, (a_j, s_jk)| {
+                    acc + metrics::scalar_mul(Operation::Prove, *a_j, s_jk)
+                });
+            x.iter()
+                .zip(gamma_s[k].iter())
+                .filter(|(_, exp)| !exp.is_zero())
+                .fold(a_product, |acc, (x_i, exp)| {
+                    acc + metrics::scalar_mul(Operation::Prove, x_i.value, exp)
+                })
+        };
+
+        let phi11 = metrics::scalar_mul(Operation::Prove, cks.v.0 .0, t11)
+            + metrics::scalar_mul(Operation::Prove, cks.v.1 .0, t12);
+        let phi12 = metrics::scalar_mul(Operation::Prove, cks.v.0 .1, t11)
+            + metrics::scalar_mul(Operation::Prove, cks.v.1 .1, t12)
+            + phi_product(0);
+        let phi21 = metrics::scalar_mul(Operation::Prove, cks.v.0 .0, t21)
+            + metrics::scalar_mul(Operation::Prove, cks.v.1 .0, t22);
+        let phi22 = metrics::scalar_mul(Operation::Prove, cks.v.0 .1, t21)
+            + metrics::scalar_mul(Operation::Prove, cks.v.1 .1, t22)
+            + phi_product(1);
+
+        // Compute φ as in (7).
+        let phi = Matrix::new(&[[phi11, phi12], [phi21, phi22]]) + z_v;
+
+        let theta11 = <E as Pairing>::G1::zero();
+        let theta12 = theta_product(0);
+        let theta21 = <E as Pairing>::G1::zero();
+        let theta22 = theta_product(1);
+
         // Compute θ as in (7).
         let theta = Matrix::new(&[[theta11, theta12], [theta21, theta22]]) + z_u;
 
+        metrics::record_call(Operation::Prove);
+
         // π = (φ, θ)
         Proof {
             phi: phi.into(),
@@ -195,6 +445,29 @@ impl<E: Pairing> Proof<E> {
         }
     }
 
+    /// Same as [Proof::new], but the variables `x` are consumed from an iterator in a single pass, so that
+    /// they are not buffered. The variables `y` are buffered, since every `x_i` is combined with all of them
+    /// in the γ-weighted sums (i.e. the memory is `O(n)` in addition to the equation).
+    ///
+    /// ## Panics
+    /// Panics if 'a.len() != y.len()' or 'b.len() != x.len()', where `a` and `b` are the constants in the equation `E`.
+    pub fn new_from_iter<R: Rng>(
+        rng: &mut R,
+        cks: &CommitmentKeys<E>,
+        equ: &Equation<E>,
+        x: impl ExactSizeIterator<Item = Variable<<E as Pairing>::G1>>,
+        y: impl ExactSizeIterator<Item = Variable<<E as Pairing>::G2>>,
+    ) -> Self {
+        assert_eq!(equ.b.len(), x.len());
+        let y = y.collect::<Vec<_>>();
+        let mut prover = StreamingProver::new(cks, &equ.a, &y);
+        for (i, x_i) in x.enumerate() {
+            let gamma_i = equ.gamma.as_ref().row(i);
+            prover.push(&x_i, equ.b[i], gamma_i.iter());
+        }
+        prover.finish(Matrix::<E::ScalarField>::rand(rng, 2, 2))
+    }
+
     /// Implements the Proof Randomization function (proof adaption) `RdProof(ck, E, (c, r), (d, s)), π)` defined in the paper.
     /// Randomized the components (`φ`, `θ`) in this proof for the equation `E` with the commitment keys `ck` and the Commitments `c`, `d`
     /// (and their internal randomness `r`, `s` respectively).
@@ -246,133 +519,139 @@ impl<E: Pairing> Proof<E> {
         equ: &Equation<E>,
         cr: &[ComRandomness<<E as Pairing>::G1>],
         ds: &[ComRandomness<<E as Pairing>::G2>],
+    ) {
+        let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
+        self.randomize_with_internal_randomness(cks, equ, cr, ds, z);
+    }
+
+    /// Same as [Proof::randomize], except that the internal randomness `Z` (a 2x2 matrix) is supplied by
+    /// the caller instead of being sampled inside. The randomized proof is deterministic given the inputs.
+    ///
+    /// `Z` must be sampled uniformly at random and used only once, otherwise the randomized proof can be
+    /// linked to this proof.
+    ///
+    /// ## Panics
+    /// Panics if 'a.len() != ds.len()', 'b.len() != cr.len()' or 'z.dim() != (2, 2)', where `a` and `b` are
+    /// the constants in the equation `E`.
+    pub fn randomize_with_internal_randomness(
+        &mut self,
+        cks: &CommitmentKeys<E>,
+        equ: &Equation<E>,
+        cr: &[ComRandomness<<E as Pairing>::G1>],
+        ds: &[ComRandomness<<E as Pairing>::G2>],
+        z: Matrix<E::ScalarField>,
     ) {
         assert_eq!(equ.a.len(), ds.len());
         assert_eq!(equ.b.len(), cr.len());
+        assert_eq!(z.dim(), (2, 2));
 
-        let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
-        let z_u = z_u::<E>(&z, &cks.u);
-        let z_v = z_v::<E>(&z, &cks.v);
+        metrics::record_call(Operation::Randomize);
+
+        let z_u = z_u::<E>(&z, &cks.u, Operation::Randomize);
+        let z_v = z_v::<E>(&z, &cks.v, Operation::Randomize);
 
         let c = cr.iter().map(|(c_i, _)| *c_i).collect::<Vec<_>>();
         let d = ds.iter().map(|(d_j, _)| *d_j).collect::<Vec<_>>();
         let r = cr.iter().map(|(_, r_i)| *r_i).collect::<Vec<_>>();
         let s = ds.iter().map(|(_, s_j)| *s_j).collect::<Vec<_>>();
 
-        let (t11, t12, t21, t22) = t11_t12_t21_t22::<E>(&r, &s, &equ.gamma);
+        let (t11, t12, t21, t22) = Randomness::gamma_t_product(&r, &s, &equ.gamma);
 
-        self.phi = {
-            let phi11 = {
-                let d_product =
-                    d.iter()
-                        .enumerate()
-                        .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
-                            let exp = r
-                                .iter()
-                                .enumerate()
-                                .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
-                                    acc + equ.gamma[(i, j)].mul(r_i.0)
-                                });
-                            acc + d_j.0.mul(exp)
-                        });
-                let v_product = cks.v.0 .0.mul(&t11) + cks.v.1 .0.mul(&t12);
-                d_product + v_product
-            };
-
-            let phi12 = {
-                let b_product = equ
-                    .b
-                    .iter()
-                    .zip(r.iter())
-                    .fold(<E as Pairing>::G2::zero(), |acc, (b_i, r_i)| {
-                        acc + b_i.mul(r_i.0)
-                    });
-                let d_product =
-                    d.iter()
-                        .enumerate()
-                        .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
-                            let exp = r
-                                .iter()
-                                .enumerate()
-                                .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
-                                    acc + equ.gamma[(i, j)].mul(r_i.0)
-                                });
-                            acc + d_j.1.mul(exp)
-                        });
-                let v_product = cks.v.0 .1.mul(&t11) + cks.v.1 .1.mul(&t12);
-                b_product + d_product + v_product
-            };
+        self.phi =
+            {
+                let phi11 = {
+                    let d_product =
+                        d.iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
+                                let exp = r
+                                    .iter()
+                                    .enumerate()
+                                    .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
+                                        acc + equ.gamma[(i, j)].mul(r_i.0)
+                                    });
+                                acc + metrics::scalar_mul(Operation::Randomize, d_j.0, exp)
+                            });
+                    let v_product = metrics::scalar_mul(Operation::Randomize, cks.v.0 .0, &t11)
+                        + metrics::scalar_mul(Operation::Randomize, cks.v.1 .0, &t12);
+                    d_product + v_product
+                };
 
-            let phi21 = {
-                let d_product =
-                    d.iter()
-                        .enumerate()
-                        .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
-                            let exp = r
-                                .iter()
-                                .enumerate()
-                                .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
-                                    acc + equ.gamma[(i, j)].mul(r_i.1)
-                                });
-                            acc + d_j.0.mul(exp)
-                        });
-                let v_product = cks.v.0 .0.mul(&t21) + cks.v.1 .0.mul(&t22);
-                d_product + v_product
-            };
+                let phi12 = {
+                    let b_product = equ.b.iter().zip(r.iter()).fold(
+                        <E as Pairing>::G2::zero(),
+                        |acc, (b_i, r_i)| {
+                            acc + metrics::scalar_mul(Operation::Randomize, *b_i, r_i.0)
+                        },
+                    );
+                    let d_product =
+                        d.iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
+                                let exp = r
+                                    .iter()
+                                    .enumerate()
+                                    .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
+                                        acc + equ.gamma[(i, j)].mul(r_i.0)
+                                    });
+                                acc + metrics::scalar_mul(Operation::Randomize, d_j.1, exp)
+                            });
+                    let v_product = metrics::scalar_mul(Operation::Randomize, cks.v.0 .1, &t11)
+                        + metrics::scalar_mul(Operation::Randomize, cks.v.1 .1, &t12);
+                    b_product + d_product + v_product
+                };
 
-            let phi22 = {
-                let b_product = equ
-                    .b
-                    .iter()
-                    .zip(r.iter())
-                    .fold(<E as Pairing>::G2::zero(), |acc, (b_i, r_i)| {
-                        acc + b_i.mul(r_i.1)
-                    });
-                let d_product =
-                    d.iter()
-                        .enumerate()
-                        .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
-                            let exp = r
-                                .iter()
-                                .enumerate()
-                                .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
-                                    acc + equ.gamma[(i, j)].mul(r_i.1)
-                                });
-                            acc + d_j.1.mul(exp)
-                        });
-                let v_product = cks.v.0 .1.mul(&t21) + cks.v.1 .1.mul(&t22);
-                b_product + d_product + v_product
-            };
+                let phi21 = {
+                    let d_product =
+                        d.iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
+                                let exp = r
+                                    .iter()
+                                    .enumerate()
+                                    .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
+                                        acc + equ.gamma[(i, j)].mul(r_i.1)
+                                    });
+                                acc + metrics::scalar_mul(Operation::Randomize, d_j.0, exp)
+                            });
+                    let v_product = metrics::scalar_mul(Operation::Randomize, cks.v.0 .0, &t21)
+                        + metrics::scalar_mul(Operation::Randomize, cks.v.1 .0, &t22);
+                    d_product + v_product
+                };
 
-            (self.phi.clone().into::<<E as Pairing>::G2>()
-                + Matrix::new(&[[phi11, phi12], [phi21, phi22]])
-                + z_v)
-                .into()
-        };
+                let phi22 = {
+                    let b_product = equ.b.iter().zip(r.iter()).fold(
+                        <E as Pairing>::G2::zero(),
+                        |acc, (b_i, r_i)| {
+                            acc + metrics::scalar_mul(Operation::Randomize, *b_i, r_i.1)
+                        },
+                    );
+                    let d_product =
+                        d.iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G2::zero(), |acc, (j, d_j)| {
+                                let exp = r
+                                    .iter()
+                                    .enumerate()
+                                    .fold(E::ScalarField::zero(), |acc, (i, r_i)| {
+                                        acc + equ.gamma[(i, j)].mul(r_i.1)
+                                    });
+                                acc + metrics::scalar_mul(Operation::Randomize, d_j.1, exp)
+                            });
+                    let v_product = metrics::scalar_mul(Operation::Randomize, cks.v.0 .1, &t21)
+                        + metrics::scalar_mul(Operation::Randomize, cks.v.1 .1, &t22);
+                    b_product + d_product + v_product
+                };
 
-        self.theta = {
-            let theta11 = c
-                .iter()
-                .enumerate()
-                .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
-                    let exp = s
-                        .iter()
-                        .enumerate()
-                        .fold(E::ScalarField::zero(), |acc, (j, s_j)| {
-                            acc + equ.gamma[(i, j)].mul(s_j.0)
-                        });
-                    acc + c_i.0.mul(exp)
-                });
+                (self.phi.clone().into::<<E as Pairing>::G2>()
+                    + Matrix::new(&[[phi11, phi12], [phi21, phi22]])
+                    + z_v)
+                    .into()
+            };
 
-            let theta12 = {
-                let a_product = equ
-                    .a
-                    .iter()
-                    .zip(s.iter())
-                    .fold(<E as Pairing>::G1::zero(), |acc, (a_j, s_j)| {
-                        acc + a_j.mul(s_j.0)
-                    });
-                let c_product =
+        self.theta =
+            {
+                let theta11 =
                     c.iter()
                         .enumerate()
                         .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
@@ -382,33 +661,32 @@ impl<E: Pairing> Proof<E> {
                                 .fold(E::ScalarField::zero(), |acc, (j, s_j)| {
                                     acc + equ.gamma[(i, j)].mul(s_j.0)
                                 });
-                            acc + c_i.1.mul(exp)
+                            acc + metrics::scalar_mul(Operation::Randomize, c_i.0, exp)
                         });
-                a_product + c_product
-            };
 
-            let theta21 = c
-                .iter()
-                .enumerate()
-                .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
-                    let exp = s
-                        .iter()
-                        .enumerate()
-                        .fold(E::ScalarField::zero(), |acc, (j, s_j)| {
-                            acc + equ.gamma[(i, j)].mul(s_j.1)
-                        });
-                    acc + c_i.0.mul(exp)
-                });
+                let theta12 = {
+                    let a_product = equ.a.iter().zip(s.iter()).fold(
+                        <E as Pairing>::G1::zero(),
+                        |acc, (a_j, s_j)| {
+                            acc + metrics::scalar_mul(Operation::Randomize, *a_j, s_j.0)
+                        },
+                    );
+                    let c_product =
+                        c.iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
+                                let exp = s
+                                    .iter()
+                                    .enumerate()
+                                    .fold(E::ScalarField::zero(), |acc, (j, s_j)| {
+                                        acc + equ.gamma[(i, j)].mul(s_j.0)
+                                    });
+                                acc + metrics::scalar_mul(Operation::Randomize, c_i.1, exp)
+                            });
+                    a_product + c_product
+                };
 
-            let theta22 = {
-                let a_product = equ
-                    .a
-                    .iter()
-                    .zip(s.iter())
-                    .fold(<E as Pairing>::G1::zero(), |acc, (a_j, s_j)| {
-                        acc + a_j.mul(s_j.1)
-                    });
-                let c_product =
+                let theta21 =
                     c.iter()
                         .enumerate()
                         .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
@@ -418,16 +696,264 @@ impl<E: Pairing> Proof<E> {
                                 .fold(E::ScalarField::zero(), |acc, (j, s_j)| {
                                     acc + equ.gamma[(i, j)].mul(s_j.1)
                                 });
-                            acc + c_i.1.mul(exp)
+                            acc + metrics::scalar_mul(Operation::Randomize, c_i.0, exp)
                         });
-                a_product + c_product
+
+                let theta22 = {
+                    let a_product = equ.a.iter().zip(s.iter()).fold(
+                        <E as Pairing>::G1::zero(),
+                        |acc, (a_j, s_j)| {
+                            acc + metrics::scalar_mul(Operation::Randomize, *a_j, s_j.1)
+                        },
+                    );
+                    let c_product =
+                        c.iter()
+                            .enumerate()
+                            .fold(<E as Pairing>::G1::zero(), |acc, (i, c_i)| {
+                                let exp = s
+                                    .iter()
+                                    .enumerate()
+                                    .fold(E::ScalarField::zero(), |acc, (j, s_j)| {
+                                        acc + equ.gamma[(i, j)].mul(s_j.1)
+                                    });
+                                acc + metrics::scalar_mul(Operation::Randomize, c_i.1, exp)
+                            });
+                    a_product + c_product
+                };
+
+                (self.theta.clone().into::<<E as Pairing>::G1>()
+                    + Matrix::new(&[[theta11, theta12], [theta21, theta22]])
+                    + z_u)
+                    .into()
             };
+    }
 
-            (self.theta.clone().into::<<E as Pairing>::G1>()
-                + Matrix::new(&[[theta11, theta12], [theta21, theta22]])
-                + z_u)
-                .into()
-        };
+    /// Re-randomizes the internal randomness `Z` of this proof by adding `Z (x) u` and `Z (x) v` (defined in (5))
+    /// for a freshly sampled `Z`, without recomputing the terms depending on the equation and the commitments.
+    /// The resulting proof still verifies against the same equation and commitments.
+    ///
+    /// It is much cheaper than [Proof::randomize] but it does not re-randomize the commitments. The new proof
+    /// hides the internal randomness of the original one, whereas the commitments remain linkable. Use
+    /// [ProofSystem::randomize](crate::ProofSystem::randomize) if the commitments need to be randomized as well.
+    ///
+    /// It is the internal-only re-randomization of the proof, e.g. when the commitments are pinned by an external
+    /// registry. It gives the same proof as [Proof::randomize] with zero randomness for all the commitments (given
+    /// the same rng), because the terms depending on the equation and the commitments vanish for zero randomness.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ark_bls12_381::Bls12_381 as E;
+    /// use ark_ec::pairing::Pairing;
+    /// use ark_std::{test_rng, UniformRand};
+    /// use gs_ppe::{setup, CommitmentKeys, Matrix, ProofSystem, Variable};
+    ///
+    /// type G1 = <E as Pairing>::G1;
+    /// type G2 = <E as Pairing>::G2;
+    /// type G1Affine = <E as Pairing>::G1Affine;
+    /// type G2Affine = <E as Pairing>::G2Affine;
+    /// type Fr = <E as Pairing>::ScalarField;
+    ///
+    /// let rng = &mut test_rng();
+    /// let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    /// let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    /// let (x, y) = (
+    ///     Variable::<G1>::new(rng, x_value),
+    ///     Variable::<G2>::new(rng, y_value),
+    /// );
+    /// let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+    ///
+    /// let cks = CommitmentKeys::<E>::rand(rng);
+    ///
+    /// let ProofSystem {
+    ///     equation,
+    ///     c,
+    ///     d,
+    ///     mut proof,
+    /// } = setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma);
+    /// proof.add_key_randomness(rng, &cks);
+    ///
+    /// assert!(equation.verify(&cks, &c, &d, &proof));
+    /// ```
+    pub fn add_key_randomness<R: Rng>(&mut self, rng: &mut R, cks: &CommitmentKeys<E>) {
+        metrics::record_call(Operation::Randomize);
+        let z = Matrix::<E::ScalarField>::rand(rng, 2, 2);
+        let z_u = z_u::<E>(&z, &cks.u, Operation::Randomize);
+        let z_v = z_v::<E>(&z, &cks.v, Operation::Randomize);
+
+        self.phi = (self.phi.clone().into::<<E as Pairing>::G2>() + z_v).into();
+        self.theta = (self.theta.clone().into::<<E as Pairing>::G1>() + z_u).into();
+    }
+
+    /// Applies the linear map `L` (a `k x m` matrix) to the committed variables `x` in G1, i.e. the variables
+    /// become `x' = L x` (`X'_k = Π_i X_i^L_ki`). Returns the proof and the commitments `c' = L c` of the new
+    /// variables, which verify against the equation [Equation::apply_linear_map_g1] if `L` is invertible.
+    ///
+    /// The commitments are homomorphic, i.e. `c'_k = Π_i c_i^L_ki = Com(ck, X'_k, r'_k)` with the randomness
+    /// `r' = L r`. The verification equations depend on the commitments `c` only through the products
+    /// `Π_i e(c_i, w_i)` (componentwise), where `w_i = b_i Π_j d_j^gamma_ij` is linear in the row `i` of `b` and
+    /// `gamma`. For the constants with `b = L^T b'` and `gamma = L^T gamma'`, the products are equal to
+    /// `Π_k e(c'_k, w'_k)`. Likewise, the terms `Σ_i r_i w_i` in `θ` are equal to `Σ_k r'_k w'_k`, so that `φ` and
+    /// `θ` need not be re-expressed: the returned proof is the given proof.
+    ///
+    /// ## Panics
+    /// Panics if the number of columns of `l` is not `c.len()`.
+    pub fn apply_linear_map_g1(
+        proof: Proof<E>,
+        l: &Matrix<E::ScalarField>,
+        c: &[Com<<E as Pairing>::G1>],
+    ) -> (Proof<E>, Vec<Com<<E as Pairing>::G1>>) {
+        let (k, m) = l.dim();
+        assert_eq!(m, c.len());
+        let c_prime = (0..k)
+            .map(|row| {
+                c.iter()
+                    .enumerate()
+                    .map(|(i, c_i)| *c_i * l[(row, i)])
+                    .sum()
+            })
+            .collect();
+        (proof, c_prime)
+    }
+
+    /// Returns the canonical transcript encoding of this proof. See [transcript] for the details of the encoding.
+    pub fn to_transcript_bytes(&self) -> Vec<u8> {
+        let mut bytes = transcript::header(b"Proof");
+        self.append_transcript_body(&mut bytes);
+        bytes
+    }
+
+    /// Appends the body `φ || θ` of the transcript encoding.
+    pub(crate) fn append_transcript_body(&self, bytes: &mut Vec<u8>) {
+        transcript::append_matrix(bytes, &self.phi);
+        transcript::append_matrix(bytes, &self.theta);
+    }
+
+    /// Returns the concatenated compressed points `φ11 || φ12 || φ21 || φ22`.
+    pub fn phi_to_flat_bytes(&self) -> Vec<u8> {
+        to_flat_bytes(&self.phi)
+    }
+
+    /// Returns the concatenated compressed points `θ11 || θ12 || θ21 || θ22`.
+    pub fn theta_to_flat_bytes(&self) -> Vec<u8> {
+        to_flat_bytes(&self.theta)
+    }
+
+    /// Deserializes the proof from the concatenated points `φ11 || φ12 || φ21 || φ22` and
+    /// `θ11 || θ12 || θ21 || θ22` (e.g. produced by [Proof::phi_to_flat_bytes] and [Proof::theta_to_flat_bytes]
+    /// if `compress` is [Compress::Yes]). The points are checked to be valid.
+    ///
+    /// Returns [SerializationError] if the bytes are not exactly four valid points in the serialization mode
+    /// `compress`.
+    pub fn from_phi_theta_flat(
+        phi_bytes: &[u8],
+        theta_bytes: &[u8],
+        compress: Compress,
+    ) -> Result<Self, SerializationError> {
+        Ok(Proof {
+            phi: from_flat_bytes(phi_bytes, compress)?,
+            theta: from_flat_bytes(theta_bytes, compress)?,
+        })
+    }
+
+    /// Returns which components of the proof consist of identity elements, i.e. a column of `φ` or a row of
+    /// `θ` with both elements the identity, which a compact representation could omit.
+    ///
+    /// Note that a proof generated by [Proof::new] is [ProofStructure::Full] with overwhelming probability,
+    /// because the internal randomness `Z` randomizes every element of `φ` and `θ`. The reduced structures
+    /// arise only from a fixed (e.g. zero) internal randomness in [Proof::new_with_internal_randomness].
+    pub fn analyze_structure(&self) -> ProofStructure {
+        let phi_zero_column =
+            (0..2).any(|j| self.phi.as_ref().column(j).iter().all(|p| p.is_zero()));
+        let theta_zero_row = (0..2).any(|i| self.theta.as_ref().row(i).iter().all(|p| p.is_zero()));
+        match (phi_zero_column, theta_zero_row) {
+            (false, false) => ProofStructure::Full,
+            (true, false) => ProofStructure::PhiZeroColumn,
+            (false, true) => ProofStructure::ThetaZeroRow,
+            (true, true) => ProofStructure::BothReduced,
+        }
+    }
+
+    /// Returns the sum `θ11 + θ12 + θ21 + θ22` of the elements of `θ`. For debugging, e.g. a zero sum of a
+    /// proof generated by [Proof::new] likely indicates a structural issue.
+    pub fn theta_sum(&self) -> <E as Pairing>::G1 {
+        self.theta.as_ref().iter().map(|p| p.into_group()).sum()
+    }
+
+    /// Returns the sum `φ11 + φ12 + φ21 + φ22` of the elements of `φ`. See [Proof::theta_sum].
+    pub fn phi_sum(&self) -> <E as Pairing>::G2 {
+        self.phi.as_ref().iter().map(|p| p.into_group()).sum()
+    }
+
+    /// Checks that all the elements of `φ` and `θ` are on the curve and in the prime-order subgroup, as
+    /// [Proof::from_parts] does.
+    pub fn has_valid_subgroup_membership(&self) -> bool {
+        self.phi.as_ref().iter().all(|p| p.check().is_ok())
+            && self.theta.as_ref().iter().all(|p| p.check().is_ok())
+    }
+}
+
+/// The structure of a proof returned by [Proof::analyze_structure].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProofStructure {
+    /// No column of `φ` and no row of `θ` consists of identity elements.
+    Full,
+    /// A column of `φ` consists of identity elements.
+    PhiZeroColumn,
+    /// A row of `θ` consists of identity elements.
+    ThetaZeroRow,
+    /// A column of `φ` and a row of `θ` consist of identity elements.
+    BothReduced,
+}
+
+impl ProofStructure {
+    /// Returns the number of bytes saved by omitting the identity elements from the compressed encoding of
+    /// `Proof::to_bytes_compressed` (feature `bls12-381`) over BLS12-381, i.e. 96 bytes per point in G2 and
+    /// 48 bytes per point in G1.
+    pub fn estimated_size_reduction(&self) -> usize {
+        const G1_COMPRESSED_SIZE: usize = 48;
+        const G2_COMPRESSED_SIZE: usize = 96;
+        match self {
+            ProofStructure::Full => 0,
+            ProofStructure::PhiZeroColumn => 2 * G2_COMPRESSED_SIZE,
+            ProofStructure::ThetaZeroRow => 2 * G1_COMPRESSED_SIZE,
+            ProofStructure::BothReduced => 2 * G2_COMPRESSED_SIZE + 2 * G1_COMPRESSED_SIZE,
+        }
+    }
+}
+
+#[cfg(feature = "bls12-381")]
+impl Proof<ark_bls12_381::Bls12_381> {
+    /// The size of a compressed proof over BLS12-381, i.e. four compressed G2 points (96 bytes each) in `φ`
+    /// and four compressed G1 points (48 bytes each) in `θ`.
+    pub const COMPRESSED_SIZE: usize = 4 * 96 + 4 * 48;
+
+    /// Serializes the proof into a fixed-size byte array, which consists of the compressed points
+    /// `φ11 || φ12 || φ21 || φ22 || θ11 || θ12 || θ21 || θ22`.
+    pub fn to_bytes_compressed(&self) -> [u8; Self::COMPRESSED_SIZE] {
+        let mut bytes = [0u8; Self::COMPRESSED_SIZE];
+        let (phi_bytes, theta_bytes) = bytes.split_at_mut(4 * 96);
+        for (chunk, p) in phi_bytes.chunks_exact_mut(96).zip(self.phi.as_ref().iter()) {
+            p.serialize_compressed(chunk)
+                .expect("compressed G2 point has 96 bytes");
+        }
+        for (chunk, p) in theta_bytes
+            .chunks_exact_mut(48)
+            .zip(self.theta.as_ref().iter())
+        {
+            p.serialize_compressed(chunk)
+                .expect("compressed G1 point has 48 bytes");
+        }
+        bytes
+    }
+
+    /// Deserializes the proof from the fixed-size byte array produced by [Proof::to_bytes_compressed].
+    /// The points are checked to be valid (on curve and in the prime-order subgroup).
+    pub fn from_bytes_compressed(
+        bytes: &[u8; Self::COMPRESSED_SIZE],
+    ) -> Result<Self, ark_serialize::SerializationError> {
+        let (phi_bytes, theta_bytes) = bytes.split_at(4 * 96);
+        Self::from_phi_theta_flat(phi_bytes, theta_bytes, Compress::Yes)
     }
 }
 
@@ -460,19 +986,234 @@ impl<E: Pairing> Div for Proof<E> {
     }
 }
 
+/// The error returned by [Proof::from_parts].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InvalidProof {
+    /// The dimension of `φ` is not (2, 2).
+    PhiDim((usize, usize)),
+    /// The dimension of `θ` is not (2, 2).
+    ThetaDim((usize, usize)),
+    /// The element of `φ` or `θ` is not a valid point.
+    Point(InvalidPoint),
+}
+
+impl std::fmt::Display for InvalidProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidProof::PhiDim((m, n)) => {
+                write!(f, "phi has dimension ({}, {}) instead of (2, 2)", m, n)
+            }
+            InvalidProof::ThetaDim((m, n)) => {
+                write!(f, "theta has dimension ({}, {}) instead of (2, 2)", m, n)
+            }
+            InvalidProof::Point(invalid_point) => invalid_point.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InvalidProof {}
+
+/// Displays the first 8 bytes of the compressed encodings of the entries of `φ` and `θ` in row-major order in hex,
+/// e.g. `Proof(phi: [8d0e2c1f9a3b7e44..., ...], theta: [a1b2c3d4e5f60718..., ...])`.
+impl<E: Pairing> Display for Proof<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Proof(phi: ")?;
+        fmt_short_hex_matrix(f, &self.phi)?;
+        write!(f, ", theta: ")?;
+        fmt_short_hex_matrix(f, &self.theta)?;
+        write!(f, ")")
+    }
+}
+
+/// Hashes the compressed encodings `φ11 || φ12 || φ21 || φ22` and `θ11 || θ12 || θ21 || θ22`, so that equal
+/// proofs hash equally regardless of how they were computed, e.g. for deduplication in a `HashSet`.
+///
+/// `Ord` is intentionally not implemented, as the group elements have no meaningful order. To key a `BTreeMap`,
+/// use the bytes of [Proof::to_transcript_bytes] instead.
+impl<E: Pairing> Hash for Proof<E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.phi_to_flat_bytes().hash(state);
+        self.theta_to_flat_bytes().hash(state);
+    }
+}
+
+/// Concatenates the compressed points of the 2x2 matrix in row-major order.
+fn to_flat_bytes<T: Clone + CanonicalSerialize>(matrix: &Matrix<T>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    matrix.as_ref().iter().for_each(|p| {
+        p.serialize_compressed(&mut bytes)
+            .expect("serializing into a vector never fails")
+    });
+    bytes
+}
+
+/// Deserializes exactly four points into a 2x2 matrix in row-major order.
+fn from_flat_bytes<T: Clone + CanonicalDeserialize>(
+    mut bytes: &[u8],
+    compress: Compress,
+) -> Result<Matrix<T>, SerializationError> {
+    let points = (0..4)
+        .map(|_| T::deserialize_with_mode(&mut bytes, compress, Validate::Yes))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !bytes.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(Matrix::from_vecs(
+        points.chunks(2).map(|row| row.to_vec()).collect(),
+    ))
+}
+
+/// The prover of [Proof::new] which consumes the variables `x` one at a time, given the constants `a` and the
+/// variables `y`. As in [Proof::new], the γ-weighted sums are accumulated in the scalar field, so that each variable
+/// costs a constant number of scalar multiplications in the groups:
+/// - `Σ_i gamma_ij r_ik` for each `j`, i.e. the exponents of `y_j` in φ, which are applied in [StreamingProver::finish].
+/// - `Σ_j gamma_ij s_jk` for the pushed `x_i`, i.e. the exponents of `x_i` in θ, which are applied in
+///   [StreamingProver::push].
+/// - `t_kl = Σ_i r_ik (Σ_j gamma_ij s_jl)`.
+///
+/// The zero exponents are skipped, e.g. the terms of the variables in a linear equation.
+pub(crate) struct StreamingProver<'a, E: Pairing> {
+    cks: &'a CommitmentKeys<E>,
+    a: &'a [<E as Pairing>::G1Affine],
+    y: &'a [Variable<<E as Pairing>::G2>],
+    gamma_r: [Vec<E::ScalarField>; 2],
+    b_r: (<E as Pairing>::G2, <E as Pairing>::G2),
+    x_gamma_s: (<E as Pairing>::G1, <E as Pairing>::G1),
+    t: (
+        E::ScalarField,
+        E::ScalarField,
+        E::ScalarField,
+        E::ScalarField,
+    ),
+}
+
+impl<'a, E: Pairing> StreamingProver<'a, E> {
+    /// ## Panics
+    /// Panics if `a.len() != y.len()`.
+    pub(crate) fn new(
+        cks: &'a CommitmentKeys<E>,
+        a: &'a [<E as Pairing>::G1Affine],
+        y: &'a [Variable<<E as Pairing>::G2>],
+    ) -> Self {
+        assert_eq!(a.len(), y.len());
+        let zero = E::ScalarField::zero();
+        Self {
+            cks,
+            a,
+            y,
+            gamma_r: [vec![zero; y.len()], vec![zero; y.len()]],
+            b_r: (<E as Pairing>::G2::zero(), <E as Pairing>::G2::zero()),
+            x_gamma_s: (<E as Pairing>::G1::zero(), <E as Pairing>::G1::zero()),
+            t: (zero, zero, zero, zero),
+        }
+    }
+
+    /// Adds the variable `x_i` with the constant `b_i` and the row `gamma_i` of `gamma`.
+    ///
+    /// ## Panics
+    /// Panics if the length of `gamma_i` is not equal to `y.len()`.
+    pub(crate) fn push<'g>(
+        &mut self,
+        x_i: &Variable<<E as Pairing>::G1>,
+        b_i: <E as Pairing>::G2Affine,
+        gamma_i: impl ExactSizeIterator<Item = &'g E::ScalarField>,
+    ) {
+        assert_eq!(gamma_i.len(), self.y.len());
+        let Randomness(r1, r2) = x_i.rand;
+        let zero = E::ScalarField::zero();
+        // Σ_j gamma_ij s_jk
+        let mut sigma = (zero, zero);
+        for (j, (gamma_ij, y_j)) in gamma_i.zip(self.y).enumerate() {
+            self.gamma_r[0][j] += *gamma_ij * r1;
+            self.gamma_r[1][j] += *gamma_ij * r2;
+            sigma.0 += *gamma_ij * y_j.rand.0;
+            sigma.1 += *gamma_ij * y_j.rand.1;
+        }
+
+        self.b_r.0 += metrics::scalar_mul(Operation::Prove, b_i, r1);
+        self.b_r.1 += metrics::scalar_mul(Operation::Prove, b_i, r2);
+        if !sigma.0.is_zero() {
+            self.x_gamma_s.0 += metrics::scalar_mul(Operation::Prove, x_i.value, sigma.0);
+        }
+        if !sigma.1.is_zero() {
+            self.x_gamma_s.1 += metrics::scalar_mul(Operation::Prove, x_i.value, sigma.1);
+        }
+        self.t.0 += r1 * sigma.0;
+        self.t.1 += r1 * sigma.1;
+        self.t.2 += r2 * sigma.0;
+        self.t.3 += r2 * sigma.1;
+    }
+
+    /// Returns the proof with the internal randomness `z`, as in [Proof::new_with_internal_randomness].
+    pub(crate) fn finish(self, z: Matrix<E::ScalarField>) -> Proof<E> {
+        let (cks, (t11, t12, t21, t22)) = (self.cks, self.t);
+        // Π y_j^(Σ_i gamma_ij r_ik), skipping the zero exponents.
+        let y_product = |k: usize| {
+            self.y
+                .iter()
+                .zip(self.gamma_r[k].iter())
+                .filter(|(_, exp)| !exp.is_zero())
+                .fold(<E as Pairing>::G2::zero(), |acc, (y_j, exp)| {
+                    acc + metrics::scalar_mul(Operation::Prove, y_j.value, *exp)
+                })
+        };
+        // Π a_j^s_jk
+        let a_product = |k: usize| {
+            self.a
+                .iter()
+                .zip(self.y)
+                .fold(<E as Pairing>::G1::zero(), |acc, (a_j, y_j)| {
+                    let s_jk = if k == 0 { y_j.rand.0 } else { y_j.rand.1 };
+                    acc + metrics::scalar_mul(Operation::Prove, *a_j, s_jk)
+                })
+        };
+
+        let phi = Matrix::new(&[
+            [
+                metrics::scalar_mul(Operation::Prove, cks.v.0 .0, t11)
+                    + metrics::scalar_mul(Operation::Prove, cks.v.1 .0, t12),
+                metrics::scalar_mul(Operation::Prove, cks.v.0 .1, t11)
+                    + metrics::scalar_mul(Operation::Prove, cks.v.1 .1, t12)
+                    + self.b_r.0
+                    + y_product(0),
+            ],
+            [
+                metrics::scalar_mul(Operation::Prove, cks.v.0 .0, t21)
+                    + metrics::scalar_mul(Operation::Prove, cks.v.1 .0, t22),
+                metrics::scalar_mul(Operation::Prove, cks.v.0 .1, t21)
+                    + metrics::scalar_mul(Operation::Prove, cks.v.1 .1, t22)
+                    + self.b_r.1
+                    + y_product(1),
+            ],
+        ]) + z_v::<E>(&z, &cks.v, Operation::Prove);
+        let theta = Matrix::new(&[
+            [<E as Pairing>::G1::zero(), a_product(0) + self.x_gamma_s.0],
+            [<E as Pairing>::G1::zero(), a_product(1) + self.x_gamma_s.1],
+        ]) + z_u::<E>(&z, &cks.u, Operation::Prove);
+
+        metrics::record_call(Operation::Prove);
+
+        Proof {
+            phi: phi.into(),
+            theta: theta.into(),
+        }
+    }
+}
+
 /// Computes the matrix `Z (x) u` defined in (5).
 fn z_u<E: Pairing>(
     z: &Matrix<E::ScalarField>,
     u: &CommitmentKey<<E as Pairing>::G1>,
+    op: Operation,
 ) -> Matrix<<E as Pairing>::G1> {
     Matrix::new(&[
         [
-            u.0 .0.mul(z[(0, 0)]) + u.1 .0.mul(z[(0, 1)]),
-            u.0 .1.mul(z[(0, 0)]) + u.1 .1.mul(z[(0, 1)]),
+            metrics::scalar_mul(op, u.0 .0, z[(0, 0)]) + metrics::scalar_mul(op, u.1 .0, z[(0, 1)]),
+            metrics::scalar_mul(op, u.0 .1, z[(0, 0)]) + metrics::scalar_mul(op, u.1 .1, z[(0, 1)]),
         ],
         [
-            u.0 .0.mul(z[(1, 0)]) + u.1 .0.mul(z[(1, 1)]),
-            u.0 .1.mul(z[(1, 0)]) + u.1 .1.mul(z[(1, 1)]),
+            metrics::scalar_mul(op, u.0 .0, z[(1, 0)]) + metrics::scalar_mul(op, u.1 .0, z[(1, 1)]),
+            metrics::scalar_mul(op, u.0 .1, z[(1, 0)]) + metrics::scalar_mul(op, u.1 .1, z[(1, 1)]),
         ],
     ])
 }
@@ -481,44 +1222,35 @@ fn z_u<E: Pairing>(
 fn z_v<E: Pairing>(
     z: &Matrix<E::ScalarField>,
     v: &CommitmentKey<<E as Pairing>::G2>,
+    op: Operation,
 ) -> Matrix<<E as Pairing>::G2> {
     Matrix::new(&[
         [
-            v.0 .0.mul(z[(0, 0)].neg()) + v.1 .0.mul(z[(1, 0)].neg()),
-            v.0 .1.mul(z[(0, 0)].neg()) + v.1 .1.mul(z[(1, 0)].neg()),
+            metrics::scalar_mul(op, v.0 .0, z[(0, 0)].neg())
+                + metrics::scalar_mul(op, v.1 .0, z[(1, 0)].neg()),
+            metrics::scalar_mul(op, v.0 .1, z[(0, 0)].neg())
+                + metrics::scalar_mul(op, v.1 .1, z[(1, 0)].neg()),
         ],
         [
-            v.0 .0.mul(z[(0, 1)].neg()) + v.1 .0.mul(z[(1, 1)].neg()),
-            v.0 .1.mul(z[(0, 1)].neg()) + v.1 .1.mul(z[(1, 1)].neg()),
+            metrics::scalar_mul(op, v.0 .0, z[(0, 1)].neg())
+                + metrics::scalar_mul(op, v.1 .0, z[(1, 1)].neg()),
+            metrics::scalar_mul(op, v.0 .1, z[(0, 1)].neg())
+                + metrics::scalar_mul(op, v.1 .1, z[(1, 1)].neg()),
         ],
     ])
 }
 
-/// Computes the values defined in (6).
-fn t11_t12_t21_t22<E: Pairing>(
-    r: &[Randomness<<E as Pairing>::G1>],
-    s: &[Randomness<<E as Pairing>::G2>],
-    gamma: &Matrix<E::ScalarField>,
-) -> (
-    E::ScalarField,
-    E::ScalarField,
-    E::ScalarField,
-    E::ScalarField,
-) {
-    let mut t11 = E::ScalarField::zero();
-    let mut t12 = E::ScalarField::zero();
-    let mut t21 = E::ScalarField::zero();
-    let mut t22 = E::ScalarField::zero();
-
-    for i in 0..r.len() {
-        for j in 0..s.len() {
-            let r_i = &r[i];
-            let s_j = &s[j];
-            t11 += gamma[(i, j)].mul(r_i.0).mul(s_j.0);
-            t12 += gamma[(i, j)].mul(r_i.0).mul(s_j.1);
-            t21 += gamma[(i, j)].mul(r_i.1).mul(s_j.0);
-            t22 += gamma[(i, j)].mul(r_i.1).mul(s_j.1);
+/// Writes the entries of the matrix in row-major order with [fmt_short_hex], e.g. `[8d0e2c1f9a3b7e44..., ...]`.
+fn fmt_short_hex_matrix<T: Clone + CanonicalSerialize>(
+    f: &mut Formatter<'_>,
+    matrix: &Matrix<T>,
+) -> fmt::Result {
+    write!(f, "[")?;
+    for (k, p) in matrix.as_ref().iter().enumerate() {
+        if k > 0 {
+            write!(f, ", ")?;
         }
+        fmt_short_hex(f, p)?;
     }
-    (t11, t12, t21, t22)
+    write!(f, "]")
 }
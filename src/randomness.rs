@@ -1,13 +1,24 @@
 //! Defines the struct [Randomness], the randomness commonly used in the entire scheme. i.e. the `r` and `s`
 //! notated in the paper.
 
-use std::ops::{Add, Neg};
+use std::{
+    iter::Sum,
+    ops::{Add, Mul, Neg, Sub},
+};
 
 use ark_ec::PrimeGroup;
+use ark_ff::PrimeField;
 use ark_std::{rand::Rng, UniformRand, Zero};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::Matrix;
+
+/// The domain separation tag of [Randomness::derive].
+pub const DERIVE_DOMAIN: &[u8] = b"GS-PPE-V01-RANDOMNESS-DERIVE";
 
 /// Randomness used in the entire scheme. i.e. the `r` and `s`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Randomness<G: PrimeGroup>(pub G::ScalarField, pub G::ScalarField);
 
 impl<G: PrimeGroup> Randomness<G> {
@@ -16,9 +27,69 @@ impl<G: PrimeGroup> Randomness<G> {
         Self(G::ScalarField::rand(rng), G::ScalarField::rand(rng))
     }
 
-    /// Returns a `Randomness` with both fields set to zero.
-    pub fn zero() -> Self {
-        Self(G::ScalarField::zero(), G::ScalarField::zero())
+    /// Derives the randomness deterministically from the secret `master_seed` and the public `context` (e.g. a
+    /// message id), so that a stateless prover recommitting the same value in the same context obtains the same
+    /// commitment.
+    ///
+    /// The KDF is HKDF-SHA256 (RFC 5869) with the salt [DERIVE_DOMAIN], the input keying material `master_seed`
+    /// and the info `context`. It expands `2L` bytes, where `L` is the byte length of the scalar field plus 16
+    /// (e.g. 48 bytes for a 255-bit field), and interprets each half as a big-endian integer reduced modulo the
+    /// order of the field. The 128 extra bits make the two scalars uniform up to a negligible bias. Different
+    /// contexts give independent randomness.
+    pub fn derive(master_seed: &[u8], context: &[u8]) -> Self {
+        let len = (G::ScalarField::MODULUS_BIT_SIZE as usize).div_ceil(8) + 16;
+        let mut okm = vec![0u8; 2 * len];
+        Hkdf::<Sha256>::new(Some(DERIVE_DOMAIN), master_seed)
+            .expand(context, &mut okm)
+            .expect("the output length is far below the limit of HKDF-SHA256");
+        let (r1, r2) = okm.split_at(len);
+        Self(
+            G::ScalarField::from_be_bytes_mod_order(r1),
+            G::ScalarField::from_be_bytes_mod_order(r2),
+        )
+    }
+
+    /// Returns the products `(r1 s1, r1 s2, r2 s1, r2 s2)` of the randomness `r` and `s`.
+    pub fn cross_mul<H: PrimeGroup<ScalarField = G::ScalarField>>(
+        r: &Randomness<G>,
+        s: &Randomness<H>,
+    ) -> (
+        G::ScalarField,
+        G::ScalarField,
+        G::ScalarField,
+        G::ScalarField,
+    ) {
+        (r.0 * s.0, r.0 * s.1, r.1 * s.0, r.1 * s.1)
+    }
+
+    /// Computes the values `(t11, t12, t21, t22)` defined in (6) in the paper, i.e. `t_kl = ΣΣ gamma_ij r_ik s_jl`
+    /// for the randomness `r` of the variables `x` and `s` of the variables `y`.
+    ///
+    /// ## Panics
+    /// Panics if the dimension of `gamma` is smaller than (`r.len()`, `s.len()`).
+    pub fn gamma_t_product<H: PrimeGroup<ScalarField = G::ScalarField>>(
+        r: &[Randomness<G>],
+        s: &[Randomness<H>],
+        gamma: &Matrix<G::ScalarField>,
+    ) -> (
+        G::ScalarField,
+        G::ScalarField,
+        G::ScalarField,
+        G::ScalarField,
+    ) {
+        let zero = G::ScalarField::zero();
+        let mut t = (zero, zero, zero, zero);
+        for (i, r_i) in r.iter().enumerate() {
+            for (j, s_j) in s.iter().enumerate() {
+                let gamma_ij = gamma[(i, j)];
+                let (t11, t12, t21, t22) = Self::cross_mul(r_i, s_j);
+                t.0 += gamma_ij * t11;
+                t.1 += gamma_ij * t12;
+                t.2 += gamma_ij * t21;
+                t.3 += gamma_ij * t22;
+            }
+        }
+        t
     }
 }
 
@@ -37,3 +108,45 @@ impl<G: PrimeGroup> Neg for Randomness<G> {
         Self(-self.0, -self.1)
     }
 }
+
+impl<G: PrimeGroup> Sub for Randomness<G> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+/// Scales both fields by the scalar, e.g. the randomness of the commitment `Com(ck, X, r) * ρ` is `r * ρ`.
+impl<G: PrimeGroup> Mul<G::ScalarField> for Randomness<G> {
+    type Output = Self;
+
+    fn mul(self, rhs: G::ScalarField) -> Self {
+        Self(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+/// The `Randomness` with both fields set to zero.
+impl<G: PrimeGroup> Zero for Randomness<G> {
+    fn zero() -> Self {
+        Self(G::ScalarField::zero(), G::ScalarField::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero() && self.1.is_zero()
+    }
+}
+
+/// Sums the randomness, e.g. the net offset of the randomness returned by multiple [Com::randomize](crate::Com::randomize)
+/// calls on the same commitment.
+impl<G: PrimeGroup> Sum for Randomness<G> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl<'a, G: PrimeGroup> Sum<&'a Randomness<G>> for Randomness<G> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
@@ -0,0 +1,100 @@
+//! Provides a struct [SparseMatrix] that stores only the non-zero entries of a matrix, e.g. the matrix `gamma`
+//! of an equation in which most of the exponents are zero.
+
+use std::collections::HashMap;
+
+use ark_std::Zero;
+
+use crate::Matrix;
+
+/// A matrix of dimension `rows x cols` that stores only its non-zero entries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMatrix<F>
+where
+    F: Clone + Zero,
+{
+    rows: usize,
+    cols: usize,
+    entries: HashMap<(usize, usize), F>,
+}
+
+impl<F> SparseMatrix<F>
+where
+    F: Clone + Zero,
+{
+    /// Creates a matrix of dimension `rows x cols` of zeros.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            entries: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn dim(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the number of non-zero entries.
+    #[inline]
+    pub fn nnz(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the entry at `(i, j)`.
+    ///
+    /// ## Panics
+    /// Panics if `(i, j)` is out of bounds.
+    pub fn get(&self, (i, j): (usize, usize)) -> F {
+        assert!(i < self.rows && j < self.cols);
+        self.entries.get(&(i, j)).cloned().unwrap_or_else(F::zero)
+    }
+
+    /// Sets the entry at `(i, j)` to `value`. Zero values are not stored.
+    ///
+    /// ## Panics
+    /// Panics if `(i, j)` is out of bounds.
+    pub fn set(&mut self, (i, j): (usize, usize), value: F) {
+        assert!(i < self.rows && j < self.cols);
+        if value.is_zero() {
+            self.entries.remove(&(i, j));
+        } else {
+            self.entries.insert((i, j), value);
+        }
+    }
+
+    /// Returns an iterator over the indices and values of the non-zero entries, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &F)> {
+        self.entries.iter().map(|(index, value)| (*index, value))
+    }
+
+    /// Converts to a dense [Matrix].
+    pub fn to_dense(&self) -> Matrix<F> {
+        let mut matrix = Matrix::from_elem(self.rows, self.cols, F::zero());
+        self.entries
+            .iter()
+            .for_each(|(index, value)| matrix[*index] = value.clone());
+        matrix
+    }
+}
+
+impl<F> From<&Matrix<F>> for SparseMatrix<F>
+where
+    F: Clone + Zero,
+{
+    fn from(matrix: &Matrix<F>) -> Self {
+        let (rows, cols) = matrix.dim();
+        let entries = matrix
+            .as_ref()
+            .indexed_iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(index, value)| (index, value.clone()))
+            .collect();
+        Self {
+            rows,
+            cols,
+            entries,
+        }
+    }
+}
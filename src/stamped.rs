@@ -0,0 +1,91 @@
+//! Defines [StampedProofSystem], a [ProofSystem] stamped with the fingerprint of the commitment keys (i.e. the
+//! CRS) it is created under, see [CommitmentKeys::fingerprint].
+//!
+//! A [ProofSystem] carries no indication of its CRS, so that mixing proof systems created under different
+//! commitment keys (e.g. in the homomorphic addition) silently yields a proof system which fails to verify.
+//! The stamped proof system rejects such a mix with [KeyMismatch] instead. Stamping is opt-in at the cost of
+//! the extra 32 bytes, i.e. a plain [ProofSystem] remains unstamped.
+
+use ark_ec::pairing::Pairing;
+
+use crate::{CommitmentKeys, ProofSystem};
+
+/// The proof system together with the `fingerprint` of the commitment keys it is created under.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StampedProofSystem<E: Pairing> {
+    pub proof_system: ProofSystem<E>,
+    pub fingerprint: [u8; 32],
+}
+
+impl<E: Pairing> StampedProofSystem<E> {
+    /// Stamps the proof system with the fingerprint of the commitment keys `cks` it is created under, e.g. the
+    /// output of [setup](crate::setup) with the same keys.
+    pub fn new(proof_system: ProofSystem<E>, cks: &CommitmentKeys<E>) -> Self {
+        Self {
+            proof_system,
+            fingerprint: cks.fingerprint(),
+        }
+    }
+
+    /// Returns the sum of the proof systems by the homomorphic addition, stamped with their common fingerprint.
+    ///
+    /// Returns [KeyMismatch] if the fingerprints are different, i.e. the proof systems are created under
+    /// different commitment keys.
+    pub fn try_add(self, other: Self) -> Result<Self, KeyMismatch> {
+        if self.fingerprint != other.fingerprint {
+            return Err(KeyMismatch {
+                expected: self.fingerprint,
+                found: other.fingerprint,
+            });
+        }
+        Ok(Self {
+            proof_system: self.proof_system + other.proof_system,
+            fingerprint: self.fingerprint,
+        })
+    }
+
+    /// Verifies the proof system under the commitment keys `cks`.
+    ///
+    /// Returns [KeyMismatch] if the stamp is not the fingerprint of `cks`, otherwise the result of the verification.
+    pub fn verify(&self, cks: &CommitmentKeys<E>) -> Result<bool, KeyMismatch> {
+        let fingerprint = cks.fingerprint();
+        if self.fingerprint != fingerprint {
+            return Err(KeyMismatch {
+                expected: fingerprint,
+                found: self.fingerprint,
+            });
+        }
+        let ProofSystem {
+            equation,
+            c,
+            d,
+            proof,
+        } = &self.proof_system;
+        Ok(equation.verify(cks, c, d, proof))
+    }
+
+    /// Removes the stamp.
+    pub fn into_proof_system(self) -> ProofSystem<E> {
+        self.proof_system
+    }
+}
+
+/// The stamps of the proof systems disagree, i.e. the `found` fingerprint is not the `expected` one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyMismatch {
+    pub expected: [u8; 32],
+    pub found: [u8; 32],
+}
+
+impl std::fmt::Display for KeyMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "commitment keys mismatch, expected fingerprint ")?;
+        self.expected
+            .iter()
+            .try_for_each(|b| write!(f, "{:02x}", b))?;
+        write!(f, " but found ")?;
+        self.found.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl std::error::Error for KeyMismatch {}
@@ -0,0 +1,646 @@
+//! Provides constructors of the equations for commonly used statements, ready for [setup](crate::setup) and
+//! [Proof::new](crate::Proof::new).
+//!
+//! ## Structure-preserving signatures
+//!
+//! [sps_verification_equations] expresses the verification of the structure-preserving signature scheme in
+//! section 5.2 of [AGHO11](https://eprint.iacr.org/2011/131.pdf) (messages in G1) as pairing product
+//! equations. The reference implementation of the scheme is [SpsSecretKey], [SpsPublicKey] and [SpsSignature].
+//! With secret key `(v, z, w_1, ..., w_k)`, the public key is `(V, Z, W_1, ..., W_k) = (H^v, H^z, H^w_1, ..., H^w_k)`
+//! and a signature `(R, S, T)` on the message `(M_1, ..., M_k)` satisfies:
+//!
+//! e(R, V) e(S, H) Π e(M_i, W_i) = e(G, Z) and e(R, T) = e(G, H)
+//!
+//! where `G` and `H` are the generators of G1 and G2 respectively.
+//!
+//! ## BLS signatures
+//!
+//! [bls_possession] and [bls_possession_with_public_key] express the verification `e(σ, g2) = e(H(m), pk)` of
+//! a BLS signature `σ` in G1 with the committed signature, and the committed or public key `pk` in G2.
+//!
+//! ## Diffie-Hellman tuples
+//!
+//! [dh_tuple] and [prove_dh_tuple] express that the committed `X` in G1 and `Y` in G2 are `g1^s` and `g2^s`
+//! for the same `s`. [cross_group_link] and [prove_cross_group_link] express the same statement for linking a
+//! witness in G1 to a witness in G2, e.g. to move a witness across the sides of other equations.
+//!
+//! ## Openings to public values
+//!
+//! [opening_to_public] and [prove_opening] express that the committed `X` in G1 is a public value `V`, without
+//! revealing the randomness of the commitment.
+//!
+//! ## Equality of committed values
+//!
+//! [committed_equal_g1] and [committed_equal_g2] (and [prove_committed_equal_g1] and [prove_committed_equal_g2])
+//! express that two commitments in the same group, e.g. produced at different times, hide the same value.
+//! [prove_commitment_equality] proves the same statement for the given commitments in G1 and their openings,
+//! over the equation [Equation::for_commitment_equality].
+
+use ark_ec::{
+    pairing::{Pairing, PairingOutput},
+    AffineRepr, CurveGroup, PrimeGroup,
+};
+use ark_ff::Field;
+use ark_std::{rand::Rng, One, UniformRand, Zero};
+use std::ops::{Mul, Neg};
+
+use crate::{Com, CommitmentKeys, Equation, Matrix, Proof, ProofSystem, Randomness, Variable};
+
+/// The secret key `(v, z, w_1, ..., w_k)` of the structure-preserving signature scheme for messages of size `k`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpsSecretKey<E: Pairing> {
+    pub v: E::ScalarField,
+    pub z: E::ScalarField,
+    pub w: Vec<E::ScalarField>,
+}
+
+/// The public key `(V, Z, W_1, ..., W_k)` of the structure-preserving signature scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpsPublicKey<E: Pairing> {
+    pub v: <E as Pairing>::G2Affine,
+    pub z: <E as Pairing>::G2Affine,
+    pub w: Vec<<E as Pairing>::G2Affine>,
+}
+
+/// The signature `(R, S, T)` of the structure-preserving signature scheme.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SpsSignature<E: Pairing> {
+    pub r: <E as Pairing>::G1Affine,
+    pub s: <E as Pairing>::G1Affine,
+    pub t: <E as Pairing>::G2Affine,
+}
+
+/// A message element in the verification equations, either committed (i.e. a variable `x` in the equation)
+/// or public (i.e. a constant in the equation).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MessageSlot<E: Pairing> {
+    Committed,
+    Public(<E as Pairing>::G1Affine),
+}
+
+impl<E: Pairing> SpsSecretKey<E> {
+    /// Generates a random secret key for messages of size `k`.
+    pub fn rand<R: Rng>(rng: &mut R, k: usize) -> Self {
+        Self {
+            v: E::ScalarField::rand(rng),
+            z: E::ScalarField::rand(rng),
+            w: (0..k).map(|_| E::ScalarField::rand(rng)).collect(),
+        }
+    }
+
+    /// Returns the public key `(H^v, H^z, H^w_1, ..., H^w_k)`.
+    pub fn public_key(&self) -> SpsPublicKey<E> {
+        let h = <E as Pairing>::G2::generator();
+        SpsPublicKey {
+            v: h.mul(self.v).into_affine(),
+            z: h.mul(self.z).into_affine(),
+            w: <E as Pairing>::G2::normalize_batch(
+                &self.w.iter().map(|w_i| h.mul(w_i)).collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    /// Signs the message `(M_1, ..., M_k)`. The signature is `(G^r, G^(z - rv) Π M_i^-w_i, H^(1/r))` for a
+    /// random `r`.
+    ///
+    /// ## Panics
+    /// Panics if the size of the message is not `k`.
+    pub fn sign<R: Rng>(
+        &self,
+        rng: &mut R,
+        messages: &[<E as Pairing>::G1Affine],
+    ) -> SpsSignature<E> {
+        assert_eq!(messages.len(), self.w.len());
+
+        let g = <E as Pairing>::G1::generator();
+        let h = <E as Pairing>::G2::generator();
+        let r = loop {
+            let r = E::ScalarField::rand(rng);
+            if !r.is_zero() {
+                break r;
+            }
+        };
+
+        let s = messages
+            .iter()
+            .zip(self.w.iter())
+            .fold(g.mul(self.z - r * self.v), |acc, (m_i, w_i)| {
+                acc + m_i.mul(w_i.neg())
+            });
+        SpsSignature {
+            r: g.mul(r).into_affine(),
+            s: s.into_affine(),
+            t: h.mul(r.inverse().unwrap()).into_affine(),
+        }
+    }
+}
+
+impl<E: Pairing> SpsPublicKey<E> {
+    /// Verifies the signature on the message `(M_1, ..., M_k)` in the clear.
+    pub fn verify(&self, messages: &[<E as Pairing>::G1Affine], sig: &SpsSignature<E>) -> bool {
+        if messages.len() != self.w.len() {
+            return false;
+        }
+        let g = <E as Pairing>::G1Affine::generator();
+        let h = <E as Pairing>::G2Affine::generator();
+
+        let lhs = messages.iter().zip(self.w.iter()).fold(
+            E::pairing(sig.r, self.v) + E::pairing(sig.s, h),
+            |acc, (m_i, w_i)| acc + E::pairing(m_i, w_i),
+        );
+        lhs == E::pairing(g, self.z) && E::pairing(sig.r, sig.t) == E::pairing(g, h)
+    }
+}
+
+/// Returns the two equations of verifying a signature `(R, S, T)` on a message under the public key `pk`, where
+/// the signature is committed and the message elements are committed or public according to `message_slots`:
+///
+/// 1. `e(R, V) e(S, H) Π e(M_i, W_i) = e(G, Z)` with the variables `x = (R, S, M_i...)` where `M_i` are the
+///    committed message elements in order, and no variables `y`. The public message elements are moved
+///    to the target.
+/// 2. `e(R, T) = e(G, H)` with the variables `x = (R)` and `y = (T)`.
+///
+/// The variable `R` must be committed with the same randomness in both equations, i.e. use the same
+/// [Variable](crate::Variable) in the proofs, so that the proofs are about the same signature.
+///
+/// ## Panics
+/// Panics if `message_slots.len() != pk.w.len()`.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, CurveGroup};
+/// use ark_std::{test_rng, UniformRand};
+/// use gs_ppe::{
+///     statements::{sps_verification_equations, MessageSlot, SpsSecretKey},
+///     CommitmentKeys, Proof, Variable,
+/// };
+///
+/// type G1 = <E as Pairing>::G1;
+///
+/// let rng = &mut test_rng();
+/// let sk = SpsSecretKey::<E>::rand(rng, 2);
+/// let pk = sk.public_key();
+/// let messages = [G1::rand(rng).into_affine(), G1::rand(rng).into_affine()];
+/// let sig = sk.sign(rng, &messages);
+///
+/// // Commit to the signature and the first message element. The second message element is public.
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let equations = sps_verification_equations(&pk, &[MessageSlot::Committed, MessageSlot::Public(messages[1])]);
+/// let (r, s, t, m) = (
+///     Variable::new(rng, sig.r),
+///     Variable::new(rng, sig.s),
+///     Variable::new(rng, sig.t),
+///     Variable::new(rng, messages[0]),
+/// );
+///
+/// let (x, y) = ([r, s, m], []);
+/// let proof = Proof::new(rng, &cks, &equations[0], &x, &y);
+/// let (c, d) = (x.map(|x_i| cks.u.commit(&x_i)), []);
+/// assert!(equations[0].verify(&cks, &c, &d, &proof));
+///
+/// let (x, y) = ([r], [t]);
+/// let proof = Proof::new(rng, &cks, &equations[1], &x, &y);
+/// let (c, d) = (x.map(|x_i| cks.u.commit(&x_i)), y.map(|y_j| cks.v.commit(&y_j)));
+/// assert!(equations[1].verify(&cks, &c, &d, &proof));
+/// ```
+pub fn sps_verification_equations<E: Pairing>(
+    pk: &SpsPublicKey<E>,
+    message_slots: &[MessageSlot<E>],
+) -> Vec<Equation<E>> {
+    assert_eq!(message_slots.len(), pk.w.len());
+
+    let g = <E as Pairing>::G1Affine::generator();
+    let h = <E as Pairing>::G2Affine::generator();
+
+    // e(R, V) e(S, H) Π e(M_i, W_i) = e(G, Z)
+    let mut b = vec![pk.v, h];
+    let mut target = E::pairing(g, pk.z);
+    for (slot, w_i) in message_slots.iter().zip(pk.w.iter()) {
+        match slot {
+            MessageSlot::Committed => b.push(*w_i),
+            MessageSlot::Public(m_i) => target -= E::pairing(m_i, w_i),
+        }
+    }
+    let m = b.len();
+    let linear = Equation::new(
+        vec![],
+        b,
+        Matrix::from_elem(m, 0, E::ScalarField::zero()),
+        target,
+    );
+
+    // e(R, T) = e(G, H)
+    let quadratic = Equation::new(
+        vec![<E as Pairing>::G1Affine::zero()],
+        vec![<E as Pairing>::G2Affine::zero()],
+        Matrix::new(&[[E::ScalarField::one()]]),
+        E::pairing(g, h),
+    );
+
+    vec![linear, quadratic]
+}
+
+/// Returns the equation of verifying a BLS signature `σ` in G1 on the message hashed to `h_m = H(m)` under the
+/// committed public key `pk` in G2, i.e. `e(σ, g2) = e(h_m, pk)` written as:
+///
+/// e(h_m^-1, pk) e(σ, g2) = 1
+///
+/// with the variables `x = (σ)` and `y = (pk)`. See [bls_possession_with_public_key] for the public `pk`.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+/// use ark_std::{test_rng, UniformRand, Zero};
+/// use gs_ppe::{setup, statements::bls_possession, CommitmentKeys, Matrix, ProofSystem, Variable};
+///
+/// type G1 = <E as Pairing>::G1;
+/// type G2Affine = <E as Pairing>::G2Affine;
+/// type Fr = <E as Pairing>::ScalarField;
+///
+/// let rng = &mut test_rng();
+/// let g2 = G2Affine::generator();
+/// // A stand-in for the message hashed to G1.
+/// let h_m = G1::rand(rng).into_affine();
+/// let sk = Fr::rand(rng);
+/// let (pk, sig) = ((g2 * sk).into_affine(), (h_m * sk).into_affine());
+///
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let (sig, pk) = (Variable::new(rng, sig), Variable::new(rng, pk));
+/// let gamma = Matrix::from_elem(1, 1, Fr::zero());
+/// let proof_system = setup(rng, &cks, &[(-h_m, pk)], &[(sig, g2)], &gamma);
+/// let ProofSystem { c, d, proof, .. } = proof_system.randomize(rng, &cks);
+///
+/// let equation = bls_possession::<E>(h_m, g2);
+/// assert!(equation.verify(&cks, &c, &d, &proof));
+/// ```
+pub fn bls_possession<E: Pairing>(
+    h_m: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+) -> Equation<E> {
+    Equation::new(
+        vec![h_m.into_group().neg().into_affine()],
+        vec![g2],
+        Matrix::from_elem(1, 1, E::ScalarField::zero()),
+        PairingOutput::zero(),
+    )
+}
+
+/// Same as [bls_possession], but the public key `pk` is public, i.e. the equation is:
+///
+/// e(σ, g2) = e(h_m, pk)
+///
+/// with the variables `x = (σ)` and no variables `y`.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+/// use ark_std::{test_rng, UniformRand, Zero};
+/// use gs_ppe::{
+///     setup, statements::bls_possession_with_public_key, CommitmentKeys, Matrix, ProofSystem,
+///     Variable,
+/// };
+///
+/// type G1 = <E as Pairing>::G1;
+/// type G2Affine = <E as Pairing>::G2Affine;
+/// type Fr = <E as Pairing>::ScalarField;
+///
+/// let rng = &mut test_rng();
+/// let g2 = G2Affine::generator();
+/// // A stand-in for the message hashed to G1.
+/// let h_m = G1::rand(rng).into_affine();
+/// let sk = Fr::rand(rng);
+/// let (pk, sig) = ((g2 * sk).into_affine(), (h_m * sk).into_affine());
+///
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let sig = Variable::new(rng, sig);
+/// let gamma = Matrix::from_elem(1, 0, Fr::zero());
+/// let proof_system = setup(rng, &cks, &[], &[(sig, g2)], &gamma);
+/// let ProofSystem { c, d, proof, .. } = proof_system.randomize(rng, &cks);
+///
+/// let equation = bls_possession_with_public_key::<E>(h_m, g2, pk);
+/// assert!(equation.verify(&cks, &c, &d, &proof));
+/// ```
+pub fn bls_possession_with_public_key<E: Pairing>(
+    h_m: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+    pk: <E as Pairing>::G2Affine,
+) -> Equation<E> {
+    Equation::new(
+        vec![],
+        vec![g2],
+        Matrix::from_elem(1, 0, E::ScalarField::zero()),
+        E::pairing(h_m, pk),
+    )
+}
+
+/// Returns the equation of a Diffie-Hellman tuple `(g1, g2, X, Y)`, i.e. `X = g1^s` and `Y = g2^s` for the same
+/// `s`, which is `e(X, g2) = e(g1, Y)` written as:
+///
+/// e(g1^-1, Y) e(X, g2) = 1
+///
+/// with the variables `x = (X)` in G1 and `y = (Y)` in G2.
+pub fn dh_tuple<E: Pairing>(
+    g1: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+) -> Equation<E> {
+    Equation::new(
+        vec![g1.into_group().neg().into_affine()],
+        vec![g2],
+        Matrix::from_elem(1, 1, E::ScalarField::zero()),
+        PairingOutput::zero(),
+    )
+}
+
+/// Commits to the variables `x` and `y`, and proves that `(g1, g2, x, y)` is a Diffie-Hellman tuple, i.e.
+/// the returned proof system is over the equation [dh_tuple]. The proof does not verify if it is not a
+/// Diffie-Hellman tuple.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, AffineRepr};
+/// use ark_std::{test_rng, UniformRand};
+/// use gs_ppe::{statements::prove_dh_tuple, CommitmentKeys, ProofSystem, Variable};
+///
+/// type G1Affine = <E as Pairing>::G1Affine;
+/// type G2Affine = <E as Pairing>::G2Affine;
+/// type Fr = <E as Pairing>::ScalarField;
+///
+/// let rng = &mut test_rng();
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+///
+/// let s = Fr::rand(rng);
+/// let (x, y) = (Variable::from_scalar(rng, s), Variable::from_scalar(rng, s));
+/// let ProofSystem {
+///     equation,
+///     c,
+///     d,
+///     proof,
+/// } = prove_dh_tuple(rng, &cks, g1, g2, x, y);
+/// assert!(equation.verify(&cks, &c, &d, &proof));
+/// ```
+pub fn prove_dh_tuple<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    g1: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+    x: Variable<<E as Pairing>::G1>,
+    y: Variable<<E as Pairing>::G2>,
+) -> ProofSystem<E> {
+    let equation = dh_tuple(g1, g2);
+    let proof = Proof::new(rng, cks, &equation, &[x], &[y]);
+    ProofSystem {
+        equation,
+        c: vec![cks.u.commit(&x)],
+        d: vec![cks.v.commit(&y)],
+        proof,
+    }
+}
+
+/// Returns the equation that the variable `X` in G1 is the public `value` `V`, i.e.
+///
+/// e(X, g2) = e(V, g2)
+///
+/// with the variables `x = (X)` and no variables `y`.
+pub fn opening_to_public<E: Pairing>(
+    value: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+) -> Equation<E> {
+    Equation::new(
+        vec![],
+        vec![g2],
+        Matrix::from_elem(1, 0, E::ScalarField::zero()),
+        E::pairing(value, g2),
+    )
+}
+
+/// Proves that the variable `x` opens to the public `value`, i.e. the returned proof system is over the equation
+/// [opening_to_public], and the commitment `c` is the commitment `Com(ck, X, r)` of `x`, e.g. the one
+/// published before. The proof does not verify if the value of `x` is not `value`.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, AffineRepr};
+/// use ark_std::{test_rng, UniformRand};
+/// use gs_ppe::{statements::prove_opening, CommitmentKeys, ProofSystem, Variable};
+///
+/// type G1Affine = <E as Pairing>::G1Affine;
+/// type G2Affine = <E as Pairing>::G2Affine;
+///
+/// let rng = &mut test_rng();
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let value = G1Affine::rand(rng);
+/// let x = Variable::new(rng, value);
+/// let published = cks.u.commit(&x);
+///
+/// let ProofSystem {
+///     equation,
+///     c,
+///     d,
+///     proof,
+/// } = prove_opening(rng, &cks, x, value, G2Affine::generator());
+/// assert_eq!(c, vec![published]);
+/// assert!(equation.verify(&cks, &c, &d, &proof));
+/// ```
+pub fn prove_opening<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    x: Variable<<E as Pairing>::G1>,
+    value: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+) -> ProofSystem<E> {
+    let equation = opening_to_public(value, g2);
+    let proof = Proof::new(rng, cks, &equation, &[x], &[]);
+    ProofSystem {
+        equation,
+        c: vec![cks.u.commit(&x)],
+        d: vec![],
+        proof,
+    }
+}
+
+/// Returns the equation that the variables `X1` and `X2` in G1 are equal, i.e.
+///
+/// e(X1, g2) e(X2, g2^-1) = 1
+///
+/// with the variables `x = (X1, X2)` and no variables `y`.
+pub fn committed_equal_g1<E: Pairing>(g2: <E as Pairing>::G2Affine) -> Equation<E> {
+    Equation::new(
+        vec![],
+        vec![g2, g2.into_group().neg().into_affine()],
+        Matrix::from_elem(2, 0, E::ScalarField::zero()),
+        PairingOutput::zero(),
+    )
+}
+
+/// Returns the equation that the variables `Y1` and `Y2` in G2 are equal, i.e.
+///
+/// e(g1, Y1) e(g1^-1, Y2) = 1
+///
+/// with no variables `x` and the variables `y = (Y1, Y2)`.
+pub fn committed_equal_g2<E: Pairing>(g1: <E as Pairing>::G1Affine) -> Equation<E> {
+    Equation::new(
+        vec![g1, g1.into_group().neg().into_affine()],
+        vec![],
+        Matrix::zeros_column(2),
+        PairingOutput::zero(),
+    )
+}
+
+/// Proves that the variables `x1` and `x2` have the same value, i.e. the returned proof system is over the
+/// equation [committed_equal_g1], and the commitments `c` are the commitments of `x1` and `x2`, e.g. the ones
+/// published before. The proof does not verify if the values are different.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, AffineRepr};
+/// use ark_std::{test_rng, UniformRand};
+/// use gs_ppe::{statements::prove_committed_equal_g1, CommitmentKeys, ProofSystem, Variable};
+///
+/// type G1Affine = <E as Pairing>::G1Affine;
+/// type G2Affine = <E as Pairing>::G2Affine;
+///
+/// let rng = &mut test_rng();
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let value = G1Affine::rand(rng);
+/// let (x1, x2) = (Variable::new(rng, value), Variable::new(rng, value));
+///
+/// let ProofSystem {
+///     equation,
+///     c,
+///     d,
+///     proof,
+/// } = prove_committed_equal_g1(rng, &cks, x1, x2, G2Affine::generator());
+/// assert_eq!(c, vec![cks.u.commit(&x1), cks.u.commit(&x2)]);
+/// assert!(equation.verify(&cks, &c, &d, &proof));
+/// ```
+pub fn prove_committed_equal_g1<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    x1: Variable<<E as Pairing>::G1>,
+    x2: Variable<<E as Pairing>::G1>,
+    g2: <E as Pairing>::G2Affine,
+) -> ProofSystem<E> {
+    let equation = committed_equal_g1(g2);
+    let proof = Proof::new(rng, cks, &equation, &[x1, x2], &[]);
+    ProofSystem {
+        equation,
+        c: vec![cks.u.commit(&x1), cks.u.commit(&x2)],
+        d: vec![],
+        proof,
+    }
+}
+
+/// Proves that the commitments `c1 = Com(ck, X, r1)` and `c2 = Com(ck, X, r2)` in G1 open to the same value `X`,
+/// over the equation [Equation::for_commitment_equality]. The proof verifies with `c = [c1, c2]` and `d = []`.
+///
+/// ## Panics
+/// Panics if `c1` (resp. `c2`) does not open to `x` with the randomness `r1` (resp. `r2`).
+pub fn prove_commitment_equality<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    c1: &Com<<E as Pairing>::G1>,
+    c2: &Com<<E as Pairing>::G1>,
+    x: <E as Pairing>::G1Affine,
+    r1: Randomness<<E as Pairing>::G1>,
+    r2: Randomness<<E as Pairing>::G1>,
+) -> Proof<E> {
+    let x1 = Variable::with_randomness(x, r1);
+    let x2 = Variable::with_randomness(x, r2);
+    assert!(c1.verify_opening(&cks.u, &x1));
+    assert!(c2.verify_opening(&cks.u, &x2));
+
+    let equation = Equation::for_commitment_equality(cks);
+    Proof::new(rng, cks, &equation, &[x1, x2], &[])
+}
+
+/// Same as [prove_committed_equal_g1], but for the variables `y1` and `y2` in G2 over the equation
+/// [committed_equal_g2].
+pub fn prove_committed_equal_g2<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    y1: Variable<<E as Pairing>::G2>,
+    y2: Variable<<E as Pairing>::G2>,
+    g1: <E as Pairing>::G1Affine,
+) -> ProofSystem<E> {
+    let equation = committed_equal_g2(g1);
+    let proof = Proof::new(rng, cks, &equation, &[], &[y1, y2]);
+    ProofSystem {
+        equation,
+        c: vec![],
+        d: vec![cks.v.commit(&y1), cks.v.commit(&y2)],
+        proof,
+    }
+}
+
+/// Returns the equation that the variables `X` in G1 and `Y` in G2 have the same exponent, i.e. `e(X, g2) = e(g1, Y)`.
+/// It is the same equation as [dh_tuple].
+///
+/// To link the witnesses of other equations, prove the equations with the same variables `X` and `Y` (including
+/// their randomness), so that all the proofs are verified against the same commitments `c` and `d`.
+///
+/// ## Example
+///
+/// ```
+/// use ark_bls12_381::Bls12_381 as E;
+/// use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+/// use ark_std::{test_rng, UniformRand};
+/// use gs_ppe::{
+///     statements::{cross_group_link, opening_to_public},
+///     CommitmentKeys, Proof, Variable,
+/// };
+///
+/// type G1Affine = <E as Pairing>::G1Affine;
+/// type G2Affine = <E as Pairing>::G2Affine;
+/// type Fr = <E as Pairing>::ScalarField;
+///
+/// let rng = &mut test_rng();
+/// let cks = CommitmentKeys::<E>::rand(rng);
+/// let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+///
+/// let s = Fr::rand(rng);
+/// let (x, y) = (Variable::from_scalar(rng, s), Variable::from_scalar(rng, s));
+/// let (c, d) = (vec![cks.u.commit(&x)], vec![cks.v.commit(&y)]);
+///
+/// // The committed Y has the same exponent as the committed X ...
+/// let link = cross_group_link::<E>(g1, g2);
+/// let link_proof = Proof::new(rng, &cks, &link, &[x], &[y]);
+/// assert!(link.verify(&cks, &c, &d, &link_proof));
+///
+/// // ... which opens to the public g1^s, i.e. Y = g2^s without revealing Y.
+/// let public = (g1 * s).into_affine();
+/// let opening = opening_to_public::<E>(public, g2);
+/// let opening_proof = Proof::new(rng, &cks, &opening, &[x], &[]);
+/// assert!(opening.verify(&cks, &c, &[], &opening_proof));
+/// ```
+pub fn cross_group_link<E: Pairing>(
+    g1: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+) -> Equation<E> {
+    dh_tuple(g1, g2)
+}
+
+/// Commits to the variables `x` and `y`, and proves that they have the same exponent, i.e. the returned proof
+/// system is over the equation [cross_group_link]. The proof does not verify if the exponents are different.
+pub fn prove_cross_group_link<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    g1: <E as Pairing>::G1Affine,
+    g2: <E as Pairing>::G2Affine,
+    x: Variable<<E as Pairing>::G1>,
+    y: Variable<<E as Pairing>::G2>,
+) -> ProofSystem<E> {
+    prove_dh_tuple(rng, cks, g1, g2, x, y)
+}
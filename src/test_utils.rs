@@ -0,0 +1,126 @@
+//! Helpers for generating random satisfiable instances of the proof system in tests and benchmarks.
+//! Enabled by the feature `test-utils`.
+//!
+//! ## Example
+//!
+//! ```
+//! use ark_bls12_381::Bls12_381 as E;
+//! use ark_std::test_rng;
+//! use gs_ppe::{test_utils, CommitmentKeys, Proof, ProofSystem};
+//!
+//! let rng = &mut test_rng();
+//! let cks = CommitmentKeys::<E>::rand(rng);
+//!
+//! // A random equation with 2 variables in G1 and 3 variables in G2 and its witnesses.
+//! let (equation, x, y) = test_utils::random_instance::<E, _>(rng, 2, 3);
+//! let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+//! let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+//! let proof = Proof::new(rng, &cks, &equation, &x, &y);
+//! assert!(equation.verify(&cks, &c, &d, &proof));
+//!
+//! // Or a random proof system directly.
+//! let ProofSystem {
+//!     equation,
+//!     c,
+//!     d,
+//!     proof,
+//! } = test_utils::random_proof_system(rng, &cks, 2, 3);
+//! assert!(equation.verify(&cks, &c, &d, &proof));
+//! ```
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_std::{rand::Rng, UniformRand, Zero};
+
+use crate::{setup, CommitmentKeys, Equation, Matrix, ProofSystem, Variable};
+
+/// A satisfiable equation together with its variables `x` and `y`.
+pub type Instance<E> = (
+    Equation<E>,
+    Vec<Variable<<E as Pairing>::G1>>,
+    Vec<Variable<<E as Pairing>::G2>>,
+);
+
+/// Returns a vector of `size` random `Variable<G1>`.
+pub fn x_variable_vec<E: Pairing, R: Rng>(
+    rng: &mut R,
+    size: usize,
+) -> Vec<Variable<<E as Pairing>::G1>> {
+    variable_vec(rng, size)
+}
+
+/// Returns a vector of `size` random `Variable<G2>`.
+pub fn y_variable_vec<E: Pairing, R: Rng>(
+    rng: &mut R,
+    size: usize,
+) -> Vec<Variable<<E as Pairing>::G2>> {
+    variable_vec(rng, size)
+}
+
+/// Returns a random equation with `m` variables `x` in G1 and `n` variables `y` in G2 (i.e. `gamma` is
+/// a random `m x n` matrix), together with the variables which satisfy it.
+pub fn random_instance<E: Pairing, R: Rng>(rng: &mut R, m: usize, n: usize) -> Instance<E> {
+    let gamma = Matrix::<E::ScalarField>::rand(rng, m, n);
+    random_instance_with_gamma(rng, gamma)
+}
+
+/// Same as [random_instance], but the equation is linear, i.e. `gamma` is a zero matrix, so that the
+/// equation is `Π e(a_j, y_j) Π e(x_i, b_i) = T`.
+pub fn random_linear_instance<E: Pairing, R: Rng>(rng: &mut R, m: usize, n: usize) -> Instance<E> {
+    let gamma = Matrix::from_elem(m, n, E::ScalarField::zero());
+    random_instance_with_gamma(rng, gamma)
+}
+
+/// Same as [random_instance], but with the given matrix `gamma`.
+pub fn random_instance_with_gamma<E: Pairing, R: Rng>(
+    rng: &mut R,
+    gamma: Matrix<E::ScalarField>,
+) -> Instance<E> {
+    let (m, n) = gamma.dim();
+    let x = x_variable_vec::<E, _>(rng, m);
+    let y = y_variable_vec::<E, _>(rng, n);
+    let a = (0..n)
+        .map(|_| <E as Pairing>::G1::rand(rng).into_affine())
+        .collect::<Vec<_>>();
+    let b = (0..m)
+        .map(|_| <E as Pairing>::G2::rand(rng).into_affine())
+        .collect::<Vec<_>>();
+
+    let equation = Equation::from_witnesses(
+        a,
+        b,
+        gamma,
+        &x.iter().map(|x_i| x_i.value).collect::<Vec<_>>(),
+        &y.iter().map(|y_j| y_j.value).collect::<Vec<_>>(),
+    );
+
+    (equation, x, y)
+}
+
+/// Returns a random proof system (i.e. the equation, commitments and the proof) over an equation with `m`
+/// variables in G1 and `n` variables in G2, using the commitment keys `cks`.
+pub fn random_proof_system<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    m: usize,
+    n: usize,
+) -> ProofSystem<E> {
+    let gamma = Matrix::<E::ScalarField>::rand(rng, m, n);
+    let ay = y_variable_vec::<E, _>(rng, n)
+        .into_iter()
+        .map(|y_j| (<E as Pairing>::G1::rand(rng).into_affine(), y_j))
+        .collect::<Vec<_>>();
+    let xb = x_variable_vec::<E, _>(rng, m)
+        .into_iter()
+        .map(|x_i| (x_i, <E as Pairing>::G2::rand(rng).into_affine()))
+        .collect::<Vec<_>>();
+    setup(rng, cks, &ay, &xb, &gamma)
+}
+
+fn variable_vec<G: CurveGroup, R: Rng>(rng: &mut R, size: usize) -> Vec<Variable<G>> {
+    (0..size)
+        .map(|_| {
+            let value = G::rand(rng);
+            Variable::new(rng, value.into_affine())
+        })
+        .collect()
+}
@@ -0,0 +1,115 @@
+//! Defines the canonical byte encoding of the components in the proof system (i.e. [Com](crate::Com),
+//! [Proof](crate::Proof), [Equation](crate::Equation) and [ProofSystem](crate::ProofSystem)) for absorbing
+//! them into a transcript, e.g. for deriving a Fiat-Shamir challenge.
+//!
+//! The encoding is stable and versioned by [TRANSCRIPT_ENCODING_VERSION]. It does not depend on any
+//! serialization flags. Each value is encoded as a header followed by its body:
+//! - header: `"gs-ppe" || version (1 byte) || len(label) (1 byte) || label`, where the label is the type name,
+//!   e.g. `"Com"`.
+//! - group elements and pairing outputs: their compressed canonical serialization.
+//! - lengths of vectors and dimensions of matrices: 8 bytes in little endian.
+//! - matrices: the number of rows and columns followed by the elements in row-major order.
+//!
+//! The bodies are:
+//! - `Com`: `c1 || c2`.
+//! - `Proof`: `φ || θ` as 2x2 matrices.
+//! - `Equation`: `a || b || gamma || target`, where `a` and `b` are encoded as vectors.
+//! - `ProofSystem`: `Equation || c || d || Proof`, where `c` and `d` are encoded as vectors of `Com` bodies.
+//! - `CommitmentKeys`: `u11 || u12 || u21 || u22 || v11 || v12 || v21 || v22`.
+//!
+//! The trait [TranscriptProtocol] absorbs the encodings into a transcript, e.g. a `merlin::Transcript`.
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+
+use crate::{Com, Equation, Matrix, Proof, ProofSystem};
+
+/// Extension methods for absorbing the components in the proof system into a transcript and
+/// deriving challenge scalars from it, e.g. to bind the proofs to surrounding signatures.
+///
+/// The required methods have the same signatures as the ones of `merlin::Transcript`, so a merlin
+/// transcript (or a wrapper of it) implements this trait by forwarding the calls. The provided methods
+/// append the canonical transcript encoding of the values as messages under the given labels.
+pub trait TranscriptProtocol {
+    /// Appends a `message` to the transcript under the `label`.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+
+    /// Fills `dest` with the challenge bytes derived from the transcript under the `label`.
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]);
+
+    /// Appends the commitment `com` to the transcript under the `label`.
+    fn append_com<G: CurveGroup>(&mut self, label: &'static [u8], com: &Com<G>) {
+        self.append_message(label, &com.to_transcript_bytes());
+    }
+
+    /// Appends the proof `proof` to the transcript under the `label`.
+    fn append_proof<E: Pairing>(&mut self, label: &'static [u8], proof: &Proof<E>) {
+        self.append_message(label, &proof.to_transcript_bytes());
+    }
+
+    /// Appends the equation `equ` to the transcript under the `label`.
+    fn append_equation<E: Pairing>(&mut self, label: &'static [u8], equ: &Equation<E>) {
+        self.append_message(label, &equ.to_transcript_bytes());
+    }
+
+    /// Appends the proof system `ps` to the transcript under the `label`.
+    fn append_proof_system<E: Pairing>(&mut self, label: &'static [u8], ps: &ProofSystem<E>) {
+        self.append_message(label, &ps.to_transcript_bytes());
+    }
+
+    /// Derives a challenge scalar under the `label`. It reduces 64 challenge bytes modulo the order
+    /// of the field, so that the result is statistically close to uniform.
+    fn challenge_scalar<F: PrimeField>(&mut self, label: &'static [u8]) -> F {
+        let mut bytes = [0u8; 64];
+        self.challenge_bytes(label, &mut bytes);
+        F::from_le_bytes_mod_order(&bytes)
+    }
+}
+
+/// The version of the transcript encoding. It changes whenever the encoding changes.
+pub const TRANSCRIPT_ENCODING_VERSION: u8 = 1;
+
+/// The domain separator at the beginning of every header.
+const DOMAIN: &[u8] = b"gs-ppe";
+
+/// Returns the header `"gs-ppe" || version || len(label) || label`.
+pub(crate) fn header(label: &[u8]) -> Vec<u8> {
+    let mut bytes = DOMAIN.to_vec();
+    bytes.push(TRANSCRIPT_ENCODING_VERSION);
+    bytes.push(label.len() as u8);
+    bytes.extend_from_slice(label);
+    bytes
+}
+
+/// Appends the compressed canonical serialization of the element.
+pub(crate) fn append_element<T: CanonicalSerialize>(bytes: &mut Vec<u8>, element: &T) {
+    element
+        .serialize_compressed(bytes)
+        .expect("serializing into a vector never fails");
+}
+
+/// Appends the length as 8 bytes in little endian.
+pub(crate) fn append_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+/// Appends the elements in a vector, prefixed by its length.
+pub(crate) fn append_vec<T: CanonicalSerialize>(bytes: &mut Vec<u8>, elements: &[T]) {
+    append_len(bytes, elements.len());
+    elements.iter().for_each(|e| append_element(bytes, e));
+}
+
+/// Appends the elements in a matrix in row-major order, prefixed by its dimension.
+pub(crate) fn append_matrix<T: Clone + CanonicalSerialize>(
+    bytes: &mut Vec<u8>,
+    matrix: &Matrix<T>,
+) {
+    let (rows, cols) = matrix.dim();
+    append_len(bytes, rows);
+    append_len(bytes, cols);
+    matrix
+        .as_ref()
+        .iter()
+        .for_each(|e| append_element(bytes, e));
+}
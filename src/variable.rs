@@ -1,10 +1,18 @@
 //! Defines the struct [Variable], the variable for the Pairing Product Equation.
 //! i.e. the (`X`, `r`) and (`Y`, `s`) notated in the paper.
 
-use ark_ec::CurveGroup;
-use ark_std::rand::Rng;
+use ark_ec::{
+    hashing::{HashToCurve, HashToCurveError},
+    CurveGroup,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{rand::Rng, Zero};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Add, Sub},
+};
 
-use crate::Randomness;
+use crate::{com::ComRandomness, commit::CommitmentKey, Com, Randomness};
 
 /// Variable `X` or `Y` for the Pairing Product Equation, which represents the values that the prover wants to hide.
 /// It carries the randomness `r` or `s` for being used in commitment scheme for proof construction.
@@ -26,9 +34,213 @@ impl<G: CurveGroup> Variable<G> {
         Self::with_randomness(value, Randomness::zero())
     }
 
+    /// Constructs a public variable in a slot of the equation whose value is announced, i.e. the same as
+    /// [Variable::with_zero_randomness]. Its commitment is the trivial commitment [Com::trivial] to `value`,
+    /// which the verifier checks with [ProofSystem::check_public_slots](crate::ProofSystem::check_public_slots).
+    pub fn public(value: G::Affine) -> Self {
+        Self::with_zero_randomness(value)
+    }
+
     /// Constructs a new variable `X` or `Y` with the given `value` and internal randomness `r` or `s`,
     /// where the randomness is set to the given randomness.
     pub fn with_randomness(value: G::Affine, rand: Randomness<G>) -> Self {
         Self { value, rand }
     }
+
+    /// Constructs a new variable `X` or `Y` with the given `value` and internal randomness derived from the
+    /// `master_seed` and the `context` by [Randomness::derive].
+    pub fn new_derived(value: G::Affine, master_seed: &[u8], context: &[u8]) -> Self {
+        Self::with_randomness(value, Randomness::derive(master_seed, context))
+    }
+
+    /// Constructs a new variable `X` or `Y` with the value `G^s` for the given scalar `s`, where `G` is the
+    /// generator of group `G`, and internal randomness `r` or `s` generated by `rng`.
+    pub fn from_scalar<R: Rng>(rng: &mut R, s: G::ScalarField) -> Self {
+        Self::new(rng, (G::generator() * s).into_affine())
+    }
+
+    /// Constructs a new variable `X` or `Y` with the generator of group `G` as the value, and internal
+    /// randomness `r` or `s` generated by `rng`.
+    pub fn from_generator<R: Rng>(rng: &mut R) -> Self {
+        Self::new(rng, G::generator().into_affine())
+    }
+
+    /// Constructs a new variable `X` or `Y` with the `value` deserialized from its compressed bytes,
+    /// and internal randomness `r` or `s` generated by `rng`.
+    ///
+    /// Returns [SerializationError] if `bytes` is not a valid compressed encoding of a point in group `G`.
+    pub fn from_compressed_bytes<R: Rng>(
+        rng: &mut R,
+        bytes: &[u8],
+    ) -> Result<Self, SerializationError> {
+        let value = G::Affine::deserialize_compressed(bytes)?;
+        Ok(Self::new(rng, value))
+    }
+
+    /// Constructs a new variable `X` or `Y` with the value hashed from the message `msg` by the hasher `H` (e.g.
+    /// [MapToCurveBasedHasher](ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher) for the IETF hash to
+    /// curve) with the domain separation tag `domain`, and internal randomness `r` or `s` generated by `rng`.
+    /// The value can be recomputed from a public message by [Variable::hash_value].
+    ///
+    /// Returns [HashToCurveError] if the hasher cannot be constructed or fails to hash the message.
+    pub fn from_bytes_hash<R: Rng, H: HashToCurve<G>>(
+        rng: &mut R,
+        msg: &[u8],
+        domain: &[u8],
+    ) -> Result<Self, HashToCurveError> {
+        let value = Self::hash_value::<H>(msg, domain)?;
+        Ok(Self::new(rng, value))
+    }
+
+    /// Returns the value of [Variable::from_bytes_hash], i.e. the message `msg` hashed by the hasher `H` with
+    /// the domain separation tag `domain`.
+    ///
+    /// Returns [HashToCurveError] if the hasher cannot be constructed or fails to hash the message.
+    pub fn hash_value<H: HashToCurve<G>>(
+        msg: &[u8],
+        domain: &[u8],
+    ) -> Result<G::Affine, HashToCurveError> {
+        H::new(domain)?.hash(msg)
+    }
+
+    /// Constructs a new variable with the value `f(X)` of the variable `v = (X, r)` and fresh internal randomness
+    /// generated by `rng`, e.g. `f` is a group endomorphism or a homomorphism to another group `H`.
+    pub fn map_value<R: Rng, H: CurveGroup>(
+        rng: &mut R,
+        v: &Variable<G>,
+        f: impl Fn(G::Affine) -> H::Affine,
+    ) -> Variable<H> {
+        Self::map_value_deterministic(v, f, Randomness::rand(rng))
+    }
+
+    /// Same as [Variable::map_value], but the internal randomness is set to the given randomness `rand`.
+    pub fn map_value_deterministic<H: CurveGroup>(
+        v: &Variable<G>,
+        f: impl Fn(G::Affine) -> H::Affine,
+        rand: Randomness<H>,
+    ) -> Variable<H> {
+        Variable::with_randomness(f(v.value), rand)
+    }
+}
+
+/// Homomorphic addition of two variables, i.e. `(X, r) + (X', r') = (X + X', r + r')`, so that the commitment
+/// of the sum is the sum of the commitments.
+impl<G: CurveGroup> Add for Variable<G> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::with_randomness((self.value + other.value).into(), self.rand + other.rand)
+    }
+}
+
+/// Homomorphic subtraction of two variables, i.e. `(X, r) - (X', r') = (X - X', r - r')`, so that the commitment
+/// of the difference is the difference of the commitments.
+impl<G: CurveGroup> Sub for Variable<G> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::with_randomness((self.value - other.value).into(), self.rand - other.rand)
+    }
+}
+
+/// Displays the first 8 bytes of the compressed encoding of the value in hex, e.g. `Variable(8d0e2c1f9a3b7e44...)`.
+/// The randomness is not displayed.
+impl<G: CurveGroup> Display for Variable<G> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Variable(")?;
+        fmt_short_hex(f, &self.value)?;
+        write!(f, ")")
+    }
+}
+
+/// A variable together with its commitment `Com(ck, X, r)`, which are kept consistent, i.e. the commitment always
+/// opens to the variable under the commitment key `ck`.
+#[derive(Copy, Clone, Debug)]
+pub struct CommittedVariable<G: CurveGroup> {
+    variable: Variable<G>,
+    commitment: Com<G>,
+}
+
+impl<G: CurveGroup> CommittedVariable<G> {
+    /// Constructs a new variable with the given `value` and internal randomness generated by `rng`, and commits
+    /// to it with the commitment key `ck`.
+    pub fn commit<R: Rng>(rng: &mut R, ck: &CommitmentKey<G>, value: G::Affine) -> Self {
+        let variable = Variable::new(rng, value);
+        let commitment = ck.commit(&variable);
+        Self {
+            variable,
+            commitment,
+        }
+    }
+
+    /// Constructs the committed variable from the `variable` and its `commitment` under the commitment key `ck`.
+    ///
+    /// Returns [InvalidOpening] if the commitment does not open to the variable, i.e.
+    /// [Com::verify_opening] fails.
+    pub fn from_parts(
+        ck: &CommitmentKey<G>,
+        variable: Variable<G>,
+        commitment: Com<G>,
+    ) -> Result<Self, InvalidOpening> {
+        if !commitment.verify_opening(ck, &variable) {
+            return Err(InvalidOpening);
+        }
+        Ok(Self {
+            variable,
+            commitment,
+        })
+    }
+
+    /// Returns the variable.
+    pub fn variable(&self) -> &Variable<G> {
+        &self.variable
+    }
+
+    /// Returns the commitment.
+    pub fn commitment(&self) -> &Com<G> {
+        &self.commitment
+    }
+
+    /// Returns the variable and the commitment.
+    pub fn into_parts(self) -> (Variable<G>, Com<G>) {
+        (self.variable, self.commitment)
+    }
+
+    /// Randomizes the commitment by [Com::randomize] and adds the same randomness to the variable, so that the
+    /// commitment still opens to the variable. Returns the original commitment and the added randomness as
+    /// [ComRandomness], i.e. the input of [Proof::randomize](crate::Proof::randomize) to adapt a proof to the
+    /// randomized commitment.
+    pub fn rerandomize<R: Rng>(&mut self, rng: &mut R, ck: &CommitmentKey<G>) -> ComRandomness<G> {
+        let (original, rand) = self.commitment.randomize(rng, ck);
+        self.variable.rand = self.variable.rand + rand;
+        (original, rand)
+    }
+}
+
+/// The error returned by [CommittedVariable::from_parts] if the commitment does not open to the variable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOpening;
+
+impl std::fmt::Display for InvalidOpening {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the commitment does not open to the variable")
+    }
+}
+
+impl std::error::Error for InvalidOpening {}
+
+/// Writes the first 8 bytes of the compressed encoding of `value` in hex, followed by `...`.
+pub(crate) fn fmt_short_hex<T: CanonicalSerialize>(
+    f: &mut Formatter<'_>,
+    value: &T,
+) -> fmt::Result {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .map_err(|_| fmt::Error)?;
+    bytes
+        .iter()
+        .take(8)
+        .try_for_each(|b| write!(f, "{b:02x}"))?;
+    write!(f, "...")
 }
@@ -0,0 +1,114 @@
+//! Presents the proof system as a verifiable encryption scheme. Under the binding commitment keys (e.g.
+//! generated by [CommitmentKeys::rand_ex](crate::CommitmentKeys::rand_ex)), a commitment is an encryption of
+//! the committed variable under the extract key `ek`, and the proof certifies that the plaintexts satisfy
+//! the equation. The holder of `ek` (e.g. an authority in key escrow) decrypts the ciphertexts with
+//! [ExtractKey::decrypt_1](crate::ExtractKey::decrypt_1) and [ExtractKey::decrypt_2](crate::ExtractKey::decrypt_2).
+//!
+//! ## Example
+//!
+//! ```
+//! use ark_bls12_381::Bls12_381 as E;
+//! use ark_ec::pairing::Pairing;
+//! use ark_std::{test_rng, UniformRand};
+//! use gs_ppe::{verifiable_encryption::encrypt_and_prove, CommitmentKeys, Matrix, Variable};
+//!
+//! type G1 = <E as Pairing>::G1;
+//! type G2 = <E as Pairing>::G2;
+//! type G1Affine = <E as Pairing>::G1Affine;
+//! type G2Affine = <E as Pairing>::G2Affine;
+//! type Fr = <E as Pairing>::ScalarField;
+//!
+//! let rng = &mut test_rng();
+//! // The authority generates the keys and publishes the commitment keys.
+//! let keys = CommitmentKeys::<E>::rand_ex(rng);
+//! let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+//!
+//! // The user encrypts x and y, and proves that they satisfy e(a, y) e(x, b) e(x, y)^gamma = T.
+//! let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+//! let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+//! let (x, y) = (Variable::<G1>::new(rng, x_value), Variable::<G2>::new(rng, y_value));
+//! let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+//! let encryption = encrypt_and_prove(rng, cks, &[(a, y)], &[(x, b)], &gamma);
+//! assert!(encryption.verify(cks));
+//!
+//! // The authority decrypts exactly the values in the statement.
+//! assert_eq!(ek.decrypt_1(&encryption.cx[0]), x_value);
+//! assert_eq!(ek.decrypt_2(&encryption.cy[0]), y_value);
+//! ```
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_std::rand::Rng;
+
+use crate::{setup, Com, CommitmentKeys, Equation, Matrix, Proof, ProofSystem, Variable};
+
+/// The ciphertext of a variable, i.e. its commitment `com`, together with the `index` of the variable
+/// in the equation (i.e. `x_index` or `y_index`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VerifiableCiphertext<G: CurveGroup> {
+    pub com: Com<G>,
+    pub index: usize,
+}
+
+/// The ciphertexts `cx` and `cy` of the variables `x` and `y`, and the `proof` that the plaintexts satisfy
+/// the `equation`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiableEncryption<E: Pairing> {
+    pub equation: Equation<E>,
+    pub cx: Vec<VerifiableCiphertext<<E as Pairing>::G1>>,
+    pub cy: Vec<VerifiableCiphertext<<E as Pairing>::G2>>,
+    pub proof: Proof<E>,
+}
+
+impl<E: Pairing> VerifiableEncryption<E> {
+    /// Verifies that the ciphertexts encrypt the variables which satisfy the equation. Returns false if the
+    /// indices of the ciphertexts are not in the order of the variables in the equation.
+    pub fn verify(&self, cks: &CommitmentKeys<E>) -> bool {
+        if self.cx.iter().enumerate().any(|(i, c_i)| c_i.index != i)
+            || self.cy.iter().enumerate().any(|(j, d_j)| d_j.index != j)
+        {
+            return false;
+        }
+        let c = self.cx.iter().map(|c_i| c_i.com).collect::<Vec<_>>();
+        let d = self.cy.iter().map(|d_j| d_j.com).collect::<Vec<_>>();
+        self.equation.verify(cks, &c, &d, &self.proof)
+    }
+}
+
+impl<E: Pairing> From<ProofSystem<E>> for VerifiableEncryption<E> {
+    fn from(proof_system: ProofSystem<E>) -> Self {
+        let ProofSystem {
+            equation,
+            c,
+            d,
+            proof,
+        } = proof_system;
+        Self {
+            equation,
+            cx: ciphertexts(c),
+            cy: ciphertexts(d),
+            proof,
+        }
+    }
+}
+
+/// Encrypts the variables `x` and `y` and proves that they satisfy the equation, in the same way as [setup].
+/// The commitment keys `cks` must be binding, otherwise the ciphertexts cannot be decrypted.
+///
+/// ## Panics
+/// Panics if the dimension of `gamma` != (`xb.len()`, `ay.len()`).
+pub fn encrypt_and_prove<E: Pairing, R: Rng>(
+    rng: &mut R,
+    cks: &CommitmentKeys<E>,
+    ay: &[(<E as Pairing>::G1Affine, Variable<<E as Pairing>::G2>)],
+    xb: &[(Variable<<E as Pairing>::G1>, <E as Pairing>::G2Affine)],
+    gamma: &Matrix<E::ScalarField>,
+) -> VerifiableEncryption<E> {
+    setup(rng, cks, ay, xb, gamma).into()
+}
+
+fn ciphertexts<G: CurveGroup>(coms: Vec<Com<G>>) -> Vec<VerifiableCiphertext<G>> {
+    coms.into_iter()
+        .enumerate()
+        .map(|(index, com)| VerifiableCiphertext { com, index })
+        .collect()
+}
@@ -0,0 +1,76 @@
+//! Coarse time bounds for proof generation, randomization and verification, which catch large performance
+//! regressions (e.g. a 10x slowdown). They do not replace the benchmarks in `benches`.
+//!
+//! The bound in seconds can be overridden by the environment variable `GS_PPE_PERF_THRESHOLD_SECS`. The default
+//! bounds are generous in debug builds, as the arithmetic of unoptimized builds is much slower.
+
+use ark_bls12_381::Bls12_381 as F;
+use ark_std::test_rng;
+use gs_ppe::{test_utils::random_instance, CommitmentKeys, Proof};
+use std::time::{Duration, Instant};
+
+/// The default bound for the release builds.
+const THRESHOLD_SECS: u64 = 5;
+/// The default bound for the debug builds.
+const THRESHOLD_SECS_DEBUG: u64 = 120;
+
+const M: usize = 10;
+const N: usize = 10;
+
+#[test]
+fn test_performance_prove_and_verify() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (equation, x, y) = random_instance::<F, _>(rng, M, N);
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    assert_within_threshold("prove and verify", start.elapsed());
+}
+
+#[test]
+fn test_performance_randomize() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (equation, x, y) = random_instance::<F, _>(rng, M, N);
+    let mut c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let mut d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let mut proof = Proof::new(rng, &cks, &equation, &x, &y);
+
+    let start = Instant::now();
+    let cr = c
+        .iter_mut()
+        .map(|c_i| c_i.randomize(rng, &cks.u))
+        .collect::<Vec<_>>();
+    let ds = d
+        .iter_mut()
+        .map(|d_j| d_j.randomize(rng, &cks.v))
+        .collect::<Vec<_>>();
+    proof.randomize(rng, &cks, &equation, &cr, &ds);
+    assert_within_threshold("randomize", start.elapsed());
+    assert!(equation.verify(&cks, &c, &d, &proof));
+}
+
+fn assert_within_threshold(name: &str, elapsed: Duration) {
+    let threshold = threshold();
+    assert!(
+        elapsed <= threshold,
+        "{name} (m = {M}, n = {N}) took {elapsed:?}, which exceeds the threshold {threshold:?}"
+    );
+}
+
+fn threshold() -> Duration {
+    let default = if cfg!(debug_assertions) {
+        THRESHOLD_SECS_DEBUG
+    } else {
+        THRESHOLD_SECS
+    };
+    let secs = std::env::var("GS_PPE_PERF_THRESHOLD_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
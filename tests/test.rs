@@ -1,8 +1,16 @@
 use ark_bls12_381::Bls12_381 as F;
-use ark_ec::pairing::Pairing;
-use ark_std::{test_rng, UniformRand};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, PrimeGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    test_rng, UniformRand, Zero,
+};
 
-use gs_ppe::{setup, CommitmentKeys, Matrix, ProofSystem, Variable};
+use gs_ppe::{
+    commit::CommitmentKey, setup, setup_deterministic, transcript::TranscriptProtocol, Com,
+    CommitmentKeys, Equation, Matrix, Proof, ProofSystem, Randomness, SparseMatrix, Variable,
+};
+use rand_chacha::ChaCha20Rng;
 
 type G1 = <F as Pairing>::G1;
 type G2 = <F as Pairing>::G2;
@@ -52,7 +60,7 @@ fn test_proof_m_zero() {
     let x_value = G1Affine::rand(rng);
     let x = Variable::new(rng, x_value);
     let b = G2Affine::rand(rng);
-    let gamma = Matrix::<Fr>::new(&[[]]); // dim = (1, 0)
+    let gamma = Matrix::<Fr>::empty_rows(1); // dim = (1, 0)
 
     let cks = CommitmentKeys::<F>::rand(rng);
 
@@ -76,7 +84,7 @@ fn test_proof_n_zero() {
     let y_value = G2Affine::rand(rng);
     let y = Variable::new(rng, y_value);
     let a = G1Affine::rand(rng);
-    let gamma = Matrix::<Fr>::zeros_column(1); // dim = (0, 1)
+    let gamma = Matrix::<Fr>::empty_cols(1); // dim = (0, 1)
 
     let cks = CommitmentKeys::<F>::rand(rng);
 
@@ -94,6 +102,61 @@ fn test_proof_n_zero() {
     assert!(equation.verify(&cks, &c, &d, &proof));
 }
 
+#[test]
+fn test_proof_m_n_zero() {
+    let rng = &mut test_rng();
+    let gamma = Matrix::<Fr>::empty(); // dim = (0, 0)
+    assert_eq!(gamma.dim(), (0, 0));
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    // Setup Proof System over the constant statement 1 = T
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = setup(rng, &cks, &[], &[], &gamma);
+    assert!(c.is_empty() && d.is_empty());
+    assert_eq!(equation.dim(), (0, 0));
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    let proof = Proof::new(rng, &cks, &equation, &[], &[]);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    // The constant statement with a non-identity target is not satisfiable.
+    let target = F::pairing(G1Affine::rand(rng), G2Affine::rand(rng));
+    let equation = Equation::<F>::new(vec![], vec![], gamma, target);
+    let proof = Proof::new(rng, &cks, &equation, &[], &[]);
+    assert!(!equation.verify(&cks, &c, &d, &proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_add_degenerate_gamma() {
+    let rng = &mut test_rng();
+    assert_eq!(Matrix::<Fr>::empty_rows(2).dim(), (2, 0));
+    assert_eq!(Matrix::<Fr>::empty_cols(2).dim(), (0, 2));
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let shapes = [(0, 0), (2, 0), (0, 3), (2, 3)];
+    for (m1, n1) in shapes {
+        for (m2, n2) in shapes {
+            let (equ1, x1, y1) = gs_ppe::test_utils::random_instance::<F, _>(rng, m1, n1);
+            let (equ2, x2, y2) = gs_ppe::test_utils::random_instance::<F, _>(rng, m2, n2);
+            let equation = equ1 + equ2;
+            assert_eq!(equation.dim(), (m1 + m2, n1 + n2));
+
+            let x = [x1, x2].concat();
+            let y = [y1, y2].concat();
+            let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+            let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+            let proof = Proof::new(rng, &cks, &equation, &x, &y);
+            assert!(equation.verify(&cks, &c, &d, &proof));
+        }
+    }
+}
+
 #[test]
 fn test_randomized_proof_m_x_n() {
     let rng = &mut test_rng();
@@ -177,3 +240,3829 @@ fn test_homomorphic_proofs() {
         &proof_system_sum.proof
     ));
 }
+
+#[test]
+fn test_extract_with_bound_keys() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let x = Variable::<G1>::new(rng, x_value);
+    let y = Variable::<G2>::new(rng, y_value);
+
+    assert_eq!(ek.extract_1(&cks.u.commit(&x)), x_value);
+    assert_eq!(ek.extract_2(&cks.v.commit(&y)), y_value);
+}
+
+#[test]
+fn test_verify_aggregate() {
+    let rng = &mut test_rng();
+    let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let (x, y) = (
+        Variable::<G1>::new(rng, x_value),
+        Variable::<G2>::new(rng, y_value),
+    );
+    let equation = setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma).equation;
+
+    // 100 instances of the same equation with differently randomized commitments and proofs.
+    let mut instances = (0..100)
+        .map(|_| {
+            let x = Variable::<G1>::new(rng, x_value);
+            let y = Variable::<G2>::new(rng, y_value);
+            let proof = Proof::new(rng, &cks, &equation, &[x], &[y]);
+            (vec![cks.u.commit(&x)], vec![cks.v.commit(&y)], proof)
+        })
+        .collect::<Vec<_>>();
+
+    assert!(equation.verify_aggregate(rng, &cks, &[]));
+    assert!(equation.verify_aggregate(rng, &cks, &instances));
+
+    // One instance with a proof from another instance causes rejection.
+    let mut bad_instances = instances.clone();
+    bad_instances[42].2 = instances[7].2.clone();
+    let (c, d, proof) = &bad_instances[42];
+    assert!(!equation.verify(&cks, c, d, proof));
+    assert!(!equation.verify_aggregate(rng, &cks, &bad_instances));
+
+    // One instance with a commitment from another instance causes rejection.
+    let mut instances = instances.split_off(90);
+    instances[9].0[0] = instances[0].0[0];
+    assert!(!equation.verify_aggregate(rng, &cks, &instances));
+
+    // One instance with incorrect dimensions causes rejection.
+    instances[9].0.pop();
+    assert!(!equation.verify_aggregate(rng, &cks, &instances));
+}
+
+#[test]
+fn test_matrix_swap_and_permute() {
+    let rng = &mut test_rng();
+    let original = Matrix::<Fr>::rand(rng, 3, 4);
+
+    let mut m = original.clone();
+    m.swap_rows(0, 2);
+    assert_eq!(m[(0, 1)], original[(2, 1)]);
+    assert_eq!(m[(2, 3)], original[(0, 3)]);
+    m.swap_rows(0, 2);
+    assert_eq!(m, original);
+
+    m.swap_cols(1, 3);
+    assert_eq!(m[(2, 1)], original[(2, 3)]);
+    assert_eq!(m[(0, 3)], original[(0, 1)]);
+    m.swap_cols(1, 3);
+    assert_eq!(m, original);
+
+    m.swap_rows(1, 1);
+    m.swap_cols(2, 2);
+    assert_eq!(m, original);
+
+    // Permute then inverse-permute returns the original matrix.
+    let row_perm = [2, 0, 1];
+    let row_perm_inv = [1, 2, 0];
+    m.permute_rows(&row_perm);
+    assert_eq!(m[(0, 0)], original[(2, 0)]);
+    m.permute_rows(&row_perm_inv);
+    assert_eq!(m, original);
+
+    let col_perm = [3, 1, 0, 2];
+    let col_perm_inv = [2, 1, 3, 0];
+    m.permute_cols(&col_perm);
+    assert_eq!(m[(1, 0)], original[(1, 3)]);
+    m.permute_cols(&col_perm_inv);
+    assert_eq!(m, original);
+}
+
+#[test]
+fn test_transcript_bytes_pinned() {
+    let g1 = G1::generator();
+    let c = Com::<G1>(g1.into_affine(), (g1 + g1).into_affine());
+    assert_eq!(
+        to_hex(&c.to_transcript_bytes()),
+        "67732d7070650103436f6d\
+         97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb\
+         a572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e"
+    );
+
+    let g2 = G2::generator();
+    let d = Com::<G2>(g2.into_affine(), G2Affine::zero());
+    assert_eq!(
+        to_hex(&d.to_transcript_bytes()),
+        "67732d7070650103436f6d\
+         93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e\
+         024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8\
+         c00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\
+         000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+    );
+}
+
+#[test]
+fn test_transcript_bytes() {
+    let rng = &mut test_rng();
+    let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x, y) = (
+        Variable::<G1>::new(rng, x_value),
+        Variable::<G2>::new(rng, y_value),
+    );
+    let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let proof_system = setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma);
+
+    // Structurally-equal values produce identical bytes.
+    let c = cks.u.commit(&x);
+    assert_eq!(
+        c.to_transcript_bytes(),
+        proof_system.c[0].to_transcript_bytes()
+    );
+    let sum = proof_system.clone() + proof_system.clone();
+    assert_eq!(
+        sum.to_transcript_bytes(),
+        (proof_system.clone() + proof_system.clone()).to_transcript_bytes()
+    );
+
+    // header (13 bytes) || dim (16 bytes) || 4 G2 points || dim (16 bytes) || 4 G1 points
+    let proof_bytes = proof_system.proof.to_transcript_bytes();
+    assert_eq!(proof_bytes.len(), 13 + 16 + 4 * 96 + 16 + 4 * 48);
+    assert!(proof_bytes.starts_with(b"gs-ppe\x01\x05Proof"));
+
+    // header || equation (a, b, gamma, target) || c || d || proof
+    let bytes = proof_system.to_transcript_bytes();
+    let equation_bytes = proof_system.equation.to_transcript_bytes();
+    assert!(bytes.starts_with(b"gs-ppe\x01\x0bProofSystem"));
+    assert!(equation_bytes.starts_with(b"gs-ppe\x01\x08Equation"));
+    assert_eq!(
+        &bytes[19..19 + equation_bytes.len() - 16],
+        &equation_bytes[16..]
+    );
+    assert!(bytes.ends_with(&proof_bytes[13..]));
+
+    // Randomization changes the encoding.
+    let randomized = proof_system.clone().randomize(rng, &cks);
+    assert_ne!(randomized.to_transcript_bytes(), bytes);
+    assert_eq!(randomized.equation.to_transcript_bytes(), equation_bytes);
+}
+
+#[test]
+fn test_transcript_protocol() {
+    let rng = &mut test_rng();
+    let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x, y) = (
+        Variable::<G1>::new(rng, x_value),
+        Variable::<G2>::new(rng, y_value),
+    );
+    let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let proof_system = setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma);
+
+    let challenge = |label: &'static [u8], proof_system: &ProofSystem<F>| {
+        let mut transcript = TestTranscript::default();
+        transcript.append_equation(b"equation", &proof_system.equation);
+        transcript.append_com(b"c", &proof_system.c[0]);
+        transcript.append_com(b"d", &proof_system.d[0]);
+        transcript.append_proof(label, &proof_system.proof);
+        transcript.challenge_scalar::<Fr>(b"challenge")
+    };
+
+    // Deterministic
+    assert_eq!(
+        challenge(b"proof", &proof_system),
+        challenge(b"proof", &proof_system)
+    );
+    // Domain separation between labels
+    assert_ne!(
+        challenge(b"proof", &proof_system),
+        challenge(b"proof'", &proof_system)
+    );
+    // Binding to the appended values
+    let randomized = proof_system.clone().randomize(rng, &cks);
+    assert_ne!(
+        challenge(b"proof", &proof_system),
+        challenge(b"proof", &randomized)
+    );
+
+    // Successive challenges differ
+    let mut transcript = TestTranscript::default();
+    transcript.append_proof_system(b"proof_system", &proof_system);
+    let c1 = transcript.challenge_scalar::<Fr>(b"challenge");
+    let c2 = transcript.challenge_scalar::<Fr>(b"challenge");
+    assert_ne!(c1, c2);
+}
+
+/// A toy transcript for testing, which derives the challenges from all the appended messages by
+/// seeding a standard rng.
+#[derive(Default)]
+struct TestTranscript(Vec<u8>);
+
+impl TranscriptProtocol for TestTranscript {
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        for bytes in [label, message] {
+            self.0
+                .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.append_message(label, &(dest.len() as u64).to_le_bytes());
+        let mut seed = [0u8; 32];
+        for (i, b) in self.0.iter().enumerate() {
+            seed[i % 32] = seed[i % 32].wrapping_mul(31).wrapping_add(*b) ^ seed[(i + 1) % 32];
+        }
+        StdRng::from_seed(seed).fill_bytes(dest);
+        self.append_message(b"challenge", dest);
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_add_key_randomness() {
+    let rng = &mut test_rng();
+    let n = 3;
+    let m = 2;
+    let ay = (0..n)
+        .map(|_| {
+            let value = G2Affine::rand(rng);
+            (G1Affine::rand(rng), Variable::<G2>::new(rng, value))
+        })
+        .collect::<Vec<_>>();
+    let xb = (0..m)
+        .map(|_| {
+            let value = G1Affine::rand(rng);
+            (Variable::<G1>::new(rng, value), G2Affine::rand(rng))
+        })
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = setup(rng, &cks, &ay, &xb, &gamma);
+
+    let mut randomized = proof.clone();
+    randomized.add_key_randomness(rng, &cks);
+    assert_ne!(randomized, proof);
+    assert!(equation.verify(&cks, &c, &d, &randomized));
+
+    randomized.add_key_randomness(rng, &cks);
+    assert!(equation.verify(&cks, &c, &d, &randomized));
+}
+
+#[test]
+fn test_matrix_hadamard() {
+    let rng = &mut test_rng();
+    let m = Matrix::<Fr>::rand(rng, 2, 3);
+    let ones = Matrix::<Fr>::from_elem(2, 3, Fr::from(1u64));
+    let zeros = Matrix::<Fr>::from_elem(2, 3, Fr::from(0u64));
+
+    assert_eq!(m.hadamard::<Fr, Fr>(&ones), m);
+    assert_eq!(m.hadamard::<Fr, Fr>(&zeros), zeros);
+    assert_eq!(m.hadamard::<Fr, Fr>(&m), m.clone() * m.clone());
+
+    let mut m_inplace = m.clone();
+    m_inplace.hadamard_inplace(&ones);
+    assert_eq!(m_inplace, m);
+    m_inplace.hadamard_inplace(&zeros);
+    assert_eq!(m_inplace, zeros);
+
+    // Scalar matrix multiplied with group elements.
+    let g = Matrix::<G1>::from_elem(2, 3, G1::generator());
+    let product = g.hadamard::<Fr, G1>(&m);
+    assert_eq!(product[(1, 2)], G1::generator() * m[(1, 2)]);
+}
+
+#[test]
+fn test_variable_from_compressed_bytes() {
+    let rng = &mut test_rng();
+
+    let x_value = G1Affine::rand(rng);
+    let x = Variable::<G1>::new(rng, x_value);
+    let mut bytes = Vec::new();
+    x.value.serialize_compressed(&mut bytes).unwrap();
+    let x_prime = Variable::<G1>::from_compressed_bytes(rng, &bytes).unwrap();
+    assert_eq!(x_prime.value, x.value);
+
+    let y_value = G2Affine::rand(rng);
+    let y = Variable::<G2>::new(rng, y_value);
+    let mut bytes = Vec::new();
+    y.value.serialize_compressed(&mut bytes).unwrap();
+    let y_prime = Variable::<G2>::from_compressed_bytes(rng, &bytes).unwrap();
+    assert_eq!(y_prime.value, y.value);
+
+    // Invalid bytes
+    assert!(Variable::<G2>::from_compressed_bytes(rng, &bytes[1..]).is_err());
+    assert!(Variable::<G1>::from_compressed_bytes(rng, &[0xff; 48]).is_err());
+}
+
+#[test]
+fn test_equation_from_matrices() {
+    let rng = &mut test_rng();
+    let (m, k, n) = (1, 2, 2);
+
+    let a_matrix = Matrix::<G1Affine>::rand(rng, k, n);
+    let b_matrix = Matrix::<G2Affine>::rand(rng, m, k);
+    let x_values = Matrix::<G1Affine>::rand(rng, m, k);
+    let y_values = Matrix::<G2Affine>::rand(rng, k, n);
+    let x_matrix = Matrix::from_vecs(
+        x_values
+            .to_vecs()
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| Variable::new(rng, v)).collect())
+            .collect(),
+    );
+    let y_matrix = Matrix::from_vecs(
+        y_values
+            .to_vecs()
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| Variable::new(rng, v)).collect())
+            .collect(),
+    );
+
+    let (equation, x, y) =
+        Equation::<F>::from_matrices(a_matrix.clone(), b_matrix.clone(), &x_matrix, &y_matrix);
+
+    // Manually construct the equation element-by-element.
+    let a = vec![
+        a_matrix[(0, 0)],
+        a_matrix[(0, 1)],
+        a_matrix[(1, 0)],
+        a_matrix[(1, 1)],
+    ];
+    let b = vec![b_matrix[(0, 0)], b_matrix[(0, 1)]];
+    let (one, zero) = (Fr::from(1u64), Fr::from(0u64));
+    let gamma = Matrix::new(&[[one, one, zero, zero], [zero, zero, one, one]]);
+    let mut target = F::pairing(x_values[(0, 0)], b_matrix[(0, 0)])
+        + F::pairing(x_values[(0, 1)], b_matrix[(0, 1)]);
+    for j in 0..k {
+        for l in 0..n {
+            target += F::pairing(a_matrix[(j, l)], y_values[(j, l)]);
+            target += F::pairing(x_values[(0, j)], y_values[(j, l)]);
+        }
+    }
+    assert_eq!(equation, Equation::<F>::new(a, b, gamma, target));
+    assert_eq!(
+        x.iter().map(|x_i| x_i.value).collect::<Vec<_>>(),
+        vec![x_values[(0, 0)], x_values[(0, 1)]]
+    );
+    assert_eq!(y[2].value, y_values[(1, 0)]);
+
+    // The flattened variables satisfy the equation.
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+}
+
+#[test]
+fn test_derive_subkey() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let subkey_1 = cks.derive_subkey(1);
+    let subkey_2 = cks.derive_subkey(2);
+    assert_ne!(subkey_1, subkey_2);
+    assert_ne!(&subkey_1, cks);
+    assert_eq!(subkey_1, cks.derive_subkey(1));
+    assert_eq!(subkey_1.u.0, cks.u.0);
+    assert_eq!(subkey_1.v.0, cks.v.0);
+
+    assert!(cks.verify_derived(&subkey_1, 1));
+    assert!(cks.verify_derived(&subkey_2, 2));
+    assert!(!cks.verify_derived(&subkey_1, 2));
+    assert!(!cks.verify_derived(cks, 1));
+
+    // Extraction works under derived keys.
+    let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x, y) = (
+        Variable::<G1>::new(rng, x_value),
+        Variable::<G2>::new(rng, y_value),
+    );
+    assert_eq!(ek.extract_1(&subkey_1.u.commit(&x)), x_value);
+    assert_eq!(ek.extract_2(&subkey_2.v.commit(&y)), y_value);
+
+    // Proofs under derived keys verify.
+    let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = setup(rng, &subkey_1, &[(a, y)], &[(x, b)], &gamma);
+    assert!(equation.verify(&subkey_1, &c, &d, &proof));
+    assert!(!equation.verify(cks, &c, &d, &proof));
+}
+
+#[test]
+fn test_derive_subkey_depends_on_master_key() {
+    let rng = &mut test_rng();
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (a1, a2) = (Fr::rand(rng), Fr::rand(rng));
+    // The master keys share u1 and v1, and differ only in t1 and t2.
+    let cks = CommitmentKeys::<F>::from_trapdoor(g1, g2, a1, a2, Fr::rand(rng), Fr::rand(rng));
+    let other_cks =
+        CommitmentKeys::<F>::from_trapdoor(g1, g2, a1, a2, Fr::rand(rng), Fr::rand(rng));
+
+    // u21' - u21 = u11^δ1 is the same only if δ1 is the same.
+    let delta_u = |cks: &CommitmentKeys<F>| cks.derive_subkey(1).u.1 .0 - cks.u.1 .0;
+    let delta_v = |cks: &CommitmentKeys<F>| cks.derive_subkey(1).v.1 .0 - cks.v.1 .0;
+    assert_ne!(delta_u(&cks), delta_u(&other_cks));
+    assert_ne!(delta_v(&cks), delta_v(&other_cks));
+}
+
+#[test]
+fn test_proof_with_internal_randomness() {
+    let rng = &mut ChaCha20Rng::from_seed([7u8; 32]);
+    let (g1, g2) = (G1::generator(), G2::generator());
+    let cks = CommitmentKeys::<F>::setup(rng, g1.into_affine(), g2.into_affine());
+
+    let (a, b) = (
+        (g1 * Fr::from(7u64)).into_affine(),
+        (g2 * Fr::from(9u64)).into_affine(),
+    );
+    let (x_value, y_value) = (
+        (g1 * Fr::from(3u64)).into_affine(),
+        (g2 * Fr::from(5u64)).into_affine(),
+    );
+    let x = Variable::<G1>::with_randomness(x_value, Randomness(Fr::from(11u64), Fr::from(13u64)));
+    let y = Variable::<G2>::with_randomness(y_value, Randomness(Fr::from(17u64), Fr::from(19u64)));
+    let gamma = Matrix::new(&[[Fr::from(2u64)]]);
+    let target = F::pairing(a, y_value)
+        + F::pairing(x_value, b)
+        + F::pairing(x_value, y_value) * Fr::from(2u64);
+    let equation = Equation::<F>::new(vec![a], vec![b], gamma, target);
+    let z = Matrix::new(&[
+        [Fr::from(1u64), Fr::from(2u64)],
+        [Fr::from(3u64), Fr::from(4u64)],
+    ]);
+
+    let proof = Proof::new_with_internal_randomness(&cks, &equation, &[x], &[y], z.clone());
+    assert_eq!(
+        proof,
+        Proof::new_with_internal_randomness(&cks, &equation, &[x], &[y], z.clone())
+    );
+    let (c, d) = (cks.u.commit(&x), cks.v.commit(&y));
+    assert!(equation.verify(&cks, &[c], &[d], &proof));
+
+    // Known answer: φ11 and θ22 in the transcript encoding.
+    let bytes = proof.to_transcript_bytes();
+    assert_eq!(
+        to_hex(&bytes[29..29 + 96]),
+        "8a0b36050ed4a50bb842499b88a6e2db5f0116296ece4653bfb40ecb0090692e7870df6dd1c3292ba8caab182663e1dc\
+         106d24bd2bb9a5451c12188c4ae78baecabbcc15cba5ef688dbb21d38899be9954d7ae6605fb8c3d168d92d6f4ad844b"
+    );
+    assert_eq!(
+        to_hex(&bytes[bytes.len() - 48..]),
+        "b90ada002d6192dba6f0e16f00bb357e4e8e46463fbebfe4fa6bef1974edb596887c0134e7728cbb735e969b55aec44e"
+    );
+
+    // Randomization with supplied internal randomness is deterministic.
+    let (c_r, d_s) = (
+        (c, Randomness(Fr::from(23u64), Fr::from(29u64))),
+        (d, Randomness(Fr::from(31u64), Fr::from(37u64))),
+    );
+    let mut c_prime = c;
+    c_prime.0 = (c.0 + cks.u.0 .0 * Fr::from(23u64) + cks.u.1 .0 * Fr::from(29u64)).into_affine();
+    c_prime.1 = (c.1 + cks.u.0 .1 * Fr::from(23u64) + cks.u.1 .1 * Fr::from(29u64)).into_affine();
+    let mut d_prime = d;
+    d_prime.0 = (d.0 + cks.v.0 .0 * Fr::from(31u64) + cks.v.1 .0 * Fr::from(37u64)).into_affine();
+    d_prime.1 = (d.1 + cks.v.0 .1 * Fr::from(31u64) + cks.v.1 .1 * Fr::from(37u64)).into_affine();
+
+    let mut randomized = proof.clone();
+    randomized.randomize_with_internal_randomness(&cks, &equation, &[c_r], &[d_s], z.clone());
+    let mut randomized_again = proof.clone();
+    randomized_again.randomize_with_internal_randomness(&cks, &equation, &[c_r], &[d_s], z);
+    assert_eq!(randomized, randomized_again);
+    assert_ne!(randomized, proof);
+    assert!(equation.verify(&cks, &[c_prime], &[d_prime], &randomized));
+}
+
+#[test]
+fn test_merge_terms() {
+    let rng = &mut test_rng();
+    let (a1, a2) = (G1Affine::rand(rng), G1Affine::rand(rng));
+    let b1 = G2Affine::rand(rng);
+    let (g1, g2) = (Fr::rand(rng), Fr::rand(rng));
+
+    // a = [a1, a1, a2], b = [b1, b1], and gamma has equal columns 0, 1 and equal rows 0, 1.
+    let ay = [a1, a1, a2]
+        .into_iter()
+        .map(|a_j| {
+            let value = G2Affine::rand(rng);
+            (a_j, Variable::<G2>::new(rng, value))
+        })
+        .collect::<Vec<_>>();
+    let xb = (0..2)
+        .map(|_| {
+            let value = G1Affine::rand(rng);
+            (Variable::<G1>::new(rng, value), b1)
+        })
+        .collect::<Vec<_>>();
+    let gamma = Matrix::new(&[[g1, g1, g2], [g1, g1, g2]]);
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let proof_system = setup(rng, &cks, &ay, &xb, &gamma);
+    let equation = proof_system.equation;
+    let x = xb.iter().map(|(x_i, _)| *x_i).collect::<Vec<_>>();
+    let y = ay.iter().map(|(_, y_j)| *y_j).collect::<Vec<_>>();
+
+    // Merge the terms with a1.
+    let merged_ay = equation.merge_ay_terms();
+    let merged_y = equation.merge_y_variables(&y);
+    assert_eq!(merged_y.len(), 2);
+    // The merged variables satisfy the merged equation with the same target.
+    let merged_setup = setup(
+        rng,
+        &cks,
+        &[(a1, merged_y[0]), (a2, merged_y[1])],
+        &xb,
+        &Matrix::new(&[[g1, g2], [g1, g2]]),
+    );
+    assert_eq!(merged_ay, merged_setup.equation);
+    // The commitments of the merged variables are the sums of the original commitments.
+    assert_eq!(
+        cks.v.commit(&merged_y[0]),
+        proof_system.d[0] + proof_system.d[1]
+    );
+
+    // Merge the terms with b1.
+    let merged = merged_ay.merge_xb_terms();
+    let merged_x = merged_ay.merge_x_variables(&x);
+    assert_eq!(merged_x.len(), 1);
+
+    let c = merged_x
+        .iter()
+        .map(|x_i| cks.u.commit(x_i))
+        .collect::<Vec<_>>();
+    let d = merged_y
+        .iter()
+        .map(|y_j| cks.v.commit(y_j))
+        .collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &merged, &merged_x, &merged_y);
+    assert!(merged.verify(&cks, &c, &d, &proof));
+
+    // Nothing to merge
+    assert_eq!(merged.merge_ay_terms(), merged);
+    assert_eq!(merged.merge_xb_terms(), merged);
+}
+
+#[test]
+fn test_setup_deterministic() {
+    let rng = &mut ChaCha20Rng::from_seed([1u8; 32]);
+    let n = 2;
+    let m = 2;
+    let ay = (0..n)
+        .map(|_| (G1Affine::rand(rng), G2Affine::rand(rng)))
+        .collect::<Vec<_>>();
+    let xb = (0..m)
+        .map(|_| (G1Affine::rand(rng), G2Affine::rand(rng)))
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let proof_system = setup_deterministic([2u8; 32], &cks, &ay, &xb, &gamma);
+    assert_eq!(
+        proof_system,
+        setup_deterministic([2u8; 32], &cks, &ay, &xb, &gamma)
+    );
+    assert!(proof_system.equation.verify(
+        &cks,
+        &proof_system.c,
+        &proof_system.d,
+        &proof_system.proof
+    ));
+
+    // Pinned commitment to the first variable x.
+    assert_eq!(
+        to_hex(&proof_system.c[0].to_transcript_bytes()[11..]),
+        "870ee48b2f7c364bced01359739608817ad9b566f42112664854e8febce1cee2fd8fcbdc0626059db09580bba07fc34b\
+         88f3aa620a5eba219d88b28d61415ab5d2180a116c22f0481b3985f1b12eeb50a901b7865478f54156b0b68e11e272f0"
+    );
+
+    // Different seeds give different commitments and proofs.
+    let other = setup_deterministic([3u8; 32], &cks, &ay, &xb, &gamma);
+    assert_eq!(other.equation, proof_system.equation);
+    assert_ne!(other.c, proof_system.c);
+    assert_ne!(other.d, proof_system.d);
+    assert_ne!(other.proof, proof_system.proof);
+}
+
+#[test]
+fn test_commitment_key_normalize() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    // Freshly generated keys are normalized.
+    assert_eq!(cks.u.normalize(), cks.u);
+    assert_eq!(cks.v.normalize(), cks.v);
+    assert_eq!(cks.u.normalize().normalize(), cks.u.normalize());
+
+    // Projective manipulations which cancel out give back the same affine representation.
+    let ((u11, u12), (u21, u22)) = cks.u.to_projective();
+    let r = G1::rand(rng);
+    let u = CommitmentKey::<G1>::from_projective(((u11 + r - r, u12 + u12 - u12), (u21, u22)));
+    assert_eq!(u, cks.u);
+
+    let ((v11, v12), (v21, v22)) = cks.v.to_projective();
+    let v = CommitmentKey::<G2>::from_projective(((v11, v12), (v21 * Fr::from(2u64) - v21, v22)));
+    assert_eq!(v, cks.v);
+}
+
+#[test]
+fn test_sparse_matrix() {
+    let rng = &mut test_rng();
+    let (m, n) = (3, 4);
+    let ay = (0..n)
+        .map(|_| {
+            let value = G2Affine::rand(rng);
+            (G1Affine::rand(rng), Variable::<G2>::new(rng, value))
+        })
+        .collect::<Vec<_>>();
+    let xb = (0..m)
+        .map(|_| {
+            let value = G1Affine::rand(rng);
+            (Variable::<G1>::new(rng, value), G2Affine::rand(rng))
+        })
+        .collect::<Vec<_>>();
+    // The row 1 and the column 2 are all zeros.
+    let mut sparse = SparseMatrix::<Fr>::new(m, n);
+    sparse.set((0, 0), Fr::rand(rng));
+    sparse.set((0, 3), Fr::rand(rng));
+    sparse.set((2, 1), Fr::rand(rng));
+    sparse.set((2, 3), Fr::zero());
+    assert_eq!(sparse.dim(), (m, n));
+    assert_eq!(sparse.nnz(), 3);
+    assert!(sparse.get((1, 1)).is_zero());
+
+    let gamma = sparse.to_dense();
+    assert_eq!(gamma.dim(), (m, n));
+    assert_eq!(gamma[(2, 1)], sparse.get((2, 1)));
+    assert!(gamma[(2, 3)].is_zero());
+    assert_eq!(gamma.to_sparse(), sparse);
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem { equation, c, d, .. } = setup(rng, &cks, &ay, &xb, &gamma);
+    let x = xb.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+    let y = ay.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+    let proof = Proof::new_sparse(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    // Same as the dense proof given the same internal randomness.
+    let z = Matrix::<Fr>::rand(&mut StdRng::seed_from_u64(1), 2, 2);
+    let sparse_proof = Proof::new_sparse(&mut StdRng::seed_from_u64(1), &cks, &equation, &x, &y);
+    let dense_proof = Proof::new_with_internal_randomness(&cks, &equation, &x, &y, z);
+    assert_eq!(sparse_proof, dense_proof);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_random_linear_instance() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (equation, x, y) = gs_ppe::test_utils::random_linear_instance::<F, _>(rng, 2, 3);
+
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_sum() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let proof_systems = [(1, 2), (2, 1), (1, 1)]
+        .into_iter()
+        .map(|(m, n)| gs_ppe::test_utils::random_proof_system(rng, &cks, m, n))
+        .collect::<Vec<_>>();
+    let [ps1, ps2, ps3] = proof_systems.clone().try_into().unwrap();
+
+    let equations = proof_systems
+        .iter()
+        .map(|ps| ps.equation.clone())
+        .collect::<Vec<_>>();
+    let [eq1, eq2, eq3] = equations.clone().try_into().unwrap();
+    assert_eq!(Equation::sum(equations), (eq1 + eq2) + eq3);
+
+    let sum = ProofSystem::sum(proof_systems);
+    assert_eq!(sum, (ps1 + ps2) + ps3);
+    assert!(sum.equation.verify(&cks, &sum.c, &sum.d, &sum.proof));
+
+    let empty = ProofSystem::<F>::sum([]);
+    assert_eq!(empty.equation, Equation::sum([]));
+    assert!(empty
+        .equation
+        .verify(&cks, &empty.c, &empty.d, &empty.proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_sub() {
+    let rng = &mut test_rng();
+    let (eq1, x1, y1) = gs_ppe::test_utils::random_instance::<F, _>(rng, 2, 1);
+    let (eq2, x2, y2) = gs_ppe::test_utils::random_instance::<F, _>(rng, 1, 2);
+    let values = |x: &[&[Variable<G1>]], y: &[&[Variable<G2>]]| {
+        (
+            x.concat().iter().map(|x_i| x_i.value).collect::<Vec<_>>(),
+            y.concat().iter().map(|y_j| y_j.value).collect::<Vec<_>>(),
+        )
+    };
+
+    assert_eq!(-(-eq1.clone()), eq1);
+    let (x, y) = values(&[&x1], &[&y1]);
+    assert!((-eq1.clone()).verify_target_with_values(&x, &y));
+
+    // eq2 - eq2 has the identity as the target.
+    let zero = eq2.clone() - eq2.clone();
+    let (x, y) = values(&[&x2, &x2], &[&y2, &y2]);
+    assert!(zero.verify_target_with_values(&x, &y));
+    assert!(zero.verify_target_with_values(&[G1Affine::zero(); 2], &[G2Affine::zero(); 4]));
+
+    // (eq1 + eq2) - eq2 is eq1 with the zero-padded variables of eq2 - eq2.
+    let difference = (eq1.clone() + eq2.clone()) - eq2.clone();
+    assert_eq!(difference, eq1.clone() + zero);
+    let (x, y) = values(&[&x1, &x2, &x2], &[&y1, &y2, &y2]);
+    assert!(difference.verify_target_with_values(&x, &y));
+
+    assert_eq!(eq1.clone() / eq2.clone(), eq1.clone() - eq2.clone());
+    let (x, y) = values(&[&x1, &x2], &[&y1, &y2]);
+    assert!((eq1 - eq2).verify_target_with_values(&x, &y));
+}
+
+#[test]
+fn test_sps_verification_equations() {
+    use gs_ppe::statements::{sps_verification_equations, MessageSlot, SpsSecretKey};
+
+    let rng = &mut test_rng();
+    let sk = SpsSecretKey::<F>::rand(rng, 3);
+    let pk = sk.public_key();
+    let messages = (0..3).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let sig = sk.sign(rng, &messages);
+    assert!(pk.verify(&messages, &sig));
+
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let slots = [
+        MessageSlot::Committed,
+        MessageSlot::Public(messages[1]),
+        MessageSlot::Committed,
+    ];
+    let equations = sps_verification_equations(&pk, &slots);
+    assert_eq!(equations.len(), 2);
+
+    let r = Variable::<G1>::new(rng, sig.r);
+    let s = Variable::<G1>::new(rng, sig.s);
+    let t = Variable::<G2>::new(rng, sig.t);
+    let m0 = Variable::<G1>::new(rng, messages[0]);
+    let m2 = Variable::<G1>::new(rng, messages[2]);
+
+    let x = [r, s, m0, m2];
+    let c = x.map(|x_i| cks.u.commit(&x_i));
+    let proof = Proof::new(rng, &cks, &equations[0], &x, &[]);
+    assert!(equations[0].verify(&cks, &c, &[], &proof));
+
+    let proof = Proof::new(rng, &cks, &equations[1], &[r], &[t]);
+    assert!(equations[1].verify(&cks, &[c[0]], &[cks.v.commit(&t)], &proof));
+
+    // The signature does not verify on another public message element.
+    let slots = [
+        MessageSlot::Committed,
+        MessageSlot::Public(G1Affine::rand(rng)),
+        MessageSlot::Committed,
+    ];
+    let equations = sps_verification_equations(&pk, &slots);
+    let proof = Proof::new(rng, &cks, &equations[0], &x, &[]);
+    assert!(!equations[0].verify(&cks, &c, &[], &proof));
+}
+
+#[test]
+fn test_variable_from_scalar() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let s = Fr::rand(rng);
+    let x = Variable::<G1>::from_scalar(rng, s);
+    let y = Variable::<G2>::from_scalar(rng, s);
+    assert_eq!(ek.extract_1(&cks.u.commit(&x)), G1::generator() * s);
+    assert_eq!(ek.extract_2(&cks.v.commit(&y)), G2::generator() * s);
+
+    let g1 = Variable::<G1>::from_generator(rng);
+    assert_eq!(g1.value, G1Affine::generator());
+    assert_eq!(ek.extract_1(&cks.u.commit(&g1)), G1Affine::generator());
+
+    // x = G^s and y = H^s satisfy e(x, H) e(G^-1, y) = 1.
+    let (g, h) = (G1Affine::generator(), G2Affine::generator());
+    let gamma = Matrix::<Fr>::from_elem(1, 1, Fr::zero());
+    let proof_system = setup(rng, cks, &[(-g, y)], &[(x, h)], &gamma);
+    let equation = Equation::<F>::new(vec![-g], vec![h], gamma, Zero::zero());
+    assert_eq!(proof_system.equation, equation);
+    assert!(equation.verify(cks, &proof_system.c, &proof_system.d, &proof_system.proof));
+}
+
+#[test]
+fn test_matrix_as_2x2_array() {
+    let rng = &mut test_rng();
+    let m = Matrix::<Fr>::rand(rng, 2, 2);
+    let array = m.as_2x2_array().unwrap();
+    for i in 0..2 {
+        for j in 0..2 {
+            assert_eq!(array[i][j], m[(i, j)]);
+        }
+    }
+
+    // Not contiguous in row-major order after transposing.
+    let transposed = Matrix::<Fr>::from(m.clone().take().reversed_axes());
+    assert_eq!(transposed[(0, 1)], m[(1, 0)]);
+    assert!(transposed.as_2x2_array().is_none());
+
+    assert!(Matrix::<Fr>::rand(rng, 2, 3).as_2x2_array().is_none());
+    assert!(Matrix::<Fr>::rand(rng, 1, 1).as_2x2_array().is_none());
+}
+
+#[test]
+fn test_verifiable_encryption_escrow() {
+    use gs_ppe::verifiable_encryption::encrypt_and_prove;
+
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    // The user encrypts y = H^s and proves e(G^-1, y) = e(X, H)^-1 for the public X = G^s.
+    let s = Fr::rand(rng);
+    let (g, h) = (G1Affine::generator(), G2Affine::generator());
+    let y = Variable::<G2>::from_scalar(rng, s);
+    let gamma = Matrix::<Fr>::from_elem(0, 1, Fr::zero());
+    let mut encryption = encrypt_and_prove(rng, cks, &[(-g, y)], &[], &gamma);
+    assert!(encryption.verify(cks));
+    assert_eq!(
+        encryption.equation,
+        Equation::new(vec![-g], vec![], gamma, -F::pairing(g * s, h))
+    );
+
+    // The authority decrypts exactly the value in the statement.
+    assert_eq!(ek.decrypt_2(&encryption.cy[0]), (h * s).into_affine());
+
+    // The ciphertexts must be in the order of the variables.
+    encryption.cy[0].index = 1;
+    assert!(!encryption.verify(cks));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_flat_bytes() {
+    use ark_serialize::Compress;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 1);
+
+    let (phi_bytes, theta_bytes) = (proof.phi_to_flat_bytes(), proof.theta_to_flat_bytes());
+    assert_eq!(phi_bytes.len(), 4 * 96);
+    assert_eq!(theta_bytes.len(), 4 * 48);
+    // Same as the concatenated compressed points, in row-major order.
+    assert_eq!(&phi_bytes[..96], &proof.to_transcript_bytes()[29..29 + 96]);
+
+    let proof_prime =
+        Proof::<F>::from_phi_theta_flat(&phi_bytes, &theta_bytes, Compress::Yes).unwrap();
+    assert_eq!(proof_prime, proof);
+    assert!(equation.verify(&cks, &c, &d, &proof_prime));
+
+    // Uncompressed points.
+    let uncompressed = |bytes: &[u8], size: usize| {
+        bytes
+            .chunks(size)
+            .flat_map(|chunk| {
+                let mut out = Vec::new();
+                if size == 96 {
+                    G2Affine::deserialize_compressed(chunk)
+                        .unwrap()
+                        .serialize_uncompressed(&mut out)
+                        .unwrap();
+                } else {
+                    G1Affine::deserialize_compressed(chunk)
+                        .unwrap()
+                        .serialize_uncompressed(&mut out)
+                        .unwrap();
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+    };
+    let proof_prime = Proof::<F>::from_phi_theta_flat(
+        &uncompressed(&phi_bytes, 96),
+        &uncompressed(&theta_bytes, 48),
+        Compress::No,
+    )
+    .unwrap();
+    assert_eq!(proof_prime, proof);
+
+    // Wrong lengths.
+    assert!(
+        Proof::<F>::from_phi_theta_flat(&phi_bytes[..3 * 96], &theta_bytes, Compress::Yes).is_err()
+    );
+    let mut longer = theta_bytes.clone();
+    longer.push(0);
+    assert!(Proof::<F>::from_phi_theta_flat(&phi_bytes, &longer, Compress::Yes).is_err());
+    assert!(Proof::<F>::from_phi_theta_flat(&theta_bytes, &phi_bytes, Compress::Yes).is_err());
+}
+
+#[test]
+fn test_commitment_keys_into_parts() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let (u, v) = cks.into_parts();
+    assert_eq!((u, v), (cks.u, cks.v));
+    assert_eq!(CommitmentKeys::from_parts(u, v), cks);
+
+    let ((u11, u12), (u21, u22)) = u.into_generators();
+    assert_eq!(CommitmentKey::<G1>((u11, u12), (u21, u22)), cks.u);
+    assert_eq!(v.into_generators(), (cks.v.0, cks.v.1));
+}
+
+#[test]
+fn test_dh_tuple() {
+    use gs_ppe::statements::{dh_tuple, prove_dh_tuple};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let s = Fr::rand(rng);
+    let x = Variable::<G1>::new(rng, (g1 * s).into_affine());
+    let y = Variable::<G2>::new(rng, (g2 * s).into_affine());
+
+    let proof_system = prove_dh_tuple(rng, &cks, g1, g2, x, y);
+    assert_eq!(proof_system.equation, dh_tuple(g1, g2));
+    assert!(proof_system.equation.verify(
+        &cks,
+        &proof_system.c,
+        &proof_system.d,
+        &proof_system.proof
+    ));
+
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = proof_system.clone().randomize(rng, &cks);
+    assert_ne!(c, proof_system.c);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    // Not a Diffie-Hellman tuple.
+    let y_invalid = Variable::<G2>::new(rng, (g2 * (s + Fr::from(1u64))).into_affine());
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = prove_dh_tuple(rng, &cks, g1, g2, x, y_invalid);
+    assert!(!equation.verify(&cks, &c, &d, &proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_verify_target_with_values() {
+    let rng = &mut test_rng();
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, 2, 3);
+    let mut x = x.iter().map(|x_i| x_i.value).collect::<Vec<_>>();
+    let y = y.iter().map(|y_j| y_j.value).collect::<Vec<_>>();
+    assert!(equation.verify_target_with_values(&x, &y));
+    assert!(!equation.verify_target_with_values(&x[..1], &y));
+    assert!(!equation.verify_target_with_values(&y.iter().map(|_| x[0]).collect::<Vec<_>>(), &y));
+
+    x[1] = (x[1] + G1Affine::generator()).into_affine();
+    assert!(!equation.verify_target_with_values(&x, &y));
+}
+
+#[test]
+fn test_equation_from_witnesses() {
+    let rng = &mut test_rng();
+    let (m, n) = (2, 3);
+    let a = (0..n).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let b = (0..m).map(|_| G2Affine::rand(rng)).collect::<Vec<_>>();
+    let x = (0..m).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let y = (0..n).map(|_| G2Affine::rand(rng)).collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+
+    let target = Equation::<F>::compute_target_from(&a, &b, &gamma, &x, &y);
+    let equation = Equation::<F>::from_witnesses(a.clone(), b.clone(), gamma.clone(), &x, &y);
+    assert_eq!(equation, Equation::new(a, b, gamma, target));
+    assert!(equation.verify_target_with_values(&x, &y));
+    assert!(!equation.verify_target_with_values(&x, &[y[1], y[0], y[2]]));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_verify_checked() {
+    use ark_bls12_381::{Fq, Fq2};
+    use gs_ppe::equation::InvalidPoint;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 2);
+    assert_eq!(equation.verify_checked(&cks, &c, &d, &proof), Ok(true));
+    let mut other = c.clone();
+    other.swap(0, 1);
+    assert_eq!(equation.verify_checked(&cks, &other, &d, &proof), Ok(false));
+
+    // A point which is not on the curve.
+    let off_curve = G1Affine::new_unchecked(Fq::rand(rng), Fq::rand(rng));
+    assert!(!off_curve.is_on_curve());
+    let mut invalid = c.clone();
+    invalid[1].0 = off_curve;
+    assert_eq!(
+        equation.verify_checked(&cks, &invalid, &d, &proof),
+        Err(InvalidPoint::C(1))
+    );
+
+    // A point on the curve but not in the prime-order subgroup.
+    let low_order = loop {
+        if let Some(p) = G2Affine::get_point_from_x_unchecked(Fq2::rand(rng), true) {
+            break p;
+        }
+    };
+    assert!(low_order.is_on_curve() && !low_order.is_in_correct_subgroup_assuming_on_curve());
+    let mut invalid = d.clone();
+    invalid[0].1 = low_order;
+    assert_eq!(
+        equation.verify_checked(&cks, &c, &invalid, &proof),
+        Err(InvalidPoint::D(0))
+    );
+}
+
+#[test]
+fn test_matrix_into_flat_vec() {
+    let rng = &mut test_rng();
+    let m = Matrix::<Fr>::rand(rng, 3, 2);
+
+    let flat = m.clone().into_flat_vec();
+    assert_eq!(flat.len(), 6);
+    let array = ndarray::Array2::from_shape_vec((3, 2), flat).unwrap();
+    assert_eq!(Matrix::<Fr>::from(array), m);
+    assert_eq!(m.clone().to_owned_rows(), m.to_vecs());
+
+    // Not in row-major order.
+    let transposed = Matrix::<Fr>::from(m.clone().take().reversed_axes());
+    let flat = transposed.clone().into_flat_vec();
+    assert_eq!(flat[1], m[(1, 0)]);
+    let array = ndarray::Array2::from_shape_vec((2, 3), flat).unwrap();
+    assert_eq!(Matrix::<Fr>::from(array), transposed);
+    assert_eq!(transposed.clone().to_owned_rows(), transposed.to_vecs());
+
+    assert_eq!(
+        Matrix::<Fr>::zeros_column(2).to_owned_rows(),
+        Vec::<Vec<Fr>>::new()
+    );
+    assert_eq!(
+        Matrix::<Fr>::from_elem(2, 0, Fr::zero()).to_owned_rows(),
+        vec![vec![], vec![]]
+    );
+}
+
+#[test]
+fn test_com_homomorphic_addition() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let (r, r_prime) = (Randomness::<G1>::rand(rng), Randomness::<G1>::rand(rng));
+    let (x_value, x_prime_value) = (G1Affine::rand(rng), G1Affine::rand(rng));
+    let x = Variable::with_randomness(x_value, r);
+    let x_prime = Variable::with_randomness(x_prime_value, r_prime);
+    let (c, c_prime) = (cks.u.commit(&x), cks.u.commit(&x_prime));
+    assert!(c.verify_opening(&cks.u, &x));
+    assert!(!c.verify_opening(&cks.u, &x_prime));
+
+    let sum = c + c_prime;
+    let x_sum = (x_value + x_prime_value).into_affine();
+    assert_eq!(ek.extract_1(&sum), x_sum);
+    assert!(sum.verify_opening(&cks.u, &Variable::with_randomness(x_sum, r + r_prime)));
+    assert!(!sum.verify_opening(&cks.u, &Variable::with_randomness(x_sum, r)));
+
+    let (y_value, y_prime_value) = (G2Affine::rand(rng), G2Affine::rand(rng));
+    let (y, y_prime) = (
+        Variable::<G2>::new(rng, y_value),
+        Variable::<G2>::new(rng, y_prime_value),
+    );
+    let sum = cks.v.commit(&y) + cks.v.commit(&y_prime);
+    assert_eq!(ek.extract_2(&sum), (y_value + y_prime_value).into_affine());
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_com_scalar_mul() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    // Com(X, r) * rho = Com(X * rho, r * rho)
+    let rho = Fr::rand(rng);
+    let r = Randomness::<G1>::rand(rng);
+    let x_value = G1Affine::rand(rng);
+    let c = cks.u.commit(&Variable::with_randomness(x_value, r));
+    let scaled = Variable::with_randomness((x_value * rho).into_affine(), r * rho);
+    assert!((c * rho).verify_opening(&cks.u, &scaled));
+    assert_eq!(
+        c * Fr::zero(),
+        cks.u.commit(&Variable::with_randomness(
+            G1Affine::zero(),
+            Randomness::zero()
+        ))
+    );
+
+    // A random linear combination of commitments extracts to the same combination of the committed values.
+    let n = 4;
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, n);
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, n);
+    let rhos = (0..n).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+
+    let c = x
+        .iter()
+        .zip(rhos.iter())
+        .map(|(x_i, rho_i)| cks.u.commit(x_i) * *rho_i)
+        .reduce(|acc, c_i| acc + c_i)
+        .unwrap();
+    let x_combination = x
+        .iter()
+        .zip(rhos.iter())
+        .map(|(x_i, rho_i)| x_i.value * rho_i)
+        .sum::<G1>();
+    assert_eq!(ek.extract_1(&c), x_combination.into_affine());
+
+    let d = y
+        .iter()
+        .zip(rhos.iter())
+        .map(|(y_j, rho_j)| cks.v.commit(y_j) * *rho_j)
+        .reduce(|acc, d_j| acc + d_j)
+        .unwrap();
+    let y_combination = y
+        .iter()
+        .zip(rhos.iter())
+        .map(|(y_j, rho_j)| y_j.value * rho_j)
+        .sum::<G2>();
+    assert_eq!(ek.extract_2(&d), y_combination.into_affine());
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_com_zero() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let x_value = G1Affine::rand(rng);
+    let c = cks.u.commit(&Variable::<G1>::new(rng, x_value));
+    assert_eq!(Com::zero() + c, c);
+    assert_eq!(c + Com::zero(), c);
+    assert!(Com::<G1>::zero().is_zero());
+    assert!(!c.is_zero());
+    assert!(Com::<G1>::zero().is_zero_commitment(&cks.u, &Randomness::zero()));
+    assert_eq!(ek.extract_1(&Com::zero()), G1Affine::zero());
+    assert_eq!(ek.extract_2(&Com::zero()), G2Affine::zero());
+
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 3);
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    assert_eq!(c.iter().sum::<Com<G1>>(), c[0] + c[1] + c[2]);
+    assert_eq!(
+        Vec::<Com<G1>>::new().into_iter().sum::<Com<G1>>(),
+        Com::zero()
+    );
+
+    // Padding the commitments with a zero commitment of a new variable keeps the proof valid.
+    let ProofSystem {
+        equation,
+        mut c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, cks, 2, 2);
+    let padding = Equation::<F>::new(
+        vec![],
+        vec![G2Affine::rand(rng)],
+        Matrix::from_elem(1, 0, Fr::zero()),
+        ark_ec::pairing::PairingOutput::zero(),
+    );
+    let equation = equation + padding;
+    c.push(Com::zero());
+    assert!(equation.verify(cks, &c, &d, &proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_display() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let x = Variable::<G1>::with_zero_randomness(G1Affine::generator());
+    let mut bytes = Vec::new();
+    x.value.serialize_compressed(&mut bytes).unwrap();
+    let prefix = bytes[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    assert_eq!(x.to_string(), format!("Variable({prefix}...)"));
+    // The randomness is not displayed.
+    assert_eq!(Variable::<G1>::new(rng, x.value).to_string(), x.to_string());
+
+    let ProofSystem { c, proof, .. } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 1);
+    assert_eq!(c[0].to_string(), c[0].to_string());
+    assert_ne!(c[0].to_string(), c[1].to_string());
+    assert!(c[0].to_string().starts_with("Com("));
+    assert_eq!(c[0].to_string().matches("...").count(), 2);
+
+    let display = proof.to_string();
+    assert_eq!(display, proof.clone().to_string());
+    assert!(display.starts_with("Proof(phi: ["));
+    assert!(display.contains("], theta: ["));
+    assert_eq!(display.matches("...").count(), 8);
+}
+
+#[test]
+fn test_randomness_arithmetic() {
+    let rng = &mut test_rng();
+    let (r, s) = (Randomness::<G1>::rand(rng), Randomness::<G1>::rand(rng));
+    let rho = Fr::rand(rng);
+    assert_eq!(r + s, Randomness(r.0 + s.0, r.1 + s.1));
+    assert_eq!(r - s, Randomness(r.0 - s.0, r.1 - s.1));
+    assert_eq!(r - s, r + (-s));
+    assert_eq!(r * rho, Randomness(r.0 * rho, r.1 * rho));
+    assert_eq!(r + Randomness::zero(), r);
+    assert!((r - r).is_zero());
+    assert!(!r.is_zero());
+    assert_eq!([r, s, r].iter().sum::<Randomness<G1>>(), r + s + r);
+    assert_eq!(
+        Vec::<Randomness<G1>>::new()
+            .into_iter()
+            .sum::<Randomness<G1>>(),
+        Randomness::zero()
+    );
+
+    // The offsets of multiple randomizations accumulate into a single net offset.
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (x_value, x_rand) = (G1Affine::rand(rng), Randomness::<G1>::rand(rng));
+    let original = cks.u.commit(&Variable::with_randomness(x_value, x_rand));
+    let mut c = original;
+    let offset = (0..3)
+        .map(|_| c.randomize(rng, &cks.u).1)
+        .sum::<Randomness<G1>>();
+    assert!((c - original).is_zero_commitment(&cks.u, &offset));
+    assert!(c.verify_opening(&cks.u, &Variable::with_randomness(x_value, x_rand + offset)));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_variable_add_and_sub() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 2);
+    let (x1, x2) = (x[0], x[1]);
+    assert_eq!((x1 + x2).value, (x1.value + x2.value).into_affine());
+    assert_eq!((x1 - x2).value, (x1.value - x2.value).into_affine());
+    assert_eq!(
+        cks.u.commit(&(x1 + x2)),
+        cks.u.commit(&x1) + cks.u.commit(&x2)
+    );
+    assert_eq!(
+        cks.u.commit(&(x1 - x2)),
+        cks.u.commit(&x1) - cks.u.commit(&x2)
+    );
+    assert!(cks.u.commit(&(x1 - x1)).is_zero());
+
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, 2);
+    let (y1, y2) = (y[0], y[1]);
+    assert_eq!(
+        cks.v.commit(&(y1 + y2)),
+        cks.v.commit(&y1) + cks.v.commit(&y2)
+    );
+    assert_eq!(
+        cks.v.commit(&(y1 - y2)),
+        cks.v.commit(&y1) - cks.v.commit(&y2)
+    );
+}
+
+#[test]
+fn test_matrix_row_echelon_form() {
+    let f = |x: i64| Fr::from(x);
+    let mut matrix = Matrix::new(&[
+        [f(0), f(2), f(4), f(2)],
+        [f(1), f(1), f(1), f(1)],
+        [f(2), f(4), f(6), f(4)],
+    ]);
+
+    let mut scaled = matrix.clone();
+    scaled.scale_row(0, f(3));
+    assert_eq!(scaled.to_vecs()[0], vec![f(0), f(6), f(12), f(6)]);
+    assert_eq!(scaled.to_vecs()[1..], matrix.to_vecs()[1..]);
+    scaled.add_row_multiple(2, 1, f(-2));
+    assert_eq!(scaled.to_vecs()[2], vec![f(0), f(2), f(4), f(2)]);
+
+    // The third row is 2 (second row) + (first row), so the rank is 2.
+    let (echelon, pivots) = matrix.row_echelon_form();
+    assert_eq!(pivots, vec![0, 1]);
+    assert_eq!(
+        echelon,
+        Matrix::new(&[
+            [f(1), f(1), f(1), f(1)],
+            [f(0), f(1), f(2), f(1)],
+            [f(0), f(0), f(0), f(0)],
+        ])
+    );
+
+    matrix[(2, 3)] = f(5);
+    let (echelon, pivots) = matrix.row_echelon_form();
+    assert_eq!(pivots, vec![0, 1, 3]);
+    assert_eq!(echelon.to_vecs()[2], vec![f(0), f(0), f(0), f(1)]);
+
+    let zeros = Matrix::from_elem(3, 4, Fr::zero());
+    assert_eq!(zeros.row_echelon_form(), (zeros.clone(), vec![]));
+}
+
+#[test]
+fn test_opening_to_public() {
+    use gs_ppe::statements::{opening_to_public, prove_opening};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let g2 = G2Affine::generator();
+    let (value, wrong) = (G1Affine::rand(rng), G1Affine::rand(rng));
+    let x = Variable::<G1>::new(rng, value);
+
+    let proof_system = prove_opening(rng, &cks, x, value, g2);
+    assert_eq!(proof_system.c, vec![cks.u.commit(&x)]);
+    assert!(proof_system.d.is_empty());
+    assert_eq!(proof_system.equation, opening_to_public(value, g2));
+    let (equation, c, d, proof) = proof_system.components();
+    assert!(equation.verify(&cks, c, d, proof));
+
+    // The proof does not open to a wrong value.
+    let ProofSystem { c, d, proof, .. } = proof_system.clone();
+    assert!(!opening_to_public::<F>(wrong, g2).verify(&cks, &c, &d, &proof));
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = prove_opening(rng, &cks, x, wrong, g2);
+    assert!(!equation.verify(&cks, &c, &d, &proof));
+
+    // The re-randomized proof system verifies with fresh commitment randomness.
+    let randomized = proof_system.clone().randomize(rng, &cks);
+    assert_ne!(randomized.c, proof_system.c);
+    let (equation, c, d, proof) = randomized.components();
+    assert!(equation.verify(&cks, c, d, proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_commitment_keys_rand_batch() {
+    let rng = &mut test_rng();
+    let count = 3;
+    for keys in [
+        CommitmentKeys::<F>::rand_batch(rng, count),
+        CommitmentKeys::<F>::rand_wi_batch(rng, count),
+    ] {
+        assert_eq!(keys.len(), count);
+        for (k, cks) in keys.iter().enumerate() {
+            assert!(keys[k + 1..].iter().all(|other| other != cks));
+            assert_eq!(cks.u.normalize(), cks.u);
+            assert_eq!(cks.v.normalize(), cks.v);
+            let ProofSystem {
+                equation,
+                c,
+                d,
+                proof,
+            } = gs_ppe::test_utils::random_proof_system(rng, cks, 2, 2);
+            assert!(equation.verify(cks, &c, &d, &proof));
+        }
+    }
+    assert!(CommitmentKeys::<F>::rand_batch(rng, 0).is_empty());
+}
+
+#[test]
+fn test_committed_equal() {
+    use gs_ppe::statements::{prove_committed_equal_g1, prove_committed_equal_g2};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+    let verify = |proof_system: &ProofSystem<F>| {
+        let (equation, c, d, proof) = proof_system.components();
+        equation.verify(&cks, c, d, proof)
+    };
+
+    let (value, other) = (G1Affine::rand(rng), G1Affine::rand(rng));
+    let (x1, x2) = (
+        Variable::<G1>::new(rng, value),
+        Variable::<G1>::new(rng, value),
+    );
+    let x3 = Variable::<G1>::new(rng, other);
+    let proof_system = prove_committed_equal_g1(rng, &cks, x1, x2, g2);
+    assert_eq!(proof_system.c, vec![cks.u.commit(&x1), cks.u.commit(&x2)]);
+    assert!(verify(&proof_system));
+    assert!(!verify(&prove_committed_equal_g1(rng, &cks, x1, x3, g2)));
+    let randomized = proof_system.clone().randomize(rng, &cks);
+    assert_ne!(randomized.c, proof_system.c);
+    assert!(verify(&randomized));
+
+    let (value, other) = (G2Affine::rand(rng), G2Affine::rand(rng));
+    let (y1, y2) = (
+        Variable::<G2>::new(rng, value),
+        Variable::<G2>::new(rng, value),
+    );
+    let y3 = Variable::<G2>::new(rng, other);
+    let proof_system = prove_committed_equal_g2(rng, &cks, y1, y2, g1);
+    assert_eq!(proof_system.d, vec![cks.v.commit(&y1), cks.v.commit(&y2)]);
+    assert!(verify(&proof_system));
+    assert!(!verify(&prove_committed_equal_g2(rng, &cks, y3, y2, g1)));
+    let randomized = proof_system.clone().randomize(rng, &cks);
+    assert_ne!(randomized.d, proof_system.d);
+    assert!(verify(&randomized));
+}
+
+#[test]
+fn test_cross_group_link() {
+    use gs_ppe::statements::{cross_group_link, prove_cross_group_link};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let s = Fr::rand(rng);
+    let x_value = (g1 * s).into_affine();
+    let x = Variable::<G1>::new(rng, x_value);
+    let y_value = (g2 * s).into_affine();
+    let y = Variable::<G2>::new(rng, y_value);
+
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = prove_cross_group_link(rng, &cks, g1, g2, x, y);
+    assert_eq!(equation, cross_group_link(g1, g2));
+    assert_eq!(
+        (c.clone(), d.clone()),
+        (vec![cks.u.commit(&x)], vec![cks.v.commit(&y)])
+    );
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    // A proof of another equation over the same variables shares the commitment d.
+    let other = gs_ppe::statements::committed_equal_g2::<F>(G1Affine::generator());
+    let y_copy = Variable::<G2>::new(rng, y_value);
+    let other_proof = Proof::new(rng, &cks, &other, &[], &[y, y_copy]);
+    let shared = vec![d[0], cks.v.commit(&y_copy)];
+    assert!(other.verify(&cks, &[], &shared, &other_proof));
+
+    let y_value = (g2 * (s + Fr::from(1u64))).into_affine();
+    let y = Variable::<G2>::new(rng, y_value);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = prove_cross_group_link(rng, &cks, g1, g2, x, y);
+    assert!(!equation.verify(&cks, &c, &d, &proof));
+}
+
+#[test]
+fn test_matrix_serialization() {
+    use ark_serialize::Compress;
+
+    let rng = &mut test_rng();
+    for (rows, cols) in [(2, 3), (1, 1), (0, 3), (3, 0), (0, 0)] {
+        let matrix = Matrix::<Fr>::rand(rng, rows, cols);
+        let mut bytes = Vec::new();
+        matrix.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 8 + 32 * rows * cols);
+        assert_eq!(bytes.len(), matrix.compressed_size());
+        assert_eq!(bytes[..4], (rows as u32).to_le_bytes());
+        assert_eq!(bytes[4..8], (cols as u32).to_le_bytes());
+        assert_eq!(
+            Matrix::<Fr>::deserialize_compressed(&bytes[..]).unwrap(),
+            matrix
+        );
+        if rows * cols > 0 {
+            assert!(Matrix::<Fr>::deserialize_compressed(&bytes[..bytes.len() - 1]).is_err());
+        }
+    }
+
+    let matrix = Matrix::<G1Affine>::rand(rng, 2, 2);
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        matrix.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(bytes.len(), matrix.serialized_size(compress));
+        let deserialized = Matrix::<G1Affine>::deserialize_with_mode(
+            &bytes[..],
+            compress,
+            ark_serialize::Validate::Yes,
+        )
+        .unwrap();
+        assert_eq!(deserialized, matrix);
+    }
+
+    // The elements are in row-major order, even if the matrix is stored in column-major order.
+    let transposed = Matrix::<Fr>::from(Matrix::<Fr>::rand(rng, 3, 2).take().reversed_axes());
+    let mut bytes = Vec::new();
+    transposed.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        Matrix::<Fr>::deserialize_compressed(&bytes[..]).unwrap(),
+        transposed
+    );
+    let mut first_row = Vec::new();
+    transposed.to_vecs()[0]
+        .serialize_compressed(&mut first_row)
+        .unwrap();
+    assert_eq!(bytes[8..8 + 3 * 32], first_row[8..]);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_setup_with_seed() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, 2, 3);
+    let (seed, other_seed) = ([1u8; 32], [2u8; 32]);
+
+    let proof = Proof::new_with_seed(&seed, &cks, &equation, &x, &y);
+    let same = Proof::new_with_seed(&seed, &cks, &equation, &x, &y);
+    let other = Proof::new_with_seed(&other_seed, &cks, &equation, &x, &y);
+    assert_eq!(proof.to_transcript_bytes(), same.to_transcript_bytes());
+    assert_ne!(proof.to_transcript_bytes(), other.to_transcript_bytes());
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    assert!(equation.verify(&cks, &c, &d, &other));
+
+    let ay = y
+        .iter()
+        .map(|y_j| (G1Affine::rand(rng), *y_j))
+        .collect::<Vec<_>>();
+    let xb = x
+        .iter()
+        .map(|x_i| (*x_i, G2Affine::rand(rng)))
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, 2, 3);
+    let proof_system = gs_ppe::setup_with_seed(&seed, &cks, &ay, &xb, &gamma);
+    let same = gs_ppe::setup_with_seed(&seed, &cks, &ay, &xb, &gamma);
+    let other = gs_ppe::setup_with_seed(&other_seed, &cks, &ay, &xb, &gamma);
+    assert_eq!(
+        proof_system.to_transcript_bytes(),
+        same.to_transcript_bytes()
+    );
+    assert_ne!(proof_system.proof, other.proof);
+    assert_eq!((&proof_system.c, &proof_system.d), (&other.c, &other.d));
+    let (equation, c, d, proof) = proof_system.components();
+    assert!(equation.verify(&cks, c, d, proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_system_dims() {
+    use gs_ppe::ProofSystemDims;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let dims = |m, n| ProofSystemDims { m, n, c: m, d: n };
+
+    let ps1 = gs_ppe::test_utils::random_proof_system(rng, &cks, 0, 2);
+    assert_eq!(ps1.equation.dim(), (0, 2));
+    assert_eq!((ps1.equation.num_x(), ps1.equation.num_y()), (0, 2));
+    assert_eq!(ps1.dims(), dims(0, 2));
+    assert!(ps1.is_consistent());
+
+    let ps2 = gs_ppe::test_utils::random_proof_system(rng, &cks, 3, 0);
+    assert_eq!(ps2.equation.dim(), (3, 0));
+    assert_eq!((ps2.equation.num_x(), ps2.equation.num_y()), (3, 0));
+    assert_eq!(ps2.dims(), dims(3, 0));
+    assert!(ps2.is_consistent());
+
+    let sum = ps1 + ps2;
+    assert_eq!(sum.equation.dim(), (3, 2));
+    assert_eq!(sum.dims(), dims(3, 2));
+    assert!(sum.is_consistent());
+    assert!(ProofSystem::<F>::sum([]).is_consistent());
+    assert_eq!(ProofSystem::<F>::sum([]).dims(), dims(0, 0));
+
+    let mut inconsistent = sum.clone();
+    inconsistent.c.pop();
+    assert_eq!(inconsistent.dims(), ProofSystemDims { c: 2, ..dims(3, 2) });
+    assert!(!inconsistent.is_consistent());
+    let mut inconsistent = sum;
+    inconsistent.d.push(Com::zero());
+    assert!(!inconsistent.is_consistent());
+}
+
+#[test]
+fn test_dh_equation() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let x = Fr::rand(rng);
+    let target = F::pairing(g1, g2) * x;
+
+    let (equation, witness) = Equation::<F>::dh_equation(g1, g2, target);
+    assert_eq!(equation.dim(), (1, 0));
+    let x_var = witness(rng, x);
+    assert_eq!(x_var.value, (g1 * x).into_affine());
+    assert!(equation.verify_target_with_values(&[x_var.value], &[]));
+
+    let c = vec![cks.u.commit(&x_var)];
+    let proof = Proof::new(rng, &cks, &equation, &[x_var], &[]);
+    assert!(equation.verify(&cks, &c, &[], &proof));
+
+    // The witness of another discrete logarithm does not satisfy the equation.
+    let wrong = witness(rng, x + Fr::from(1u64));
+    let proof = Proof::new(rng, &cks, &equation, &[wrong], &[]);
+    assert!(!equation.verify(&cks, &[cks.u.commit(&wrong)], &[], &proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_from_parts() {
+    use ark_bls12_381::{Fq, Fq2};
+    use gs_ppe::{equation::InvalidPoint, prove::InvalidProof};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 2);
+
+    let (phi, theta) = proof.clone().into_parts();
+    let rebuilt = Proof::<F>::from_parts(phi.clone(), theta.clone()).unwrap();
+    assert_eq!(rebuilt, proof);
+    assert!(equation.verify(&cks, &c, &d, &rebuilt));
+
+    let mut rows = phi.to_vecs();
+    rows.push(rows[0].clone());
+    assert_eq!(
+        Proof::<F>::from_parts(Matrix::from_vecs(rows), theta.clone()),
+        Err(InvalidProof::PhiDim((3, 2)))
+    );
+    assert_eq!(
+        Proof::<F>::from_parts(phi.clone(), Matrix::from_elem(2, 1, G1Affine::zero())),
+        Err(InvalidProof::ThetaDim((2, 1)))
+    );
+
+    // A point which is not on the curve.
+    let mut invalid = theta.clone();
+    invalid[(1, 0)] = G1Affine::new_unchecked(Fq::rand(rng), Fq::rand(rng));
+    assert_eq!(
+        Proof::<F>::from_parts(phi.clone(), invalid),
+        Err(InvalidProof::Point(InvalidPoint::Theta((1, 0))))
+    );
+
+    // A point on the curve but not in the prime-order subgroup.
+    let low_order = loop {
+        if let Some(p) = G2Affine::get_point_from_x_unchecked(Fq2::rand(rng), true) {
+            break p;
+        }
+    };
+    let mut invalid = phi;
+    invalid[(0, 1)] = low_order;
+    assert_eq!(
+        Proof::<F>::from_parts(invalid, theta),
+        Err(InvalidProof::Point(InvalidPoint::Phi((0, 1))))
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_matrix_from_affine_slice() {
+    let rng = &mut test_rng();
+    let points = (0..4).map(|_| G1::rand(rng)).collect::<Vec<_>>();
+    let affine = points.iter().map(|p| p.into_affine()).collect::<Vec<_>>();
+    let expected = Matrix::new(&[[affine[0], affine[1]], [affine[2], affine[3]]]);
+
+    let matrix = Matrix::from_affine_slice(&affine, 2, 2);
+    assert_eq!(matrix, expected);
+    assert_eq!(
+        (
+            matrix[(0, 0)],
+            matrix[(0, 1)],
+            matrix[(1, 0)],
+            matrix[(1, 1)]
+        ),
+        (affine[0], affine[1], affine[2], affine[3])
+    );
+    assert_eq!(
+        Matrix::<G1Affine>::from_projective_slice(&points, 2, 2),
+        expected
+    );
+    assert_eq!(
+        Matrix::<G1Affine>::from_projective_slice(&points, 1, 4),
+        Matrix::from_affine_slice(&affine, 1, 4)
+    );
+
+    // Rebuild a proof from the flat points.
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 1, 2);
+    let (phi, theta) = proof.into_parts();
+    let phi = phi.into_flat_vec();
+    let theta = theta.into_flat_vec();
+    let proof = Proof::<F>::from_parts(
+        Matrix::from_affine_slice(&phi, 2, 2),
+        Matrix::from_affine_slice(&theta, 2, 2),
+    )
+    .unwrap();
+    assert!(equation.verify(&cks, &c, &d, &proof));
+}
+
+#[test]
+#[should_panic]
+fn test_matrix_from_affine_slice_invalid_len() {
+    let rng = &mut test_rng();
+    let affine = (0..3).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    Matrix::from_affine_slice(&affine, 2, 2);
+}
+
+#[test]
+fn test_com_valid() {
+    use ark_bls12_381::{Fq, Fq2};
+    use ark_serialize::{Compress, Valid, Validate};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let x_value = G1Affine::rand(rng);
+    let c = cks.u.commit(&Variable::<G1>::new(rng, x_value));
+    assert!(c.is_valid());
+    assert!(c.check().is_ok());
+
+    let mut bytes = Vec::new();
+    c.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), c.compressed_size());
+    assert_eq!(Com::<G1>::deserialize_compressed(&bytes[..]).unwrap(), c);
+    let coms = vec![c, Com::zero()];
+    let mut bytes = Vec::new();
+    coms.serialize_uncompressed(&mut bytes).unwrap();
+    assert_eq!(
+        Vec::<Com<G1>>::deserialize_uncompressed(&bytes[..]).unwrap(),
+        coms
+    );
+
+    // A point which is not on the curve.
+    let off_curve = Com::<G1>(c.0, G1Affine::new_unchecked(Fq::rand(rng), Fq::rand(rng)));
+    assert!(!off_curve.is_valid());
+
+    // A point on the curve but not in the prime-order subgroup.
+    let low_order = loop {
+        if let Some(p) = G2Affine::get_point_from_x_unchecked(Fq2::rand(rng), true) {
+            break p;
+        }
+    };
+    let y_value = G2Affine::rand(rng);
+    let d = cks.v.commit(&Variable::<G2>::new(rng, y_value));
+    let invalid = Com::<G2>(low_order, d.1);
+    assert!(!invalid.is_valid());
+    assert!(invalid.check().is_err());
+
+    let mut bytes = Vec::new();
+    invalid.serialize_compressed(&mut bytes).unwrap();
+    assert!(Com::<G2>::deserialize_compressed(&bytes[..]).is_err());
+    let unchecked =
+        Com::<G2>::deserialize_with_mode(&bytes[..], Compress::Yes, Validate::No).unwrap();
+    assert_eq!(unchecked, invalid);
+    assert!(Vec::<Com<G2>>::check(&vec![d, unchecked]).is_err());
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_extractable_commitments() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 2);
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, 3);
+    let ay = y
+        .iter()
+        .map(|y_j| (G1Affine::rand(rng), *y_j))
+        .collect::<Vec<_>>();
+    let xb = x
+        .iter()
+        .map(|x_i| (*x_i, G2Affine::rand(rng)))
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, 2, 3);
+    let proof_system = setup(rng, cks, &ay, &xb, &gamma);
+    let commitments = proof_system.extractable_commitments();
+    assert_eq!(
+        (&commitments.c, &commitments.d),
+        (&proof_system.c, &proof_system.d)
+    );
+
+    let (x_values, y_values) = commitments.extract(ek);
+    assert_eq!(x_values, x.iter().map(|x_i| x_i.value).collect::<Vec<_>>());
+    assert_eq!(y_values, y.iter().map(|y_j| y_j.value).collect::<Vec<_>>());
+    assert!(proof_system
+        .equation
+        .verify_target_with_values(&x_values, &y_values));
+
+    // The extracted values of a randomized proof system are the same.
+    let randomized = proof_system.randomize(rng, cks);
+    assert_eq!(
+        randomized.extractable_commitments().extract(ek),
+        (x_values, y_values)
+    );
+}
+
+#[test]
+fn test_commitment_keys_validate() {
+    use gs_ppe::commit::InvalidCommitmentKeys;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    assert_eq!(cks.validate(), Ok(()));
+    assert_eq!(CommitmentKeys::<F>::rand_wi(rng).validate(), Ok(()));
+
+    let mut zeroed = cks;
+    zeroed.v.1 .0 = G2Affine::zero();
+    assert_eq!(zeroed.validate(), Err(InvalidCommitmentKeys::V((1, 0))));
+
+    // The affine coordinates (x, y) of u12 are swapped, so it is not on the curve.
+    let mut swapped = cks;
+    let (x, y) = swapped.u.0 .1.xy().unwrap();
+    swapped.u.0 .1 = G1Affine::new_unchecked(y, x);
+    assert_eq!(swapped.validate(), Err(InvalidCommitmentKeys::U((0, 1))));
+
+    let mut duplicated = cks;
+    duplicated.u.1 = duplicated.u.0;
+    assert_eq!(
+        duplicated.validate(),
+        Err(InvalidCommitmentKeys::DegenerateU)
+    );
+    let mut duplicated = cks;
+    duplicated.v.0 = duplicated.v.1;
+    assert_eq!(
+        duplicated.validate(),
+        Err(InvalidCommitmentKeys::DegenerateV)
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_scale() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 2);
+    let (one, two) = (Fr::from(1u64), Fr::from(2u64));
+
+    assert_eq!(equation.scale(one), equation);
+    assert_eq!(equation.scale_gamma(one), equation);
+    assert_eq!(
+        equation.scale(two).scale(two),
+        equation.scale(Fr::from(4u64))
+    );
+    assert_eq!(
+        equation.scale_gamma(two).scale_gamma(two),
+        equation.scale_gamma(Fr::from(4u64))
+    );
+
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    assert!(!equation.scale(two).verify(&cks, &c, &d, &proof));
+    assert!(!equation.scale_gamma(two).verify(&cks, &c, &d, &proof));
+
+    // The equation with zero gamma is linear.
+    let linear = equation.scale_gamma(Fr::zero());
+    assert_eq!(linear.dim(), equation.dim());
+    assert_ne!(linear, equation);
+}
+
+#[test]
+fn test_commitment_keys_setup_full() {
+    use gs_ppe::{commit::Trapdoor, ExtractKey};
+
+    let rng = &mut test_rng();
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (cks, trapdoor) = CommitmentKeys::<F>::setup_full(rng, g1, g2);
+    let Trapdoor { a1, a2, t1, t2 } = trapdoor;
+
+    // The trapdoor reproduces the key points from the generators.
+    let u1 = (g1, (g1 * a1).into_affine());
+    let v1 = (g2, (g2 * a2).into_affine());
+    assert_eq!(cks.u.0, u1);
+    assert_eq!(
+        cks.u.1,
+        ((g1 * t1).into_affine(), (g1 * (a1 * t1)).into_affine())
+    );
+    assert_eq!(cks.v.0, v1);
+    assert_eq!(
+        cks.v.1,
+        ((g2 * t2).into_affine(), (g2 * (a2 * t2)).into_affine())
+    );
+
+    let ek = ExtractKey::from(&trapdoor);
+    assert_eq!(ek, ExtractKey(a1, a2));
+    let x_value = G1Affine::rand(rng);
+    assert_eq!(
+        ek.extract_1(&cks.u.commit(&Variable::<G1>::new(rng, x_value))),
+        x_value
+    );
+    let y_value = G2Affine::rand(rng);
+    assert_eq!(
+        ek.extract_2(&cks.v.commit(&Variable::<G2>::new(rng, y_value))),
+        y_value
+    );
+
+    // setup_ex derives the same keys from the same randomness.
+    let seed = [3u8; 32];
+    let (cks, trapdoor) = CommitmentKeys::<F>::setup_full(&mut StdRng::from_seed(seed), g1, g2);
+    let (cks_ex, ek) = CommitmentKeys::<F>::setup_ex(&mut StdRng::from_seed(seed), g1, g2);
+    assert_eq!((cks, ExtractKey::from(&trapdoor)), (cks_ex, ek));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_verify_selected() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+
+    assert!(equation.verify_selected(&cks, &c, &d, &proof, &[true; 4]));
+    assert!(equation.verify_selected(&cks, &c, &d, &proof, &[false; 4]));
+    assert!(!equation.verify_selected(&cks, &c[1..], &d, &proof, &[false; 4]));
+
+    // φ22 appears only in Equation 2 and Equation 4.
+    let (mut phi, theta) = proof.into_parts();
+    phi[(1, 1)] = G2Affine::rand(rng);
+    let corrupted = Proof::from_parts(phi, theta).unwrap();
+    assert!(!equation.verify(&cks, &c, &d, &corrupted));
+    for bits in 0..16 {
+        let checks = [bits & 1 != 0, bits & 2 != 0, bits & 4 != 0, bits & 8 != 0];
+        assert_eq!(
+            equation.verify_selected(&cks, &c, &d, &corrupted, &checks),
+            !(checks[1] || checks[3])
+        );
+    }
+}
+
+#[test]
+fn test_commitment_keys_generators() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (g1, g2) = (cks.generator_g1(), cks.generator_g2());
+    assert!(g1.is_on_curve() && g1.is_in_correct_subgroup_assuming_on_curve());
+    assert!(g2.is_on_curve() && g2.is_in_correct_subgroup_assuming_on_curve());
+    // A non-identity point in the group of prime order generates the group.
+    assert!(!g1.is_zero() && !g2.is_zero());
+
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    for cks in [
+        CommitmentKeys::<F>::setup(rng, g1, g2),
+        CommitmentKeys::<F>::setup_wi(rng, g1, g2),
+    ] {
+        assert_eq!((cks.generator_g1(), cks.generator_g2()), (g1, g2));
+    }
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_target_pairs() {
+    use gs_ppe::Target;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    // e(x, b) = e(p1, q1) e(p2, q2), where x = p1^k, b = q1^(1/k) and p2 = q2 = 1.
+    let k = Fr::rand(rng);
+    let (p1, q1) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let pairs = vec![(p1, q1), (G1Affine::zero(), G2Affine::zero())];
+    let x = Variable::<G1>::new(rng, (p1 * k).into_affine());
+    let b = (q1 * ark_ff::Field::inverse(&k).unwrap()).into_affine();
+
+    let target = Target::<F>::from(pairs.clone());
+    let value = F::pairing(p1, q1);
+    assert_eq!(target.value(), value);
+    assert_eq!(ark_ec::pairing::PairingOutput::from(target.clone()), value);
+    assert_eq!(Target::from(value), Target::Value(value));
+
+    let gamma = Matrix::<Fr>::empty_rows(1);
+    let equ_pairs = Equation::<F>::new_with_target(vec![], vec![b], gamma.clone(), target);
+    let equ_value = Equation::<F>::new(vec![], vec![b], gamma, value);
+    assert_eq!(equ_pairs.target(), &Target::Pairs(pairs));
+    assert_eq!(
+        equ_pairs.to_transcript_bytes(),
+        equ_value.to_transcript_bytes()
+    );
+
+    let c = [cks.u.commit(&x)];
+    let proof = Proof::new(rng, &cks, &equ_value, &[x], &[]);
+    assert!(equ_pairs.verify(&cks, &c, &[], &proof));
+    assert!(equ_value.verify(&cks, &c, &[], &proof));
+
+    // Both representations reject the same wrong target.
+    let q = G2Affine::rand(rng);
+    let wrong_pairs = Equation::<F>::new_with_target(
+        vec![],
+        vec![b],
+        Matrix::empty_rows(1),
+        vec![(p1, q)].into(),
+    );
+    let wrong_value = Equation::<F>::new(vec![], vec![b], Matrix::empty_rows(1), F::pairing(p1, q));
+    assert!(!wrong_pairs.verify(&cks, &c, &[], &proof));
+    assert!(!wrong_value.verify(&cks, &c, &[], &proof));
+
+    // The operations on the equations are consistent with the values.
+    assert_eq!((-equ_pairs.clone()).target().value(), -value);
+    assert_eq!(equ_pairs.scale(k).target().value(), value * k);
+    let sum = equ_pairs.clone() + equ_pairs.clone();
+    assert!(matches!(sum.target(), Target::Pairs(pairs) if pairs.len() == 4));
+    let c = [c[0], c[0]];
+    let proof = Proof::new(rng, &cks, &sum, &[x, x], &[]);
+    assert!(sum.verify(&cks, &c, &[], &proof));
+
+    let (equ, x2, y2) = gs_ppe::test_utils::random_instance::<F, _>(rng, 1, 2);
+    let sum = equ_pairs + equ;
+    assert!(matches!(sum.target(), Target::Value(_)));
+    let x = [vec![x], x2].concat();
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y2.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &sum, &x, &y2);
+    assert!(sum.verify(&cks, &c, &d, &proof));
+}
+
+#[test]
+fn test_matrix_trace() {
+    let m = Matrix::<Fr>::new(&[[1u64, 2], [3, 4]].map(|row| row.map(Fr::from)));
+    assert_eq!(m.diagonal_entries(), vec![Fr::from(1u64), Fr::from(4u64)]);
+    assert_eq!(m.trace(), Fr::from(5u64));
+
+    let m = Matrix::<Fr>::new(&[[2u64, 0, 7], [5, 3, 0], [1, 1, 6]].map(|row| row.map(Fr::from)));
+    assert_eq!(
+        m.diagonal_entries(),
+        vec![Fr::from(2u64), Fr::from(3u64), Fr::from(6u64)]
+    );
+    assert_eq!(m.trace(), Fr::from(11u64));
+    assert_eq!(Matrix::<Fr>::empty().trace(), Fr::zero());
+
+    let rng = &mut test_rng();
+    let g1 = G1Affine::generator();
+    let g2 = G2Affine::rand(rng);
+    let points = Matrix::from(m.as_ref().mapv(|m_ij| (g1 * m_ij).into_affine()));
+    assert_eq!(
+        points.identity_pairing_product::<F>(g2),
+        F::pairing(g1, g2) * m.trace()
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_matrix_trace_not_square() {
+    Matrix::<Fr>::from_elem(2, 3, Fr::zero()).trace();
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_transpose() {
+    use gs_ppe::dual::Dual;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let dual_cks = cks.transpose();
+    assert_eq!((dual_cks.u, dual_cks.v), (cks.v, cks.u));
+
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, 2, 3);
+    let dual_equation = equation.transpose();
+    assert_eq!(dual_equation.dim(), (3, 2));
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+
+    // The same relation is proved in both orientations.
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    let dual_proof = Proof::<Dual<F>>::new(rng, &dual_cks, &dual_equation, &y, &x);
+    assert!(dual_equation.verify(&dual_cks, &d, &c, &dual_proof));
+
+    // The proof over E is also a proof of the dual equation.
+    assert!(dual_equation.verify(&dual_cks, &d, &c, &proof.transpose()));
+
+    // The target given as pairs is transposed as well.
+    let (p, q) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let equation =
+        Equation::<F>::new_with_target(vec![], vec![q], Matrix::empty_rows(1), vec![(p, q)].into());
+    let x = Variable::<G1>::new(rng, p);
+    let proof = Proof::new(rng, &cks, &equation, &[x], &[]);
+    let dual_equation = equation.transpose();
+    assert!(dual_equation.verify(&dual_cks, &[], &[cks.u.commit(&x)], &proof.transpose()));
+}
+
+#[test]
+fn test_variable_map_value() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let value = G1Affine::rand(rng);
+    let x = Variable::<G1>::new(rng, value);
+
+    let mapped = Variable::map_value::<_, G1>(rng, &x, |p| p);
+    assert_eq!(mapped.value, value);
+    assert_ne!(cks.u.commit(&mapped), cks.u.commit(&x));
+
+    let rand = Randomness::<G1>::rand(rng);
+    let mapped = Variable::map_value_deterministic::<G1>(&x, |p| p, rand);
+    assert_eq!(
+        cks.u.commit(&mapped),
+        cks.u.commit(&Variable::with_randomness(value, rand))
+    );
+
+    // A group endomorphism, e.g. X -> X^k.
+    let k = Fr::rand(rng);
+    let mapped = Variable::map_value::<_, G1>(rng, &x, |p| (p * k).into_affine());
+    assert_eq!(mapped.value, (value * k).into_affine());
+}
+
+#[test]
+fn test_commitment_keys_from_trapdoor_known_answers() {
+    fn compressed_hex<T: CanonicalSerialize>(point: &T) -> String {
+        let mut bytes = Vec::new();
+        point.serialize_compressed(&mut bytes).unwrap();
+        to_hex(&bytes)
+    }
+
+    let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+    let (a1, a2, t1, t2) = (
+        Fr::from(2u64),
+        Fr::from(3u64),
+        Fr::from(5u64),
+        Fr::from(7u64),
+    );
+    assert_eq!(
+        compressed_hex(&g1),
+        "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+    );
+
+    // u1 = (g1, g1^2), u2 = (g1^5, g1^10), v1 = (g2, g2^3), v2 = (g2^7, g2^21)
+    let cks = CommitmentKeys::<F>::from_trapdoor(g1, g2, a1, a2, t1, t2);
+    assert_eq!((cks.u.0 .0, cks.v.0 .0), (g1, g2));
+    let expected = [
+        "a572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e",
+        "b0e7791fb972fe014159aa33a98622da3cdc98ff707965e536d8636b5fcc5ac7a91a8c46e59a00dca575af0f18fb13dc",
+        "af81da25ecf1c84b577fefbedd61077a81dc43b00304015b2b596ab67f00e41c86bb00ebd0f90d4b125eb0539891aeed",
+        "89380275bbc8e5dcea7dc4dd7e0550ff2ac480905396eda55062650f8d251c96eb480673937cc6d9d6a44aaa56ca66dc122915c824a0857e2ee414a3dccb23ae691ae54329781315a0c75df1c04d6d7a50a030fc866f09d516020ef82324afae",
+        "8d0273f6bf31ed37c3b8d68083ec3d8e20b5f2cc170fa24b9b5be35b34ed013f9a921f1cad1644d4bdb14674247234c8049cd1dbb2d2c3581e54c088135fef36505a6823d61b859437bfc79b617030dc8b40e32bad1fa85b9c0f368af6d38d3c",
+        "aba1ec44f95121bd622932b84bbb4b3d279f69c494ee44db68e3165c86b627ba5e397ee197313fb5b775972798997332186a1da343cacf1815b9c8b6c807f536249dbfdb59d77bf4920ad2198a0d83ada21f7c39de6f06a5599f22571cab288d",
+    ];
+    let actual = [
+        compressed_hex(&cks.u.0 .1),
+        compressed_hex(&cks.u.1 .0),
+        compressed_hex(&cks.u.1 .1),
+        compressed_hex(&cks.v.0 .1),
+        compressed_hex(&cks.v.1 .0),
+        compressed_hex(&cks.v.1 .1),
+    ];
+    assert_eq!(actual, expected);
+
+    // u2 = (g1^5, g1^9), v2 = (g2^7, g2^20), where the other points are the same as the standard setup.
+    let cks_wi = CommitmentKeys::<F>::from_trapdoor_wi(g1, g2, a1, a2, t1, t2);
+    assert_eq!((cks_wi.u.0, cks_wi.u.1 .0), (cks.u.0, cks.u.1 .0));
+    assert_eq!((cks_wi.v.0, cks_wi.v.1 .0), (cks.v.0, cks.v.1 .0));
+    assert_eq!(
+        compressed_hex(&cks_wi.u.1 .1),
+        "99cdf3807146e68e041314ca93e1fee0991224ec2a74beb2866816fd0826ce7b6263ee31e953a86d1b72cc2215a57793"
+    );
+    assert_eq!(
+        compressed_hex(&cks_wi.v.1 .1),
+        "b137d93502ef32471f47890a181d7823b3a86dbfcadcc930ae53952f528d617e742a52e4f243c615cc28163dc31bd8060c86c92c9598dde7e6fc5e05d70a34c7a14cff5f400f33cf6cc26e6bf6d9a0bbc421c00f3360721f51974d76be43bd38"
+    );
+    assert_eq!(cks_wi.u.1 .1, (g1 * Fr::from(9u64)).into_affine());
+    assert_eq!(cks_wi.v.1 .1, (g2 * Fr::from(20u64)).into_affine());
+
+    // The random setups are the same as from_trapdoor with the random scalars.
+    let (cks, trapdoor) = CommitmentKeys::<F>::setup_full(&mut test_rng(), g1, g2);
+    let gs_ppe::commit::Trapdoor { a1, a2, t1, t2 } = trapdoor;
+    assert_eq!(cks, CommitmentKeys::from_trapdoor(g1, g2, a1, a2, t1, t2));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_setup_with_openings() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (m, n) = (2, 3);
+    let ay = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, n)
+        .into_iter()
+        .map(|y_j| (G1Affine::rand(rng), y_j))
+        .collect::<Vec<_>>();
+    let xb = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, m)
+        .into_iter()
+        .map(|x_i| (x_i, G2Affine::rand(rng)))
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+
+    let gs_ppe::SetupOutput {
+        proof_system,
+        cr,
+        ds,
+    } = gs_ppe::setup_with_openings(rng, &cks, &ay, &xb, &gamma);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        mut proof,
+    } = proof_system;
+    assert_eq!(cr.iter().map(|(c_i, _)| *c_i).collect::<Vec<_>>(), c);
+    assert_eq!(ds.iter().map(|(d_j, _)| *d_j).collect::<Vec<_>>(), d);
+    for ((c_i, r_i), (x_i, _)) in cr.iter().zip(&xb) {
+        assert!(c_i.verify_opening(&cks.u, &Variable::with_randomness(x_i.value, *r_i)));
+    }
+    for ((d_j, s_j), (_, y_j)) in ds.iter().zip(&ay) {
+        assert!(d_j.verify_opening(&cks.v, &Variable::with_randomness(y_j.value, *s_j)));
+    }
+
+    // Randomize the proof with the openings, i.e. the commitments are randomized by Com(0, r_i) and Com(0, s_j).
+    proof.randomize(rng, &cks, &equation, &cr, &ds);
+    let c = cr
+        .iter()
+        .map(|(c_i, r_i)| {
+            *c_i + cks
+                .u
+                .commit(&Variable::with_randomness(G1Affine::zero(), *r_i))
+        })
+        .collect::<Vec<_>>();
+    let d = ds
+        .iter()
+        .map(|(d_j, s_j)| {
+            *d_j + cks
+                .v
+                .commit(&Variable::with_randomness(G2Affine::zero(), *s_j))
+        })
+        .collect::<Vec<_>>();
+    assert!(equation.verify(&cks, &c, &d, &proof));
+}
+
+#[test]
+fn test_matrix_ndarray_round_trip() {
+    let rng = &mut test_rng();
+    let arr = ndarray::Array2::from_shape_fn((2, 3), |_| Fr::rand(rng));
+
+    let matrix = Matrix::from_ndarray(arr.clone());
+    assert_eq!(matrix.dim(), (2, 3));
+    assert_eq!(matrix[(1, 2)], arr[(1, 2)]);
+    assert_eq!(matrix.clone().to_ndarray(), arr);
+    assert_eq!(ndarray::Array2::from(matrix.clone()), arr);
+    assert_eq!(Matrix::<Fr>::from(arr.clone()), matrix);
+    assert_eq!(
+        Matrix::from_ndarray(ndarray::Array2::from(matrix.clone())),
+        matrix
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_setup_from_iter() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    for (m, n) in [(0, 0), (1, 0), (0, 1), (2, 3), (4, 2)] {
+        let ay = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, n)
+            .into_iter()
+            .map(|y_j| (G1Affine::rand(rng), y_j))
+            .collect::<Vec<_>>();
+        let xb = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, m)
+            .into_iter()
+            .map(|x_i| (x_i, G2Affine::rand(rng)))
+            .collect::<Vec<_>>();
+        let gamma = Matrix::<Fr>::rand(rng, m, n);
+        let rows = gamma.to_vecs();
+
+        let seed = [m as u8 * 16 + n as u8; 32];
+        let expected = setup(&mut StdRng::from_seed(seed), &cks, &ay, &xb, &gamma);
+        let proof_system = gs_ppe::setup_from_iter(
+            &mut StdRng::from_seed(seed),
+            &cks,
+            ay.iter().copied(),
+            xb.iter().copied(),
+            rows.iter().map(|row| row.as_slice()),
+        );
+        assert_eq!(proof_system, expected);
+
+        let (equation, c, d, proof) = proof_system.into_components();
+        assert!(equation.verify(&cks, &c, &d, &proof));
+
+        let x = xb.iter().map(|(x_i, _)| *x_i).collect::<Vec<_>>();
+        let y = ay.iter().map(|(_, y_j)| *y_j).collect::<Vec<_>>();
+        let proof = Proof::new(&mut StdRng::from_seed(seed), &cks, &equation, &x, &y);
+        let streamed = Proof::new_from_iter(
+            &mut StdRng::from_seed(seed),
+            &cks,
+            &equation,
+            x.into_iter(),
+            y.into_iter(),
+        );
+        assert_eq!(streamed, proof);
+    }
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_from_dual() {
+    use gs_ppe::dual::Dual;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, 3, 2);
+    let dual = equation.transpose();
+    assert_eq!(Equation::from_dual(&dual), equation);
+
+    // The dual equation authored directly is proved over E after transposing back.
+    let dual_cks = cks.transpose();
+    let dual_proof = Proof::<Dual<F>>::new(rng, &dual_cks, &dual, &y, &x);
+    let d = y
+        .iter()
+        .map(|y_j| dual_cks.u.commit(y_j))
+        .collect::<Vec<_>>();
+    let c = x
+        .iter()
+        .map(|x_i| dual_cks.v.commit(x_i))
+        .collect::<Vec<_>>();
+    assert!(dual.verify(&dual_cks, &d, &c, &dual_proof));
+
+    let equation = Equation::from_dual(&dual);
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    let equation = Equation::<F>::new_with_target(
+        vec![],
+        vec![G2Affine::rand(rng)],
+        Matrix::empty_rows(1),
+        vec![(G1Affine::rand(rng), G2Affine::rand(rng))].into(),
+    );
+    assert_eq!(Equation::from_dual(&equation.transpose()), equation);
+}
+
+#[test]
+fn test_variable_from_bytes_hash() {
+    use ark_bls12_381::Fq;
+    use ark_ec::hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher};
+    use ark_ff::{field_hashers::DefaultFieldHasher, BigInteger, PrimeField};
+    use sha2::Sha256;
+
+    // The suites BLS12381G1_XMD:SHA-256_SSWU_RO_ and BLS12381G2_XMD:SHA-256_SSWU_RO_ of RFC 9380.
+    type G1Hasher = MapToCurveBasedHasher<
+        G1,
+        DefaultFieldHasher<Sha256, 128>,
+        WBMap<ark_bls12_381::g1::Config>,
+    >;
+    type G2Hasher = MapToCurveBasedHasher<
+        G2,
+        DefaultFieldHasher<Sha256, 128>,
+        WBMap<ark_bls12_381::g2::Config>,
+    >;
+
+    fn fq_hex(f: &Fq) -> String {
+        to_hex(&f.into_bigint().to_bytes_be())
+    }
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    // The test vectors of RFC 9380, Appendix J.9.1.
+    let domain = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+    for (msg, x_hex, y_hex) in [
+        (
+            &b""[..],
+            "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+            "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+        ),
+        (
+            &b"abc"[..],
+            "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+            "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d",
+        ),
+    ] {
+        let x = Variable::<G1>::from_bytes_hash::<_, G1Hasher>(rng, msg, domain).unwrap();
+        let x_value = Variable::<G1>::hash_value::<G1Hasher>(msg, domain).unwrap();
+        assert_eq!(x.value, x_value);
+        assert!(cks.u.commit(&x).verify_opening(&cks.u, &x));
+        assert_eq!(fq_hex(&x_value.x), x_hex);
+        assert_eq!(fq_hex(&x_value.y), y_hex);
+    }
+
+    // The test vectors of RFC 9380, Appendix J.10.1.
+    let domain = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+    for (msg, x_hex, y_hex) in [
+        (
+            &b""[..],
+            [
+                "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+            ],
+            [
+                "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+            ],
+        ),
+        (
+            &b"abc"[..],
+            [
+                "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+            ],
+            [
+                "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+                "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+            ],
+        ),
+    ] {
+        let y = Variable::<G2>::from_bytes_hash::<_, G2Hasher>(rng, msg, domain).unwrap();
+        let y_value = Variable::<G2>::hash_value::<G2Hasher>(msg, domain).unwrap();
+        assert_eq!(y.value, y_value);
+        assert!(cks.v.commit(&y).verify_opening(&cks.v, &y));
+        assert_eq!([fq_hex(&y_value.x.c0), fq_hex(&y_value.x.c1)], x_hex);
+        assert_eq!([fq_hex(&y_value.y.c0), fq_hex(&y_value.y.c1)], y_hex);
+    }
+
+    // Domain separation
+    let x_value = Variable::<G1>::hash_value::<G1Hasher>(b"abc", b"GS-PPE-TEST-V01").unwrap();
+    let other = Variable::<G1>::hash_value::<G1Hasher>(b"abc", b"GS-PPE-TEST-V02").unwrap();
+    assert_ne!(other, x_value);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_analyze_structure() {
+    use gs_ppe::prove::ProofStructure;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let ProofSystem { proof, .. } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+    assert_eq!(proof.analyze_structure(), ProofStructure::Full);
+    assert_eq!(ProofStructure::Full.estimated_size_reduction(), 0);
+
+    // Without variables and internal randomness, all elements are the identity.
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, 0, 0);
+    let z = Matrix::from_elem(2, 2, Fr::zero());
+    let proof = Proof::new_with_internal_randomness(&cks, &equation, &x, &y, z);
+    assert_eq!(proof.analyze_structure(), ProofStructure::BothReduced);
+    assert_eq!(proof.analyze_structure().estimated_size_reduction(), 288);
+
+    let (phi, theta) = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3)
+        .proof
+        .into_parts();
+    let mut phi_reduced = phi.clone();
+    phi_reduced[(0, 1)] = G2Affine::zero();
+    phi_reduced[(1, 1)] = G2Affine::zero();
+    let proof = Proof::<F>::from_parts(phi_reduced.clone(), theta.clone()).unwrap();
+    assert_eq!(proof.analyze_structure(), ProofStructure::PhiZeroColumn);
+    assert_eq!(proof.analyze_structure().estimated_size_reduction(), 192);
+
+    let mut theta_reduced = theta.clone();
+    theta_reduced[(1, 0)] = G1Affine::zero();
+    theta_reduced[(1, 1)] = G1Affine::zero();
+    let proof = Proof::<F>::from_parts(phi.clone(), theta_reduced.clone()).unwrap();
+    assert_eq!(proof.analyze_structure(), ProofStructure::ThetaZeroRow);
+    assert_eq!(proof.analyze_structure().estimated_size_reduction(), 96);
+
+    // A zero row of phi or a zero column of theta is not reduced.
+    let mut phi_row = phi.clone();
+    phi_row[(0, 0)] = G2Affine::zero();
+    phi_row[(0, 1)] = G2Affine::zero();
+    let proof = Proof::<F>::from_parts(phi_row, theta).unwrap();
+    assert_eq!(proof.analyze_structure(), ProofStructure::Full);
+}
+
+#[test]
+fn test_committed_variable_from_parts() {
+    use gs_ppe::{variable::InvalidOpening, CommittedVariable};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let value = G1Affine::rand(rng);
+    let x = CommittedVariable::<G1>::commit(rng, &cks.u, value);
+    assert!(x.commitment().verify_opening(&cks.u, x.variable()));
+
+    let (variable, commitment) = x.into_parts();
+    assert!(CommittedVariable::from_parts(&cks.u, variable, commitment).is_ok());
+
+    // Same value with different randomness.
+    let other = Variable::<G1>::new(rng, variable.value);
+    assert_eq!(
+        CommittedVariable::from_parts(&cks.u, other, commitment).err(),
+        Some(InvalidOpening)
+    );
+    // Under different commitment keys.
+    let other_cks = CommitmentKeys::<F>::rand(rng);
+    assert_eq!(
+        CommittedVariable::from_parts(&other_cks.u, variable, commitment).err(),
+        Some(InvalidOpening)
+    );
+}
+
+#[test]
+fn test_committed_variable_rerandomize() {
+    use gs_ppe::{setup_with_commitments, CommittedVariable};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (m, n) = (2, 3);
+    let mut xb = (0..m)
+        .map(|_| {
+            let value = G1Affine::rand(rng);
+            (
+                CommittedVariable::<G1>::commit(rng, &cks.u, value),
+                G2Affine::rand(rng),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut ay = (0..n)
+        .map(|_| {
+            let value = G2Affine::rand(rng);
+            (
+                G1Affine::rand(rng),
+                CommittedVariable::<G2>::commit(rng, &cks.v, value),
+            )
+        })
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        mut proof,
+    } = setup_with_commitments(rng, &cks, &ay, &xb, &gamma);
+    assert!(xb
+        .iter()
+        .zip(&c)
+        .all(|((x_i, _), c_i)| x_i.commitment() == c_i));
+    assert!(ay
+        .iter()
+        .zip(&d)
+        .all(|((_, y_j), d_j)| y_j.commitment() == d_j));
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    let cr = xb
+        .iter_mut()
+        .map(|(x_i, _)| x_i.rerandomize(rng, &cks.u))
+        .collect::<Vec<_>>();
+    let ds = ay
+        .iter_mut()
+        .map(|(_, y_j)| y_j.rerandomize(rng, &cks.v))
+        .collect::<Vec<_>>();
+    assert!(cr.iter().zip(&c).all(|((c_i, _), c_i_)| c_i == c_i_));
+    assert!(ds.iter().zip(&d).all(|((d_j, _), d_j_)| d_j == d_j_));
+
+    // The randomized commitments still open to the variables.
+    for (x_i, _) in xb.iter() {
+        assert!(x_i.commitment().verify_opening(&cks.u, x_i.variable()));
+    }
+    for (_, y_j) in ay.iter() {
+        assert!(y_j.commitment().verify_opening(&cks.v, y_j.variable()));
+    }
+
+    // The proof adapts to the randomized commitments.
+    let c_new = xb
+        .iter()
+        .map(|(x_i, _)| *x_i.commitment())
+        .collect::<Vec<_>>();
+    let d_new = ay
+        .iter()
+        .map(|(_, y_j)| *y_j.commitment())
+        .collect::<Vec<_>>();
+    assert_ne!(c, c_new);
+    assert!(!equation.verify(&cks, &c_new, &d_new, &proof));
+    proof.randomize(rng, &cks, &equation, &cr, &ds);
+    assert!(equation.verify(&cks, &c_new, &d_new, &proof));
+
+    // A fresh proof over the rerandomized variables verifies against the same commitments.
+    let ProofSystem {
+        c: c_fresh,
+        d: d_fresh,
+        proof,
+        ..
+    } = setup_with_commitments(rng, &cks, &ay, &xb, &gamma);
+    assert_eq!((&c_fresh, &d_fresh), (&c_new, &d_new));
+    assert!(equation.verify(&cks, &c_fresh, &d_fresh, &proof));
+}
+
+#[test]
+fn test_prove_commitment_equality() {
+    use gs_ppe::statements::prove_commitment_equality;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let equation = Equation::for_commitment_equality(&cks);
+    let value = G1Affine::rand(rng);
+    let (r1, r2) = (Randomness::<G1>::rand(rng), Randomness::<G1>::rand(rng));
+    let c1 = cks.u.commit(&Variable::with_randomness(value, r1));
+    let c2 = cks.u.commit(&Variable::with_randomness(value, r2));
+    assert_ne!(c1, c2);
+
+    let proof = prove_commitment_equality(rng, &cks, &c1, &c2, value, r1, r2);
+    assert!(equation.verify(&cks, &[c1, c2], &[], &proof));
+    assert!(!equation.verify(&cks, &[c2, c1], &[], &proof));
+
+    // The commitments to different values do not satisfy the equation.
+    let other_value = G1Affine::rand(rng);
+    let other = Variable::<G1>::new(rng, other_value);
+    let x1 = Variable::with_randomness(value, r1);
+    let c3 = cks.u.commit(&other);
+    let proof = Proof::new(rng, &cks, &equation, &[x1, other], &[]);
+    assert!(!equation.verify(&cks, &[c1, c3], &[], &proof));
+}
+
+#[test]
+#[should_panic]
+fn test_prove_commitment_equality_wrong_opening() {
+    use gs_ppe::statements::prove_commitment_equality;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let value = G1Affine::rand(rng);
+    let (r1, r2) = (Randomness::<G1>::rand(rng), Randomness::<G1>::rand(rng));
+    let c1 = cks.u.commit(&Variable::with_randomness(value, r1));
+    let c2 = cks.u.commit(&Variable::with_randomness(value, r2));
+    prove_commitment_equality(rng, &cks, &c1, &c2, value, r2, r1);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_com_proof_hash() {
+    use ark_serialize::Compress;
+    use std::collections::HashSet;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    // The same commitment computed directly and as the sum of two commitments.
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 2);
+    let (x1, x2) = (x[0], x[1]);
+    let c = cks.u.commit(&(x1 + x2));
+    let c_sum = cks.u.commit(&x1) + cks.u.commit(&x2);
+    assert_eq!(c, c_sum);
+
+    let coms = HashSet::from([c, c_sum, cks.u.commit(&x1)]);
+    assert_eq!(coms.len(), 2);
+    assert!(coms.contains(&c));
+
+    // The same proof computed directly and deserialized from bytes.
+    let ProofSystem { proof, .. } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+    let deserialized = Proof::<F>::from_phi_theta_flat(
+        &proof.phi_to_flat_bytes(),
+        &proof.theta_to_flat_bytes(),
+        Compress::Yes,
+    )
+    .unwrap();
+    let ProofSystem { proof: other, .. } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+    let proofs = HashSet::from([proof.clone(), deserialized, other]);
+    assert_eq!(proofs.len(), 2);
+    assert!(proofs.contains(&proof));
+}
+
+#[test]
+fn test_matrix_flatten_row_major() {
+    let rng = &mut test_rng();
+    for (rows, cols) in [(2, 3), (3, 1), (0, 2), (2, 0)] {
+        let m = Matrix::<Fr>::rand(rng, rows, cols);
+        let flat = m.flatten_row_major();
+        assert_eq!(flat.len(), rows * cols);
+        assert_eq!(flat, m.to_vecs().concat());
+        assert_eq!(Matrix::from_flat_row_major(flat, rows, cols), m);
+    }
+
+    // Transposed view of the matrix is not in the standard layout.
+    let m = Matrix::<Fr>::from(Matrix::<Fr>::rand(rng, 3, 2).take().reversed_axes());
+    assert_eq!(m.flatten_row_major(), m.to_vecs().concat());
+}
+
+#[test]
+#[should_panic]
+fn test_matrix_from_flat_row_major_invalid_len() {
+    Matrix::from_flat_row_major(vec![Fr::zero(); 5], 2, 3);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_setup_batch() {
+    use gs_ppe::{setup_batch, SetupInstance};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let instances = [(2, 2), (1, 3), (0, 2), (2, 0)]
+        .into_iter()
+        .map(|(m, n)| SetupInstance::<F> {
+            ay: gs_ppe::test_utils::y_variable_vec::<F, _>(rng, n)
+                .into_iter()
+                .map(|y_j| (G1Affine::rand(rng), y_j))
+                .collect(),
+            xb: gs_ppe::test_utils::x_variable_vec::<F, _>(rng, m)
+                .into_iter()
+                .map(|x_i| (x_i, G2Affine::rand(rng)))
+                .collect(),
+            gamma: Matrix::rand(rng, m, n),
+        })
+        .collect::<Vec<_>>();
+
+    let seed = rng.next_u64();
+    let proof_systems = setup_batch(&mut StdRng::seed_from_u64(seed), &cks, instances.clone());
+
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let expected = instances
+        .iter()
+        .map(|instance| setup(rng, &cks, &instance.ay, &instance.xb, &instance.gamma))
+        .collect::<Vec<_>>();
+    assert_eq!(proof_systems, expected);
+    for ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } in proof_systems.iter()
+    {
+        assert!(equation.verify(&cks, c, d, proof));
+    }
+    assert!(setup_batch(rng, &cks, vec![]).is_empty());
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_from_bilinear_form() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (g1, g2) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (m, n) = (2, 3);
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+    let xs = (0..m).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let ys = (0..n).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let x = xs
+        .iter()
+        .map(|x_i| Variable::<G1>::new(rng, (g1 * x_i).into_affine()))
+        .collect::<Vec<_>>();
+    let y = ys
+        .iter()
+        .map(|y_j| Variable::<G2>::new(rng, (g2 * y_j).into_affine()))
+        .collect::<Vec<_>>();
+
+    let (equation, base) = Equation::<F>::from_bilinear_form(gamma.clone(), &x, &y, g1, g2);
+    let mut form = Fr::zero();
+    for i in 0..m {
+        for j in 0..n {
+            form += gamma[(i, j)] * xs[i] * ys[j];
+        }
+    }
+    assert_eq!(base, F::pairing(g1, g2));
+    assert_eq!(equation.target().value(), base * form);
+
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    // Other witnesses do not satisfy the equation in general.
+    let x_other = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, m);
+    let c_other = x_other
+        .iter()
+        .map(|x_i| cks.u.commit(x_i))
+        .collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x_other, &y);
+    assert!(!equation.verify(&cks, &c_other, &d, &proof));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_system_prune() {
+    use gs_ppe::equation::PruneMap;
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let proof_system = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+
+    // The summand with the identity constants and zero gamma contributes nothing.
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 2);
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, 1);
+    let trivial = setup(
+        rng,
+        &cks,
+        &[(G1Affine::zero(), y[0])],
+        &[(x[0], G2Affine::zero()), (x[1], G2Affine::zero())],
+        &Matrix::from_elem(2, 1, Fr::zero()),
+    );
+    let padded = proof_system.clone() + trivial;
+    assert_eq!(padded.dims().m, 4);
+    assert!(padded
+        .equation
+        .verify(&cks, &padded.c, &padded.d, &padded.proof));
+
+    let (pruned, map) = padded.prune();
+    assert_eq!(
+        map,
+        PruneMap {
+            x: vec![0, 1],
+            y: vec![0, 1, 2],
+        }
+    );
+    assert_eq!(pruned.equation, proof_system.equation);
+    assert_eq!((&pruned.c, &pruned.d), (&proof_system.c, &proof_system.d));
+    assert!(pruned
+        .equation
+        .verify(&cks, &pruned.c, &pruned.d, &pruned.proof));
+
+    // Nothing to prune in the pruned proof system.
+    assert_eq!(pruned.prune().0, pruned);
+}
+
+#[test]
+fn test_equation_prune_keeps_gamma_terms() {
+    let rng = &mut test_rng();
+    // The identity constants with non-zero gamma are kept.
+    let mut gamma = Matrix::from_elem(2, 2, Fr::zero());
+    gamma[(1, 0)] = Fr::rand(rng);
+    let equation = Equation::<F>::new(
+        vec![G1Affine::zero(), G1Affine::zero()],
+        vec![G2Affine::zero(), G2Affine::zero()],
+        gamma.clone(),
+        F::pairing(G1Affine::rand(rng), G2Affine::rand(rng)),
+    );
+    let (pruned, map) = equation.prune();
+    assert_eq!((map.x, map.y), (vec![1], vec![0]));
+    assert_eq!(pruned.dim(), (1, 1));
+    assert_eq!(
+        pruned,
+        Equation::new(
+            vec![G1Affine::zero()],
+            vec![G2Affine::zero()],
+            Matrix::new(&[[gamma[(1, 0)]]]),
+            equation.target().value(),
+        )
+    );
+}
+
+#[test]
+fn test_commitment_key_multi_commit() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+    let xs = (0..3).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+
+    let (coms, r) = cks.u.multi_commit(rng, &xs);
+    assert_eq!(coms.len(), xs.len());
+    assert_eq!(coms, cks.u.open_multi(&xs, r));
+    for (com, x) in coms.iter().zip(&xs) {
+        assert_eq!(*com, cks.u.commit(&Variable::with_randomness(*x, r)));
+        assert_eq!(ek.extract_1(com), *x);
+    }
+
+    // The first elements are shared and the second elements differ by the committed values.
+    assert!(coms.iter().all(|com| com.0 == coms[0].0));
+    assert_eq!(
+        (coms[1].1.into_group() - coms[0].1).into_affine(),
+        (xs[1].into_group() - xs[0]).into_affine()
+    );
+
+    let ys = (0..2).map(|_| G2Affine::rand(rng)).collect::<Vec<_>>();
+    let (coms, s) = cks.v.multi_commit(rng, &ys);
+    for (com, y) in coms.iter().zip(&ys) {
+        assert_eq!(*com, cks.v.commit(&Variable::with_randomness(*y, s)));
+        assert_eq!(ek.extract_2(com), *y);
+    }
+    assert!(cks.v.open_multi(&[], s).is_empty());
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_add_key_randomness_is_randomize_with_zero_offsets() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+
+    let seed = rng.next_u64();
+    let mut expected = proof.clone();
+    let cr = c
+        .iter()
+        .map(|c_i| (*c_i, Randomness::zero()))
+        .collect::<Vec<_>>();
+    let ds = d
+        .iter()
+        .map(|d_j| (*d_j, Randomness::zero()))
+        .collect::<Vec<_>>();
+    expected.randomize(&mut StdRng::seed_from_u64(seed), &cks, &equation, &cr, &ds);
+    let mut rerandomized = proof;
+    rerandomized.add_key_randomness(&mut StdRng::seed_from_u64(seed), &cks);
+    assert_eq!(rerandomized, expected);
+    assert!(equation.verify(&cks, &c, &d, &rerandomized));
+}
+
+#[test]
+fn test_matrix_inverse() {
+    let rng = &mut test_rng();
+    let f = |x: i64| Fr::from(x);
+    let matrix = Matrix::<Fr>::rand(rng, 3, 3);
+    let inverse = matrix.inverse().unwrap();
+    for i in 0..3 {
+        for j in 0..3 {
+            let entry = (0..3).fold(Fr::zero(), |acc, k| acc + matrix[(i, k)] * inverse[(k, j)]);
+            assert_eq!(entry, f((i == j) as i64));
+        }
+    }
+    assert_eq!(inverse.inverse().unwrap(), matrix);
+
+    let singular = Matrix::new(&[[f(1), f(2)], [f(2), f(4)]]);
+    assert_eq!(singular.inverse(), None);
+    assert_eq!(Matrix::<Fr>::empty().inverse(), Some(Matrix::empty()));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_apply_linear_map_g1() {
+    let rng = &mut test_rng();
+    let f = |x: i64| Fr::from(x);
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (m, n) = (2, 3);
+    let a = (0..n).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let b = (0..m).map(|_| G2Affine::rand(rng)).collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, m);
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, n);
+    let x_values = x.iter().map(|x_i| x_i.value).collect::<Vec<_>>();
+    let y_values = y.iter().map(|y_j| y_j.value).collect::<Vec<_>>();
+    let equation =
+        Equation::<F>::from_witnesses(a.clone(), b.clone(), gamma.clone(), &x_values, &y_values);
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+
+    // The identity map keeps the statement.
+    let identity = Matrix::new(&[[f(1), f(0)], [f(0), f(1)]]);
+    let (mapped, c_mapped) = Proof::apply_linear_map_g1(proof.clone(), &identity, &c);
+    assert_eq!((&mapped, &c_mapped), (&proof, &c));
+    assert_eq!(equation.apply_linear_map_g1(&identity).unwrap(), equation);
+
+    // The permutation swaps the variables, the constants b and the rows of gamma.
+    let swap = Matrix::new(&[[f(0), f(1)], [f(1), f(0)]]);
+    let (mapped, c_mapped) = Proof::apply_linear_map_g1(proof.clone(), &swap, &c);
+    assert_eq!(c_mapped, vec![c[1], c[0]]);
+    let swapped = equation.apply_linear_map_g1(&swap).unwrap();
+    let mut gamma_swapped = gamma.clone();
+    gamma_swapped.swap_rows(0, 1);
+    assert_eq!(
+        swapped,
+        Equation::new(
+            a,
+            vec![b[1], b[0]],
+            gamma_swapped,
+            equation.target().value()
+        )
+    );
+    assert!(swapped.verify(&cks, &c_mapped, &d, &mapped));
+    assert!(!equation.verify(&cks, &c_mapped, &d, &mapped));
+
+    // A random invertible map, which the mapped values satisfy.
+    let l = Matrix::<Fr>::rand(rng, m, m);
+    let (mapped, c_mapped) = Proof::apply_linear_map_g1(proof, &l, &c);
+    let mapped_equation = equation.apply_linear_map_g1(&l).unwrap();
+    assert!(mapped_equation.verify(&cks, &c_mapped, &d, &mapped));
+    let mapped_values = (0..m)
+        .map(|k| {
+            x_values
+                .iter()
+                .enumerate()
+                .fold(G1::zero(), |acc, (i, x_i)| acc + *x_i * l[(k, i)])
+                .into_affine()
+        })
+        .collect::<Vec<_>>();
+    assert!(mapped_equation.verify_target_with_values(&mapped_values, &y_values));
+
+    assert_eq!(
+        equation.apply_linear_map_g1(&Matrix::from_elem(m, m, f(1))),
+        None
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_extract_and_verify() {
+    use gs_ppe::{extract::ExtractionError, ExtractKey};
+
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+    let ay = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, 3)
+        .into_iter()
+        .map(|y_j| (G1Affine::rand(rng), y_j))
+        .collect::<Vec<_>>();
+    let xb = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 2)
+        .into_iter()
+        .map(|x_i| (x_i, G2Affine::rand(rng)))
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, 2, 3);
+    let proof_system = setup(rng, cks, &ay, &xb, &gamma);
+
+    let (x, y) = ek.extract_and_verify(cks, &proof_system).unwrap();
+    assert_eq!(x, xb.iter().map(|(x_i, _)| x_i.value).collect::<Vec<_>>());
+    assert_eq!(y, ay.iter().map(|(_, y_j)| y_j.value).collect::<Vec<_>>());
+
+    let unrelated = ExtractKey::<F>(Fr::rand(rng), Fr::rand(rng));
+    assert_eq!(
+        unrelated.extract_and_verify(cks, &proof_system),
+        Err(ExtractionError::EquationMismatch)
+    );
+
+    let other_keys = CommitmentKeys::<F>::rand_ex(rng);
+    assert_eq!(
+        ek.extract_and_verify(other_keys.commitment_keys(), &proof_system),
+        Err(ExtractionError::InvalidProof)
+    );
+    let mut inconsistent = proof_system;
+    inconsistent.c.pop();
+    assert_eq!(
+        ek.extract_and_verify(cks, &inconsistent),
+        Err(ExtractionError::InvalidProof)
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_is_degenerate() {
+    use ark_ec::pairing::PairingOutput;
+    use gs_ppe::Target;
+
+    let rng = &mut test_rng();
+    let empty = Equation::<F>::empty();
+    assert!(empty.is_degenerate());
+    assert!(!empty.has_nonzero_target());
+    assert_eq!(empty, Equation::sum(vec![]));
+
+    // Probabilistically, the targets of random instances are never the identity.
+    let cks = CommitmentKeys::<F>::rand(rng);
+    for _ in 0..100 {
+        let ProofSystem { equation, .. } = gs_ppe::test_utils::random_proof_system(rng, &cks, 1, 0);
+        assert!(equation.has_nonzero_target());
+        assert!(!equation.is_degenerate());
+    }
+
+    // The identity target with constants is not degenerate.
+    let equation = Equation::<F>::new(
+        vec![G1Affine::rand(rng)],
+        vec![],
+        Matrix::empty_cols(1),
+        PairingOutput::zero(),
+    );
+    assert!(!equation.has_nonzero_target());
+    assert!(!equation.is_degenerate());
+
+    // The target of pairs is checked by its value.
+    let (p, q) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let pairs = Target::Pairs(vec![(p, q), ((-p.into_group()).into_affine(), q)]);
+    let equation = Equation::<F>::new_with_target(vec![], vec![], Matrix::empty(), pairs);
+    assert!(equation.is_degenerate());
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_extract_key_split() {
+    use gs_ppe::extract::PartialExtraction;
+
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+    let (share_a, share_b) = ek.split(rng);
+    assert_eq!(share_a.0 + share_b.0, ek.0);
+    assert_eq!(share_a.1 + share_b.1, ek.1);
+
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 1)[0];
+    let c = cks.u.commit(&x);
+    let (partial_a, partial_b) = (share_a.partial_extract_1(&c), share_b.partial_extract_1(&c));
+    assert_eq!(PartialExtraction::combine(&partial_a, &partial_b), x.value);
+    assert_eq!(
+        PartialExtraction::combine(&partial_b, &partial_a),
+        ek.extract_1(&c)
+    );
+    // A single partial extraction does not reveal the value.
+    assert_ne!((c.1 + partial_a.partial).into_affine(), x.value);
+    assert_ne!((c.1 + partial_b.partial).into_affine(), x.value);
+
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, 1)[0];
+    let d = cks.v.commit(&y);
+    let (partial_a, partial_b) = (share_a.partial_extract_2(&d), share_b.partial_extract_2(&d));
+    assert_eq!(PartialExtraction::combine(&partial_a, &partial_b), y.value);
+    assert_ne!((d.1 + partial_a.partial).into_affine(), y.value);
+
+    // Another split of the same key gives different shares.
+    let (other_a, _) = ek.split(rng);
+    assert_ne!(other_a, share_a);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+#[should_panic]
+fn test_partial_extraction_combine_different_commitments() {
+    use gs_ppe::extract::PartialExtraction;
+
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+    let (share_a, share_b) = ek.split(rng);
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, 2);
+    let _ = PartialExtraction::combine(
+        &share_a.partial_extract_1(&cks.u.commit(&x[0])),
+        &share_b.partial_extract_1(&cks.u.commit(&x[1])),
+    );
+}
+
+#[test]
+fn test_matrix_vandermonde() {
+    use ark_ff::Field;
+
+    let rng = &mut test_rng();
+    let f = |x: i64| Fr::from(x);
+    let vandermonde = Matrix::vandermonde(&[f(2), f(3)], 2);
+    assert_eq!(
+        vandermonde,
+        Matrix::new(&[[f(1), f(2), f(4)], [f(1), f(3), f(9)]])
+    );
+
+    // p(x) = 1 + 2x + 3x^2
+    let coeffs = [f(1), f(2), f(3)];
+    assert_eq!(Matrix::evaluate_polynomial(&coeffs, f(2)), f(17));
+    assert_eq!(Matrix::evaluate_polynomial(&[], f(2)), f(0));
+    assert_eq!(
+        Matrix::from_polynomial_evaluations(&coeffs, &[f(2), f(3)]),
+        Matrix::new(&[[f(17)], [f(34)]])
+    );
+
+    // The evaluations are the product of the Vandermonde matrix and the coefficients.
+    let points = (0..4).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let coeffs = (0..3).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let vandermonde = Matrix::vandermonde(&points, 2);
+    let evaluations = Matrix::from_polynomial_evaluations(&coeffs, &points);
+    for i in 0..4 {
+        let product = (0..3).fold(Fr::zero(), |acc, j| acc + vandermonde[(i, j)] * coeffs[j]);
+        assert_eq!(evaluations[(i, 0)], product);
+    }
+
+    // The matrix gamma[i][j] = alpha^(i*j).
+    let alpha = Fr::rand(rng);
+    let powers = (0..3).map(|i| alpha.pow([i])).collect::<Vec<_>>();
+    let gamma = Matrix::vandermonde(&powers, 2);
+    assert_eq!(gamma[(2, 2)], alpha.pow([4]));
+}
+
+#[test]
+fn test_randomness_derive() {
+    fn randomness_hex(rand: &Randomness<G1>) -> String {
+        let mut bytes = Vec::new();
+        rand.0.serialize_compressed(&mut bytes).unwrap();
+        rand.1.serialize_compressed(&mut bytes).unwrap();
+        to_hex(&bytes)
+    }
+
+    let (seed, context) = (b"master seed of the prover", b"message-id-1");
+    let rand = Randomness::<G1>::derive(seed, context);
+    // The vectors are computed by an independent implementation of HKDF-SHA256 (RFC 5869), with the halves of
+    // the 96-byte output reduced modulo the order of the scalar field.
+    assert_eq!(rand, Randomness::derive(seed, context));
+    assert_eq!(randomness_hex(&rand), "416df52a388f85adf4aef42019b81abd06c6b51bff07bfb6c8a61e40c7d13d3a347e0c512bb879188ea4757de6b610a22338b4281e302962b8cde17cfa6fe95a");
+    let rand_empty = Randomness::<G1>::derive(seed, b"");
+    assert_eq!(
+        randomness_hex(&rand_empty),
+        "7d29dd784584df5820abd733af4e3025fa14ed9302e539162a5ba67f0148c324114df8dc4a13cfa167730b77ad8481f51fd1349ac60c3929a21b57625759b018"
+    );
+
+    // Different contexts and seeds give different randomness, also if the concatenations are equal.
+    let others = [
+        Randomness::<G1>::derive(seed, b"message-id-2"),
+        Randomness::derive(b"another master seed", context),
+        Randomness::derive(b"master seed of the prove", b"rmessage-id-1"),
+    ];
+    for other in others.iter() {
+        assert_ne!(*other, rand);
+        assert_ne!((other.0, other.1), (rand.1, rand.0));
+    }
+
+    // Recommitting the same value in the same context gives the same commitment.
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let value = G1Affine::rand(rng);
+    let x = Variable::<G1>::new_derived(value, seed, context);
+    assert_eq!(
+        cks.u.commit(&x),
+        cks.u.commit(&Variable::with_randomness(value, rand))
+    );
+    let x_other = Variable::<G1>::new_derived(value, seed, b"message-id-2");
+    assert_ne!(cks.u.commit(&x), cks.u.commit(&x_other));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_sums_and_subgroup_membership() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let ProofSystem { proof, .. } = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 3);
+    assert!(proof.has_valid_subgroup_membership());
+    let (phi, theta) = proof.clone().into_parts();
+    let theta_sum: G1 = theta.as_ref().iter().map(|p| p.into_group()).sum();
+    let phi_sum: G2 = phi.as_ref().iter().map(|p| p.into_group()).sum();
+    assert_eq!(proof.theta_sum(), theta_sum);
+    assert_eq!(proof.phi_sum(), phi_sum);
+    assert!(!proof.theta_sum().is_zero());
+    assert!(!proof.phi_sum().is_zero());
+
+    // Without variables and internal randomness, the sums are zero, although the points are valid.
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, 0, 0);
+    let z = Matrix::from_elem(2, 2, Fr::zero());
+    let proof = Proof::new_with_internal_randomness(&cks, &equation, &x, &y, z);
+    assert!(proof.theta_sum().is_zero());
+    assert!(proof.phi_sum().is_zero());
+    assert!(proof.has_valid_subgroup_membership());
+}
+
+#[test]
+fn test_public_slots() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    // In a 2x2 equation, x_0 and y_1 are public, x_1 and y_0 are private.
+    let (x_public, y_public) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x_private, y_private) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let xb = vec![
+        (Variable::<G1>::public(x_public), G2Affine::rand(rng)),
+        (Variable::<G1>::new(rng, x_private), G2Affine::rand(rng)),
+    ];
+    let ay = vec![
+        (G1Affine::rand(rng), Variable::<G2>::new(rng, y_private)),
+        (G1Affine::rand(rng), Variable::<G2>::public(y_public)),
+    ];
+    let gamma = Matrix::<Fr>::rand(rng, 2, 2);
+    let proof_system = setup(rng, &cks, &ay, &xb, &gamma);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = &proof_system;
+    assert!(equation.verify(&cks, c, d, proof));
+
+    assert_eq!(c[0], Com::trivial(x_public));
+    assert_eq!(d[1], Com::trivial(y_public));
+    assert!(proof_system.check_public_slots(&[(0, x_public)], &[(1, y_public)]));
+    assert!(proof_system.check_public_slots(&[], &[]));
+
+    // The private slots are hiding commitments, and wrong values or indices are rejected.
+    assert!(!proof_system.check_public_slots(&[(1, x_private)], &[]));
+    assert!(!proof_system.check_public_slots(&[], &[(0, y_private)]));
+    assert!(!proof_system.check_public_slots(&[(0, x_private)], &[(1, y_public)]));
+    assert!(!proof_system.check_public_slots(&[(2, x_public)], &[]));
+}
+
+#[test]
+fn test_matrix_structured_rand() {
+    let rng = &mut test_rng();
+
+    let lower = Matrix::<Fr>::rand_lower_triangular(rng, 4);
+    assert_eq!(lower.dim(), (4, 4));
+    for i in 0..4 {
+        for j in 0..4 {
+            assert_eq!(lower[(i, j)].is_zero(), j > i);
+        }
+    }
+    assert!(lower.inverse().is_some());
+
+    let sums = (0..3).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let fixed = Matrix::<Fr>::rand_with_fixed_row_sums(rng, 3, 4, &sums);
+    assert_eq!(fixed.dim(), (3, 4));
+    for (row, sum) in fixed.to_vecs().into_iter().zip(sums.iter()) {
+        assert_eq!(row.iter().sum::<Fr>(), *sum);
+    }
+    let single = Matrix::<Fr>::rand_with_fixed_row_sums(rng, 3, 1, &sums);
+    assert_eq!(
+        single,
+        Matrix::from_vecs(sums.iter().map(|s| vec![*s]).collect())
+    );
+    let empty = Matrix::<Fr>::rand_with_fixed_row_sums(rng, 2, 0, &[Fr::zero(), Fr::zero()]);
+    assert_eq!(empty.dim(), (2, 0));
+
+    let symmetric = Matrix::<Fr>::rand_symmetric(rng, 4);
+    assert_eq!(symmetric.dim(), (4, 4));
+    assert_eq!(
+        symmetric,
+        Matrix::from_ndarray(symmetric.as_ref().t().to_owned())
+    );
+    assert_ne!(symmetric[(0, 1)], symmetric[(0, 2)]);
+}
+
+#[test]
+#[should_panic]
+fn test_matrix_rand_with_fixed_row_sums_without_columns() {
+    let rng = &mut test_rng();
+    let _ = Matrix::<Fr>::rand_with_fixed_row_sums(rng, 1, 0, &[Fr::from(1u64)]);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_stamped_proof_system() {
+    use gs_ppe::stamped::{KeyMismatch, StampedProofSystem};
+
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let other_cks = CommitmentKeys::<F>::rand(rng);
+    let fingerprint = cks.fingerprint();
+    let other_fingerprint = other_cks.fingerprint();
+    assert_eq!(fingerprint, cks.fingerprint());
+    assert_ne!(fingerprint, other_fingerprint);
+
+    // Pinned fingerprint, i.e. the SHA-256 hash of the transcript encoding of the keys.
+    let (g1, g2) = (G1::generator().into_affine(), G2::generator().into_affine());
+    let [a1, a2, t1, t2] = [1u64, 2, 3, 4].map(Fr::from);
+    assert_eq!(
+        to_hex(&CommitmentKeys::<F>::from_trapdoor(g1, g2, a1, a2, t1, t2).fingerprint()),
+        "d4f7931780a4abe15f39a8ffe62d4e037b926063ad85fd3f3f087adb4f4cf39c"
+    );
+
+    let ps1 = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 1);
+    let ps2 = gs_ppe::test_utils::random_proof_system(rng, &cks, 1, 2);
+    let ps3 = gs_ppe::test_utils::random_proof_system(rng, &other_cks, 1, 1);
+    let stamped1 = StampedProofSystem::new(ps1.clone(), &cks);
+    let stamped2 = StampedProofSystem::new(ps2.clone(), &cks);
+    let stamped3 = StampedProofSystem::new(ps3, &other_cks);
+    assert_eq!(stamped1.verify(&cks), Ok(true));
+    assert_eq!(stamped3.verify(&other_cks), Ok(true));
+
+    let sum = stamped1.clone().try_add(stamped2).unwrap();
+    assert_eq!(sum.fingerprint, fingerprint);
+    assert_eq!(sum.verify(&cks), Ok(true));
+    assert_eq!(sum.into_proof_system(), ps1 + ps2);
+
+    // Systems from different CRSs are rejected instead of producing a system which fails to verify.
+    let mismatch = KeyMismatch {
+        expected: fingerprint,
+        found: other_fingerprint,
+    };
+    assert_eq!(stamped1.clone().try_add(stamped3.clone()), Err(mismatch));
+    assert_eq!(stamped3.verify(&cks), Err(mismatch));
+    assert!(mismatch.to_string().starts_with(&format!(
+        "commitment keys mismatch, expected fingerprint {}",
+        to_hex(&fingerprint)
+    )));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_linear_and_bilinear_terms() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let (m, n) = (2, 3);
+    let x = gs_ppe::test_utils::x_variable_vec::<F, _>(rng, m);
+    let y = gs_ppe::test_utils::y_variable_vec::<F, _>(rng, n);
+    let x_values = x.iter().map(|x_i| x_i.value).collect::<Vec<_>>();
+    let y_values = y.iter().map(|y_j| y_j.value).collect::<Vec<_>>();
+    let a = (0..n).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let b = (0..m).map(|_| G2Affine::rand(rng)).collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+    let equation =
+        Equation::<F>::from_witnesses(a.clone(), b.clone(), gamma.clone(), &x_values, &y_values);
+
+    let linear_g1 = equation.linear_g1_equation(&x_values);
+    let linear_g2 = equation.linear_g2_equation(&y_values);
+    let bilinear = equation.bilinear_equation(&x_values, &y_values);
+    let zero_gamma = Matrix::from_elem(m, n, Fr::zero());
+    let xb_target = x_values
+        .iter()
+        .zip(b.iter())
+        .map(|(x_i, b_i)| F::pairing(x_i, b_i))
+        .sum();
+    assert_eq!(
+        linear_g1,
+        Equation::new(vec![G1Affine::zero(); n], b, zero_gamma.clone(), xb_target)
+    );
+    let ay_target = a
+        .iter()
+        .zip(y_values.iter())
+        .map(|(a_j, y_j)| F::pairing(a_j, y_j))
+        .sum();
+    assert_eq!(
+        linear_g2,
+        Equation::new(a, vec![G2Affine::zero(); m], zero_gamma, ay_target)
+    );
+    assert_eq!(bilinear.dim(), (m, n));
+
+    // The sub-targets multiply to the target.
+    assert_eq!(
+        linear_g1.target().value() + linear_g2.target().value() + bilinear.target().value(),
+        equation.target().value()
+    );
+
+    // The proofs of the sub-equations verify independently under the same commitments.
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    for sub_equation in [&linear_g1, &linear_g2, &bilinear] {
+        let proof = Proof::new(rng, &cks, sub_equation, &x, &y);
+        assert!(sub_equation.verify(&cks, &c, &d, &proof));
+    }
+    let proof = Proof::new(rng, &cks, &linear_g1, &x, &y);
+    assert!(!linear_g2.verify(&cks, &c, &d, &proof));
+}
+
+#[test]
+fn test_matrix_sub_and_is_zero() {
+    let rng = &mut test_rng();
+    let m1 = Matrix::<Fr>::rand(rng, 2, 3);
+    let m2 = Matrix::<Fr>::rand(rng, 2, 3);
+    let diff = m1.clone() - m2.clone();
+    assert_eq!(diff.clone() + m2, m1);
+    assert!(!diff.is_zero());
+    assert!((m1.clone() - m1).is_zero());
+
+    // A single non-zero entry at any position.
+    for (i, j) in [(0, 0), (1, 2), (0, 2)] {
+        let mut single = Matrix::from_elem(2, 3, Fr::zero());
+        assert!(single.is_zero());
+        single[(i, j)] = Fr::from(5u64);
+        assert!(!single.is_zero());
+    }
+
+    // The degenerate shapes without entries are zero.
+    assert!(Matrix::<Fr>::empty().is_zero());
+    assert!(Matrix::<Fr>::empty_rows(3).is_zero());
+    assert!(Matrix::<Fr>::empty_cols(2).is_zero());
+    assert_eq!(
+        Matrix::<Fr>::empty_rows(3) - Matrix::<Fr>::empty_rows(3),
+        Matrix::empty_rows(3)
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_equation_is_linear() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    let (equation, x, y) = gs_ppe::test_utils::random_linear_instance::<F, _>(rng, 2, 3);
+    assert!(equation.is_linear());
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    // The fast path gives the same proof as the one computed from the non-zero entries of gamma.
+    let proof = Proof::new(&mut ChaCha20Rng::seed_from_u64(7), &cks, &equation, &x, &y);
+    let proof_sparse =
+        Proof::new_sparse(&mut ChaCha20Rng::seed_from_u64(7), &cks, &equation, &x, &y);
+    assert_eq!(proof, proof_sparse);
+    let other = gs_ppe::test_utils::random_proof_system(rng, &cks, 1, 1);
+    assert!(!equation.verify(&cks, &c, &d, &other.proof));
+
+    let (equation, ..) = gs_ppe::test_utils::random_instance::<F, _>(rng, 2, 3);
+    assert!(!equation.is_linear());
+    let mut gamma = Matrix::from_elem(2, 3, Fr::zero());
+    gamma[(1, 2)] = Fr::from(1u64);
+    let (equation, x, y) = gs_ppe::test_utils::random_instance_with_gamma::<F, _>(rng, gamma);
+    assert!(!equation.is_linear());
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+
+    // Equations without variables x or y are linear.
+    for (m, n) in [(0, 0), (2, 0), (0, 3)] {
+        let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, m, n);
+        assert!(equation.is_linear());
+        let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+        let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+        let proof = Proof::new(rng, &cks, &equation, &x, &y);
+        assert!(equation.verify(&cks, &c, &d, &proof));
+    }
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_proof_system_components() {
+    let rng = &mut test_rng();
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let proof_system = gs_ppe::test_utils::random_proof_system(rng, &cks, 2, 1);
+
+    let (equation, c, d, proof) = proof_system.components();
+    assert!(equation.verify(&cks, c, d, proof));
+    let borrowed = (equation.clone(), c.to_vec(), d.to_vec(), proof.clone());
+
+    let owned = proof_system.clone().into_components();
+    assert_eq!(owned, borrowed);
+    assert_eq!(
+        owned,
+        (
+            proof_system.equation,
+            proof_system.c,
+            proof_system.d,
+            proof_system.proof
+        )
+    );
+}
+
+#[test]
+fn test_com_sub_and_neg() {
+    let rng = &mut test_rng();
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+
+    let (r1, r2) = (Randomness::<G1>::rand(rng), Randomness::<G1>::rand(rng));
+    let (x1_value, x2_value) = (G1Affine::rand(rng), G1Affine::rand(rng));
+    let c1 = cks.u.commit(&Variable::with_randomness(x1_value, r1));
+    let c2 = cks.u.commit(&Variable::with_randomness(x2_value, r2));
+
+    assert!((c1 - c1).is_zero_commitment(&cks.u, &Randomness::zero()));
+    assert!(!c1.is_zero_commitment(&cks.u, &r1));
+    assert_eq!(-c1 + c1, c1 - c1);
+    assert_eq!(ek.extract_1(&-c1), (-x1_value).into_group());
+    assert_eq!(
+        ek.extract_1(&(c1 - c2)),
+        (x1_value - x2_value).into_affine()
+    );
+    let diff = c1 - c2;
+    assert!(diff.verify_opening(
+        &cks.u,
+        &Variable::with_randomness((x1_value - x2_value).into_affine(), r1 + (-r2))
+    ));
+
+    // The difference of two commitments to the same value is a commitment to zero.
+    let c3 = cks.u.commit(&Variable::with_randomness(x1_value, r2));
+    assert!((c1 - c3).is_zero_commitment(&cks.u, &(r1 + (-r2))));
+}
+
+#[test]
+fn test_randomness_gamma_t_product() {
+    let rng = &mut test_rng();
+    let (m, n) = (2, 3);
+    let r = (0..m)
+        .map(|_| Randomness::<G1>::rand(rng))
+        .collect::<Vec<_>>();
+    let s = (0..n)
+        .map(|_| Randomness::<G2>::rand(rng))
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+
+    let mut expected = (Fr::zero(), Fr::zero(), Fr::zero(), Fr::zero());
+    for i in 0..m {
+        for j in 0..n {
+            let (t11, t12, t21, t22) = Randomness::cross_mul(&r[i], &s[j]);
+            assert_eq!(
+                (t11, t12, t21, t22),
+                (
+                    r[i].0 * s[j].0,
+                    r[i].0 * s[j].1,
+                    r[i].1 * s[j].0,
+                    r[i].1 * s[j].1
+                )
+            );
+            expected.0 += gamma[(i, j)] * t11;
+            expected.1 += gamma[(i, j)] * t12;
+            expected.2 += gamma[(i, j)] * t21;
+            expected.3 += gamma[(i, j)] * t22;
+        }
+    }
+    assert_eq!(Randomness::gamma_t_product(&r, &s, &gamma), expected);
+
+    // Zero randomness in one group gives a zero product.
+    let zeros = vec![Randomness::<G2>::zero(); n];
+    assert_eq!(
+        Randomness::gamma_t_product(&r, &zeros, &gamma),
+        (Fr::zero(), Fr::zero(), Fr::zero(), Fr::zero())
+    );
+}
+
+#[cfg(feature = "bls12-381")]
+#[test]
+fn test_proof_bytes_compressed() {
+    let rng = &mut test_rng();
+    let (a, b) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x_value, y_value) = (G1Affine::rand(rng), G2Affine::rand(rng));
+    let (x, y) = (
+        Variable::<G1>::new(rng, x_value),
+        Variable::<G2>::new(rng, y_value),
+    );
+    let gamma = Matrix::<Fr>::rand(rng, 1, 1);
+    let cks = CommitmentKeys::<F>::rand(rng);
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = setup(rng, &cks, &[(a, y)], &[(x, b)], &gamma);
+
+    let bytes: [u8; 576] = proof.to_bytes_compressed();
+    // Same as the points in the transcript encoding, without the dimensions.
+    let transcript_bytes = proof.to_transcript_bytes();
+    assert_eq!(&bytes[..384], &transcript_bytes[29..29 + 384]);
+    assert_eq!(&bytes[384..], &transcript_bytes[29 + 384 + 16..]);
+
+    let proof_prime = Proof::<F>::from_bytes_compressed(&bytes).unwrap();
+    assert_eq!(proof_prime, proof);
+    assert!(equation.verify(&cks, &c, &d, &proof_prime));
+
+    let mut invalid = bytes;
+    invalid[400] ^= 0xff;
+    assert!(Proof::<F>::from_bytes_compressed(&invalid).is_err());
+}
+
+#[cfg(all(feature = "metrics", feature = "test-utils"))]
+#[test]
+fn test_metrics() {
+    let rng = &mut test_rng();
+    let (m, n) = (2, 3);
+    let ay = (0..n)
+        .map(|_| {
+            let y_value = G2Affine::rand(rng);
+            (G1Affine::rand(rng), Variable::<G2>::new(rng, y_value))
+        })
+        .collect::<Vec<_>>();
+    let xb = (0..m)
+        .map(|_| {
+            let x_value = G1Affine::rand(rng);
+            (Variable::<G1>::new(rng, x_value), G2Affine::rand(rng))
+        })
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+    let cks = CommitmentKeys::<F>::rand(rng);
+
+    gs_ppe::metrics::reset();
+    let proof_system = setup(rng, &cks, &ay, &xb, &gamma);
+    let metrics = gs_ppe::metrics::snapshot();
+    assert_eq!(metrics.commit.calls, m + n);
+    assert_eq!(metrics.commit.scalar_muls, 4 * (m + n));
+    assert_eq!(metrics.prove.calls, 1);
+    assert_eq!(metrics.prove.scalar_muls, 4 * m + 4 * n + 24);
+    assert_eq!(metrics.verify, Default::default());
+
+    let ProofSystem {
+        equation,
+        c,
+        d,
+        proof,
+    } = proof_system.randomize(rng, &cks);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    let metrics = gs_ppe::metrics::snapshot();
+    assert_eq!(metrics.randomize.calls, 1);
+    assert_eq!(metrics.randomize.scalar_muls, 6 * m + 6 * n + 24);
+    assert_eq!(metrics.verify.calls, 1);
+    assert_eq!(metrics.verify.pairings, 3 * m + 2 * n + 16);
+    assert_eq!(metrics.verify.scalar_muls, 3 * m * n);
+
+    // A linear equation skips the terms of gamma.
+    let (equation, x, y) = gs_ppe::test_utils::random_linear_instance::<F, _>(rng, m, n);
+    let c = x.iter().map(|x_i| cks.u.commit(x_i)).collect::<Vec<_>>();
+    let d = y.iter().map(|y_j| cks.v.commit(y_j)).collect::<Vec<_>>();
+    gs_ppe::metrics::reset();
+    let proof = Proof::new(rng, &cks, &equation, &x, &y);
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    let metrics = gs_ppe::metrics::snapshot();
+    assert_eq!(metrics.prove.scalar_muls, 2 * m + 2 * n + 24);
+    assert_eq!(metrics.verify.pairings, 2 * m + 2 * n + 16);
+    assert_eq!(metrics.verify.scalar_muls, 0);
+
+    // The streaming prover performs the same scalar multiplications as Proof::new.
+    gs_ppe::metrics::reset();
+    let proof = Proof::new_from_iter(rng, &cks, &equation, x.iter().copied(), y.iter().copied());
+    assert!(equation.verify(&cks, &c, &d, &proof));
+    assert_eq!(
+        gs_ppe::metrics::snapshot().prove.scalar_muls,
+        2 * m + 2 * n + 24
+    );
+    let (equation, x, y) = gs_ppe::test_utils::random_instance::<F, _>(rng, m, n);
+    gs_ppe::metrics::reset();
+    Proof::new_from_iter(rng, &cks, &equation, x.into_iter(), y.into_iter());
+    let metrics = gs_ppe::metrics::snapshot();
+    assert_eq!(metrics.prove.calls, 1);
+    assert_eq!(metrics.prove.scalar_muls, 4 * m + 4 * n + 24);
+
+    // The sparse prover skips the zero exponents, i.e. one exponent of each variable for a diagonal gamma.
+    let gamma = Matrix::new(&[[Fr::from(1u64), Fr::zero()], [Fr::zero(), Fr::from(2u64)]]);
+    let (equation, x, y) = gs_ppe::test_utils::random_instance_with_gamma::<F, _>(rng, gamma);
+    gs_ppe::metrics::reset();
+    Proof::new_sparse(rng, &cks, &equation, &x, &y);
+    assert_eq!(
+        gs_ppe::metrics::snapshot().prove.scalar_muls,
+        2 * 2 + 2 * 2 + 8 + 24
+    );
+
+    // The internal-only re-randomization.
+    gs_ppe::metrics::reset();
+    let mut rerandomized = proof.clone();
+    rerandomized.add_key_randomness(rng, &cks);
+    let metrics = gs_ppe::metrics::snapshot();
+    assert_eq!(metrics.randomize.calls, 1);
+    assert_eq!(metrics.randomize.scalar_muls, 16);
+
+    gs_ppe::metrics::reset();
+    assert_eq!(gs_ppe::metrics::snapshot(), Default::default());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_spans() {
+    use gs_ppe::ExtractableCommitments;
+    use std::sync::{Arc, Mutex};
+    use tracing::{field::Field, span::Attributes, Id, Subscriber};
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    /// The names and the fields of the spans.
+    type Spans = Vec<(&'static str, Vec<String>)>;
+
+    /// Records the name and the fields of every new span.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Spans>>);
+
+    impl<S: Subscriber> Layer<S> for SpanRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            let mut fields = Vec::new();
+            attrs.record(&mut |field: &Field, value: &dyn std::fmt::Debug| {
+                fields.push(format!("{}={:?}", field.name(), value))
+            });
+            self.0
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name(), fields));
+        }
+    }
+
+    let rng = &mut test_rng();
+    let (m, n) = (2, 3);
+    let keys = CommitmentKeys::<F>::rand_ex(rng);
+    let (cks, ek) = (keys.commitment_keys(), keys.extract_key());
+    let ay = (0..n)
+        .map(|_| {
+            let y_value = G2Affine::rand(rng);
+            (G1Affine::rand(rng), Variable::<G2>::new(rng, y_value))
+        })
+        .collect::<Vec<_>>();
+    let xb = (0..m)
+        .map(|_| {
+            let x_value = G1Affine::rand(rng);
+            (Variable::<G1>::new(rng, x_value), G2Affine::rand(rng))
+        })
+        .collect::<Vec<_>>();
+    let gamma = Matrix::<Fr>::rand(rng, m, n);
+    let x = xb.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+    let y = ay.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+
+    let recorder = SpanRecorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        let proof_system = setup(rng, cks, &ay, &xb, &gamma);
+        Proof::new(rng, cks, &proof_system.equation, &x, &y);
+        let ProofSystem {
+            equation,
+            c,
+            d,
+            proof,
+        } = proof_system.randomize(rng, cks);
+        assert!(equation.verify(cks, &c, &d, &proof));
+        ExtractableCommitments { c, d }.extract(ek);
+    });
+
+    let fields = vec!["m=2".to_string(), "n=3".to_string()];
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![
+            ("setup", fields.clone()),
+            ("Proof::new", fields.clone()),
+            ("ProofSystem::randomize", fields.clone()),
+            ("Equation::verify", fields.clone()),
+            ("ExtractableCommitments::extract", fields),
+        ]
+    );
+}